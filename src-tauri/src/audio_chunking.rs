@@ -0,0 +1,561 @@
+//! Audio chunking for recordings that exceed the transcription upload limit.
+//!
+//! WebM recordings produced by MediaRecorder are split at Cluster boundaries.
+//! Every chunk repeats the container header (EBML header, Segment Info and
+//! Tracks) so it can be decoded on its own, and cluster timecodes are rebased
+//! so that each chunk starts at zero.
+
+/// EBML element IDs used by the WebM parser
+const EBML_HEADER_ID: u32 = 0x1A45_DFA3;
+const SEGMENT_ID: u32 = 0x1853_8067;
+const INFO_ID: u32 = 0x1549_A966;
+const TRACKS_ID: u32 = 0x1654_AE6B;
+const CLUSTER_ID: u32 = 0x1F43_B675;
+const TIMECODE_ID: u32 = 0xE7;
+const TIMECODE_SCALE_ID: u32 = 0x2A_D7B1;
+const DURATION_ID: u32 = 0x4489;
+const SIMPLE_BLOCK_ID: u32 = 0xA3;
+
+/// Element IDs that may appear inside a Cluster
+/// (Timecode, SimpleBlock, BlockGroup, Position, PrevSize, SilentTracks, EncryptedBlock)
+const CLUSTER_CHILD_IDS: [u32; 7] = [0xE7, 0xA3, 0xA0, 0xA7, 0xAB, 0x5854, 0xAF];
+
+/// Segment header with an "unknown" size, so chunks can hold any number of clusters
+const UNKNOWN_SIZE_SEGMENT: [u8; 12] = [
+    0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+/// Default Matroska timecode scale (1 ms per tick)
+const DEFAULT_TIMECODE_SCALE_NS: u64 = 1_000_000;
+
+/// A self-contained piece of a longer recording
+#[derive(Debug, Clone)]
+pub struct AudioChunk {
+    /// Position of the chunk within the recording (0-based)
+    pub index: usize,
+    /// Start offset within the original recording in milliseconds
+    pub start_ms: u64,
+    /// Encoded audio data, decodable on its own
+    pub data: Vec<u8>,
+}
+
+/// A Cluster element located in the source file
+#[derive(Debug)]
+struct Cluster {
+    start: usize,
+    end: usize,
+    timecode: u64,
+    /// Offset and length of the Timecode value bytes (for rebasing)
+    timecode_field: Option<(usize, usize)>,
+    /// Timecode of the last SimpleBlock, relative to the cluster
+    last_block_timecode: Option<i16>,
+}
+
+/// Parsed layout of a WebM file
+#[derive(Debug)]
+struct WebmLayout {
+    header: Vec<u8>,
+    clusters: Vec<Cluster>,
+    timecode_scale_ns: u64,
+    /// Segment duration in timecode ticks, if the muxer wrote one
+    duration: Option<f64>,
+}
+
+/// Check whether the data starts with the EBML (WebM/Matroska) magic bytes
+pub fn is_webm(data: &[u8]) -> bool {
+    data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3])
+}
+
+/// Split a WebM recording into chunks of at most `max_chunk_bytes`
+///
+/// Consecutive chunks share roughly `overlap_ms` of audio so that words cut
+/// at a chunk boundary appear complete in at least one chunk. A single
+/// cluster larger than the limit still becomes its own chunk.
+pub fn split_webm(
+    data: &[u8],
+    max_chunk_bytes: usize,
+    overlap_ms: u64,
+) -> Result<Vec<AudioChunk>, String> {
+    let layout = parse_webm(data)?;
+    let clusters = &layout.clusters;
+    let to_ms = |timecode: u64| timecode * layout.timecode_scale_ns / 1_000_000;
+    let budget = max_chunk_bytes.saturating_sub(layout.header.len());
+
+    let mut chunks = Vec::new();
+    let mut start_idx = 0;
+
+    loop {
+        // Take as many clusters as fit into the size budget (at least one)
+        let mut end_idx = start_idx;
+        let mut size = 0;
+        while end_idx < clusters.len() {
+            let cluster_len = clusters[end_idx].end - clusters[end_idx].start;
+            if end_idx > start_idx && size + cluster_len > budget {
+                break;
+            }
+            size += cluster_len;
+            end_idx += 1;
+        }
+
+        chunks.push(AudioChunk {
+            index: chunks.len(),
+            start_ms: to_ms(clusters[start_idx].timecode),
+            data: build_chunk(data, &layout, &clusters[start_idx..end_idx]),
+        });
+
+        if end_idx >= clusters.len() {
+            break;
+        }
+
+        // Start the next chunk at the cluster containing the overlap point,
+        // always advancing by at least one cluster
+        let overlap_start = to_ms(clusters[end_idx].timecode).saturating_sub(overlap_ms);
+        start_idx = (start_idx + 1..=end_idx)
+            .rev()
+            .find(|&i| to_ms(clusters[i].timecode) <= overlap_start)
+            .unwrap_or(start_idx + 1);
+    }
+
+    Ok(chunks)
+}
+
+/// Duration of a WebM recording in seconds
+///
+/// MediaRecorder doesn't write the Segment duration, so it falls back to
+/// the timecode of the last audio block (which ignores that block's length).
+pub fn webm_duration_secs(data: &[u8]) -> Option<f64> {
+    let layout = parse_webm(data).ok()?;
+    let ticks = layout.duration.or_else(|| {
+        let last = layout.clusters.last()?;
+        let block = last.last_block_timecode.unwrap_or(0).max(0);
+        Some((last.timecode + block as u64) as f64)
+    })?;
+    Some(ticks * layout.timecode_scale_ns as f64 / 1e9)
+}
+
+/// Assemble a standalone WebM file from the header and a run of clusters
+fn build_chunk(data: &[u8], layout: &WebmLayout, clusters: &[Cluster]) -> Vec<u8> {
+    let base_timecode = clusters.first().map(|c| c.timecode).unwrap_or(0);
+    let size: usize = clusters.iter().map(|c| c.end - c.start).sum();
+
+    let mut chunk = Vec::with_capacity(layout.header.len() + size);
+    chunk.extend_from_slice(&layout.header);
+
+    for cluster in clusters {
+        let offset = chunk.len();
+        chunk.extend_from_slice(&data[cluster.start..cluster.end]);
+
+        // Rebase the cluster timecode in place (the smaller value always fits);
+        // clusters of malformed files may go back in time and start at zero
+        if let Some((field_start, field_len)) = cluster.timecode_field {
+            let value = cluster.timecode.saturating_sub(base_timecode);
+            let field = offset + (field_start - cluster.start);
+            for i in 0..field_len {
+                let shift = 8 * (field_len - 1 - i);
+                chunk[field + i] = if shift < 64 {
+                    (value >> shift) as u8
+                } else {
+                    0
+                };
+            }
+        }
+    }
+
+    chunk
+}
+
+/// Locate the header elements and clusters of a WebM file
+fn parse_webm(data: &[u8]) -> Result<WebmLayout, String> {
+    let invalid = || "Invalid WebM data".to_string();
+
+    // EBML header
+    let (id, id_len) = read_id(data, 0).ok_or_else(invalid)?;
+    if id != EBML_HEADER_ID {
+        return Err("Not a WebM file".to_string());
+    }
+    let (size, size_len) = read_size(data, id_len).ok_or_else(invalid)?;
+    let ebml_end = id_len + size_len + size.ok_or_else(invalid)? as usize;
+    if ebml_end > data.len() {
+        return Err(invalid());
+    }
+
+    let mut header = data[..ebml_end].to_vec();
+    header.extend_from_slice(&UNKNOWN_SIZE_SEGMENT);
+
+    // Segment
+    let (id, id_len) = read_id(data, ebml_end).ok_or_else(invalid)?;
+    if id != SEGMENT_ID {
+        return Err("WebM segment not found".to_string());
+    }
+    let (size, size_len) = read_size(data, ebml_end + id_len).ok_or_else(invalid)?;
+    let segment_start = ebml_end + id_len + size_len;
+    let segment_end = size
+        .map(|s| (segment_start + s as usize).min(data.len()))
+        .unwrap_or(data.len());
+
+    let mut clusters = Vec::new();
+    let mut timecode_scale_ns = DEFAULT_TIMECODE_SCALE_NS;
+    let mut duration = None;
+    let mut pos = segment_start;
+
+    while pos < segment_end {
+        let Some((id, id_len)) = read_id(data, pos) else {
+            break;
+        };
+        let Some((size, size_len)) = read_size(data, pos + id_len) else {
+            break;
+        };
+        let data_start = pos + id_len + size_len;
+
+        if id == CLUSTER_ID {
+            let limit = size
+                .map(|s| (data_start + s as usize).min(segment_end))
+                .unwrap_or(segment_end);
+            let cluster = parse_cluster(data, pos, data_start, limit, size.is_some());
+            pos = cluster.end;
+            clusters.push(cluster);
+            continue;
+        }
+
+        // Other top-level elements must have a known size
+        let end = (data_start + size.ok_or_else(invalid)? as usize).min(segment_end);
+        match id {
+            INFO_ID => {
+                timecode_scale_ns = find_uint(data, data_start, end, TIMECODE_SCALE_ID)
+                    .filter(|&s| s > 0)
+                    .unwrap_or(DEFAULT_TIMECODE_SCALE_NS);
+                duration = find_float(data, data_start, end, DURATION_ID);
+                header.extend_from_slice(&data[pos..end]);
+            }
+            TRACKS_ID => header.extend_from_slice(&data[pos..end]),
+            // SeekHead, Cues, Tags and Void reference absolute positions or are optional
+            _ => {}
+        }
+        pos = end;
+    }
+
+    if clusters.is_empty() {
+        return Err("No audio clusters found in WebM data".to_string());
+    }
+
+    Ok(WebmLayout {
+        header,
+        clusters,
+        timecode_scale_ns,
+        duration,
+    })
+}
+
+/// Scan a cluster's children to find its timecode and (for unknown sizes) its end
+fn parse_cluster(
+    data: &[u8],
+    start: usize,
+    data_start: usize,
+    limit: usize,
+    known_size: bool,
+) -> Cluster {
+    let mut timecode = 0;
+    let mut timecode_field = None;
+    let mut last_block_timecode = None;
+    let mut pos = data_start;
+
+    while pos < limit {
+        let Some((id, id_len)) = read_id(data, pos) else {
+            break;
+        };
+        if !CLUSTER_CHILD_IDS.contains(&id) {
+            break;
+        }
+        let Some((Some(size), size_len)) = read_size(data, pos + id_len) else {
+            break;
+        };
+        let value_start = pos + id_len + size_len;
+        // A size field running past the cluster means the file is truncated
+        if value_start > limit {
+            break;
+        }
+        let value_end = value_start.saturating_add(size as usize).min(limit);
+        if id == TIMECODE_ID {
+            timecode = read_uint(&data[value_start..value_end]);
+            timecode_field = Some((value_start, value_end - value_start));
+        } else if id == SIMPLE_BLOCK_ID {
+            // Track number (EBML varint) followed by a signed 16-bit timecode
+            if let Some((_, track_len)) = read_size(data, value_start) {
+                let field = value_start + track_len;
+                if field + 2 <= value_end {
+                    last_block_timecode = Some(i16::from_be_bytes([data[field], data[field + 1]]));
+                }
+            }
+        }
+        pos = value_end;
+    }
+
+    Cluster {
+        start,
+        end: if known_size { limit } else { pos },
+        timecode,
+        timecode_field,
+        last_block_timecode,
+    }
+}
+
+/// Find an unsigned integer child element within a master element
+fn find_uint(data: &[u8], start: usize, end: usize, target: u32) -> Option<u64> {
+    let mut pos = start;
+    while pos < end {
+        let (id, id_len) = read_id(data, pos)?;
+        let (size, size_len) = read_size(data, pos + id_len)?;
+        let value_start = pos + id_len + size_len;
+        if value_start > end {
+            return None;
+        }
+        let value_end = value_start.saturating_add(size? as usize).min(end);
+        if id == target {
+            return Some(read_uint(&data[value_start..value_end]));
+        }
+        pos = value_end;
+    }
+    None
+}
+
+/// Find a float child element (4 or 8 bytes) within a master element
+fn find_float(data: &[u8], start: usize, end: usize, target: u32) -> Option<f64> {
+    let mut pos = start;
+    while pos < end {
+        let (id, id_len) = read_id(data, pos)?;
+        let (size, size_len) = read_size(data, pos + id_len)?;
+        let value_start = pos + id_len + size_len;
+        if value_start > end {
+            return None;
+        }
+        let value_end = value_start.saturating_add(size? as usize).min(end);
+        if id == target {
+            let bytes = &data[value_start..value_end];
+            return match bytes.len() {
+                4 => Some(f64::from(f32::from_be_bytes(bytes.try_into().ok()?))),
+                8 => Some(f64::from_be_bytes(bytes.try_into().ok()?)),
+                _ => None,
+            };
+        }
+        pos = value_end;
+    }
+    None
+}
+
+/// Read an EBML element ID (1-4 bytes, marker bits retained)
+fn read_id(data: &[u8], pos: usize) -> Option<(u32, usize)> {
+    let first = *data.get(pos)?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 4 || pos + len > data.len() {
+        return None;
+    }
+    let id = data[pos..pos + len]
+        .iter()
+        .fold(0u32, |acc, &b| (acc << 8) | u32::from(b));
+    Some((id, len))
+}
+
+/// Read an EBML size (1-8 bytes); `None` size means "unknown"
+fn read_size(data: &[u8], pos: usize) -> Option<(Option<u64>, usize)> {
+    let first = *data.get(pos)?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 || pos + len > data.len() {
+        return None;
+    }
+    let mut value = u64::from(first) & (0xFF >> len);
+    for &b in &data[pos + 1..pos + len] {
+        value = (value << 8) | u64::from(b);
+    }
+    let unknown = (1u64 << (7 * len)) - 1;
+    Some((if value == unknown { None } else { Some(value) }, len))
+}
+
+/// Read a big-endian unsigned integer of up to 8 bytes
+fn read_uint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b))
+}
+
+/// Build a minimal WebM file with one audio block per cluster (for tests)
+#[cfg(test)]
+pub(crate) fn build_test_webm(cluster_timecodes_ms: &[u64], block_size: usize) -> Vec<u8> {
+    fn element(id: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.push(0x01); // 8-byte size
+        out.extend_from_slice(&(payload.len() as u64).to_be_bytes()[1..]);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    let mut data = element(&[0x1A, 0x45, 0xDF, 0xA3], &element(&[0x42, 0x82], b"webm"));
+    data.extend_from_slice(&UNKNOWN_SIZE_SEGMENT);
+    data.extend(element(
+        &[0x15, 0x49, 0xA9, 0x66],
+        &element(&[0x2A, 0xD7, 0xB1], &[0x0F, 0x42, 0x40]),
+    ));
+    data.extend(element(
+        &[0x16, 0x54, 0xAE, 0x6B],
+        &element(&[0xAE], &element(&[0xD7], &[0x01])),
+    ));
+    for &timecode in cluster_timecodes_ms {
+        let mut cluster = element(&[0xE7], &timecode.to_be_bytes()[4..]);
+        cluster.extend(element(&[0xA3], &vec![0xAB; block_size]));
+        data.extend(element(&[0x1F, 0x43, 0xB6, 0x75], &cluster));
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_webm_detects_magic_bytes() {
+        assert!(is_webm(&build_test_webm(&[0], 10)));
+        assert!(!is_webm(b"RIFF\x00\x00\x00\x00WAVE"));
+        assert!(!is_webm(&[]));
+    }
+
+    #[test]
+    fn test_parse_webm_finds_all_clusters() {
+        let data = build_test_webm(&[0, 5_000, 10_000], 100);
+        let layout = parse_webm(&data).unwrap();
+
+        assert_eq!(layout.clusters.len(), 3);
+        assert_eq!(layout.clusters[1].timecode, 5_000);
+        assert_eq!(layout.timecode_scale_ns, 1_000_000);
+        assert_eq!(layout.clusters.last().unwrap().end, data.len());
+    }
+
+    #[test]
+    fn test_parse_webm_rejects_other_formats() {
+        assert!(parse_webm(b"OggS\x00\x02").is_err());
+        assert!(parse_webm(&[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_webm_survives_truncated_elements() {
+        let data = build_test_webm(&[0, 5_000], 100);
+        let position = |id: [u8; 4]| data.windows(4).rposition(|w| w == id).unwrap();
+
+        // The last cluster ends inside the size field of its Timecode child
+        let mut truncated = data.clone();
+        let cluster = position([0x1F, 0x43, 0xB6, 0x75]);
+        truncated[cluster + 5..cluster + 12].copy_from_slice(&5u64.to_be_bytes()[1..]);
+        let layout = parse_webm(&truncated).unwrap();
+        assert_eq!(layout.clusters.len(), 2);
+        assert!(split_webm(&truncated, 1024 * 1024, 0).is_ok());
+
+        // Info ends inside the size field of TimecodeScale
+        let mut truncated = data.clone();
+        let info = position([0x15, 0x49, 0xA9, 0x66]);
+        truncated[info + 5..info + 12].copy_from_slice(&4u64.to_be_bytes()[1..]);
+        assert_eq!(
+            find_uint(&truncated, info + 12, info + 16, TIMECODE_SCALE_ID),
+            None
+        );
+        assert!(parse_webm(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_split_webm_small_file_returns_single_chunk() {
+        let data = build_test_webm(&[0, 5_000], 100);
+        let chunks = split_webm(&data, 1024 * 1024, 5_000).unwrap();
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].start_ms, 0);
+    }
+
+    #[test]
+    fn test_split_webm_respects_size_limit_and_overlap() {
+        let timecodes: Vec<u64> = (0..10).map(|i| i * 5_000).collect();
+        let data = build_test_webm(&timecodes, 1_000);
+        let layout = parse_webm(&data).unwrap();
+        let max = layout.header.len() + 4 * 1_050;
+
+        let chunks = split_webm(&data, max, 5_000).unwrap();
+
+        for chunk in &chunks {
+            assert!(chunk.data.len() <= max);
+            assert!(is_webm(&chunk.data));
+        }
+        // Each chunk after the first starts one cluster (5 s) before the previous end
+        assert_eq!(chunks[0].start_ms, 0);
+        let starts: Vec<u64> = chunks.iter().map(|c| c.start_ms).collect();
+        assert_eq!(starts, vec![0, 15_000, 30_000]);
+    }
+
+    #[test]
+    fn test_split_webm_chunks_are_standalone_with_rebased_timecodes() {
+        let timecodes: Vec<u64> = (0..6).map(|i| i * 5_000).collect();
+        let data = build_test_webm(&timecodes, 1_000);
+        let layout = parse_webm(&data).unwrap();
+
+        let chunks = split_webm(&data, layout.header.len() + 3 * 1_050, 0).unwrap();
+        assert_eq!(chunks.len(), 2);
+
+        let second = parse_webm(&chunks[1].data).unwrap();
+        assert_eq!(second.clusters.len(), 3);
+        assert_eq!(second.clusters[0].timecode, 0);
+        assert_eq!(second.clusters[2].timecode, 10_000);
+        assert_eq!(chunks[1].start_ms, 15_000);
+    }
+
+    #[test]
+    fn test_split_webm_tolerates_out_of_order_clusters() {
+        let data = build_test_webm(&[0, 5_000, 10_000, 15_000, 3_000, 20_000], 1_000);
+        let layout = parse_webm(&data).unwrap();
+
+        let chunks = split_webm(&data, layout.header.len() + 3 * 1_050, 0).unwrap();
+        assert_eq!(chunks.len(), 2);
+
+        // The cluster before the first one of its chunk is clamped to zero
+        let second = parse_webm(&chunks[1].data).unwrap();
+        let timecodes: Vec<u64> = second.clusters.iter().map(|c| c.timecode).collect();
+        assert_eq!(timecodes, vec![0, 0, 5_000]);
+        assert_eq!(chunks[1].start_ms, 15_000);
+    }
+
+    #[test]
+    fn test_read_size_handles_unknown_marker() {
+        assert_eq!(read_size(&[0x81], 0), Some((Some(1), 1)));
+        assert_eq!(read_size(&[0x40, 0x02], 0), Some((Some(2), 2)));
+        assert_eq!(read_size(&[0xFF], 0), Some((None, 1)));
+        assert_eq!(read_size(&UNKNOWN_SIZE_SEGMENT[4..], 0), Some((None, 8)));
+    }
+
+    #[test]
+    fn test_webm_duration_from_last_cluster() {
+        let data = build_test_webm(&[0, 1000, 2500], 10);
+        assert_eq!(webm_duration_secs(&data), Some(2.5));
+        assert_eq!(webm_duration_secs(b"not webm"), None);
+    }
+
+    #[test]
+    fn test_find_float_rejects_truncated_element() {
+        // Duration with an 8-byte size field, in a parent ending after 4 bytes
+        let data = [0x44, 0x89, 0x01, 0, 0, 0, 0, 0, 0, 0x08, 0, 0];
+        assert_eq!(find_float(&data, 0, 4, DURATION_ID), None);
+        assert_eq!(find_float(&data, 0, 3, DURATION_ID), None);
+    }
+
+    #[test]
+    fn test_webm_duration_prefers_segment_info() {
+        // Append a Duration (8-byte float, 4000 ticks) to the Info element
+        let data = build_test_webm(&[0, 1000], 10);
+        let info = data
+            .windows(4)
+            .position(|w| w == [0x15, 0x49, 0xA9, 0x66])
+            .unwrap();
+        let info_end = info + 12 + read_uint(&data[info + 5..info + 12]) as usize;
+        let mut patched = data[..info].to_vec();
+        let mut payload = data[info + 12..info_end].to_vec();
+        payload.extend_from_slice(&[0x44, 0x89, 0x88]);
+        payload.extend_from_slice(&4000f64.to_be_bytes());
+        patched.extend_from_slice(&[0x15, 0x49, 0xA9, 0x66, 0x01]);
+        patched.extend_from_slice(&(payload.len() as u64).to_be_bytes()[1..]);
+        patched.extend_from_slice(&payload);
+        patched.extend_from_slice(&data[info_end..]);
+
+        assert_eq!(webm_duration_secs(&patched), Some(4.0));
+    }
+}
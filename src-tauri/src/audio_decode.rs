@@ -0,0 +1,295 @@
+//! Audio decoding to mono samples, and silence trimming before transcription.
+//!
+//! Recordings are decoded with Symphonia and downmixed to mono. Symphonia
+//! has no Opus decoder, so Opus tracks (the WebM recordings MediaRecorder
+//! produces on most platforms) go through libopus and can only be decoded
+//! in builds with the `transcode` feature.
+
+use crate::audio_format::AudioFormat;
+use crate::vad::{self, SilenceRange};
+use std::io::Cursor;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Opus always decodes at 48 kHz (and counts Ogg granules at that rate)
+pub const OPUS_SAMPLE_RATE: u32 = 48_000;
+
+/// Largest Opus frame (120 ms at 48 kHz)
+#[cfg(feature = "transcode")]
+const MAX_OPUS_FRAME_SAMPLES: usize = 5_760;
+
+/// Sample rate of transcoded and trimmed audio (wideband speech)
+pub const SPEECH_SAMPLE_RATE: u32 = 16_000;
+
+/// Encoded audio with silences cut out, its format, and the removed ranges
+pub type TrimmedAudio = (Vec<u8>, AudioFormat, Vec<SilenceRange>);
+
+/// Cut long silences out of a recording before transcription (see `vad`)
+///
+/// The remaining audio is encoded as 16 kHz mono Opus/OGG, or as 16-bit WAV
+/// if `lossless` (for on-device transcription, where size doesn't matter) or
+/// the app was built without the `transcode` feature. Returns it with the
+/// removed ranges, or `None` if there is nothing to remove.
+pub fn trim_silence(
+    data: &[u8],
+    extension: Option<&str>,
+    lossless: bool,
+) -> Result<Option<TrimmedAudio>, String> {
+    let (samples, sample_rate) = decode_to_mono(data, extension)?;
+    let samples = resample(&samples, sample_rate, SPEECH_SAMPLE_RATE);
+    let (kept, removed) = match vad::remove_silence(&samples, SPEECH_SAMPLE_RATE) {
+        Some(trimmed) => trimmed,
+        None => return Ok(None),
+    };
+
+    let (encoded, format) = if lossless {
+        (wav_file(&kept, SPEECH_SAMPLE_RATE), AudioFormat::Wav)
+    } else {
+        encode_for_upload(&kept)?
+    };
+    Ok(Some((encoded, format, removed)))
+}
+
+/// Encode trimmed speech for upload as Opus/OGG
+#[cfg(feature = "transcode")]
+fn encode_for_upload(samples: &[f32]) -> Result<(Vec<u8>, AudioFormat), String> {
+    let encoded = crate::audio_transcode::encode_ogg_opus(samples)?;
+    Ok((encoded, AudioFormat::Ogg))
+}
+
+/// Encode trimmed speech for upload
+///
+/// Always WAV: the app was built without the `transcode` feature.
+#[cfg(not(feature = "transcode"))]
+fn encode_for_upload(samples: &[f32]) -> Result<(Vec<u8>, AudioFormat), String> {
+    Ok((wav_file(samples, SPEECH_SAMPLE_RATE), AudioFormat::Wav))
+}
+
+/// Decoder for the recording's audio track
+enum AudioDecoder {
+    Symphonia(Box<dyn Decoder>),
+    #[cfg(feature = "transcode")]
+    Opus(audiopus::coder::Decoder),
+}
+
+/// Decode a recording to mono samples, returned with their sample rate
+///
+/// `extension` (e.g. "webm") helps pick the container format.
+pub fn decode_to_mono(data: &[u8], extension: Option<&str>) -> Result<(Vec<f32>, u32), String> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(data.to_vec())), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("Unsupported audio container: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "Recording has no audio track".to_string())?;
+    let track_id = track.id;
+
+    let mut decoder = if track.codec_params.codec == CODEC_TYPE_OPUS {
+        opus_decoder()?
+    } else {
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| format!("Unsupported audio codec: {}", e))?;
+        AudioDecoder::Symphonia(decoder)
+    };
+
+    let mut samples = Vec::new();
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(OPUS_SAMPLE_RATE);
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // End of stream
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(format!("Failed to read audio: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match &mut decoder {
+            #[cfg(feature = "transcode")]
+            AudioDecoder::Opus(decoder) => {
+                sample_rate = OPUS_SAMPLE_RATE;
+                let mut frame = [0f32; MAX_OPUS_FRAME_SAMPLES];
+                let input = audiopus::packet::Packet::try_from(&packet.data[..])
+                    .map_err(|e| format!("Invalid Opus packet: {}", e))?;
+                let output = audiopus::MutSignals::try_from(&mut frame[..])
+                    .map_err(|e| format!("Invalid Opus buffer: {}", e))?;
+                let decoded = decoder
+                    .decode_float(Some(input), output, false)
+                    .map_err(|e| format!("Failed to decode Opus audio: {}", e))?;
+                samples.extend_from_slice(&frame[..decoded]);
+            }
+            AudioDecoder::Symphonia(decoder) => match decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    sample_rate = spec.rate;
+                    let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                    buffer.copy_interleaved_ref(decoded);
+                    samples.extend(downmix(buffer.samples(), spec.channels.count()));
+                }
+                // Skip corrupt packets instead of failing the whole recording
+                Err(SymphoniaError::DecodeError(e)) => log::warn!("Skipping audio packet: {}", e),
+                Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+            },
+        }
+    }
+
+    Ok((samples, sample_rate))
+}
+
+/// Decoder for Opus tracks, which Symphonia can't decode
+#[cfg(feature = "transcode")]
+fn opus_decoder() -> Result<AudioDecoder, String> {
+    let decoder =
+        audiopus::coder::Decoder::new(audiopus::SampleRate::Hz48000, audiopus::Channels::Mono)
+            .map_err(|e| format!("Failed to create Opus decoder: {}", e))?;
+    Ok(AudioDecoder::Opus(decoder))
+}
+
+/// Opus tracks need libopus, which the app was built without
+#[cfg(not(feature = "transcode"))]
+fn opus_decoder() -> Result<AudioDecoder, String> {
+    Err("Opus audio can't be decoded without the `transcode` feature".to_string())
+}
+
+/// Average interleaved channels into mono
+fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Resample mono audio with linear interpolation
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let step = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / step).floor() as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let current = samples[index];
+            let next = samples.get(index + 1).copied().unwrap_or(current);
+            current + (next - current) * fraction
+        })
+        .collect()
+}
+
+/// Encode mono samples as a 16-bit PCM WAV file
+pub fn wav_file(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + samples.len() * 2);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+        out.extend_from_slice(&sample.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_duration::probe_duration;
+    use crate::fixtures;
+
+    #[test]
+    fn test_downmix_averages_channels() {
+        assert_eq!(downmix(&[1.0, 0.0, 0.5, 0.5], 2), vec![0.5, 0.5]);
+        assert_eq!(downmix(&[0.25, 0.75], 1), vec![0.25, 0.75]);
+    }
+
+    #[test]
+    fn test_resample_interpolates() {
+        assert_eq!(resample(&[0.0, 1.0, 0.0, 1.0], 16_000, 16_000).len(), 4);
+        // Downsampling by three keeps every third sample
+        assert_eq!(
+            resample(&[0.0, 0.1, 0.2, 0.3, 0.4, 0.5], 48_000, 16_000),
+            vec![0.0, 0.3]
+        );
+        // Upsampling by two interpolates between neighbours
+        assert_eq!(
+            resample(&[0.0, 1.0], 8_000, 16_000),
+            vec![0.0, 0.5, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_trim_silence() {
+        let samples = [
+            vec![0.0; 48_000],
+            fixtures::tone(24_000, 2.0, 300.0, 0.3),
+            vec![0.0; 72_000],
+        ]
+        .concat();
+        let wav = wav_file(&samples, 24_000);
+
+        let (trimmed, format, removed) = trim_silence(&wav, Some("wav"), true).unwrap().unwrap();
+        assert_eq!(format, AudioFormat::Wav);
+        assert_eq!(AudioFormat::detect(&trimmed), Some(AudioFormat::Wav));
+        let duration = probe_duration(AudioFormat::Wav, &trimmed).unwrap();
+        assert!((duration - 2.5).abs() < 0.05, "{}", duration);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(removed[0].start, 0.0);
+        assert!((removed[0].end - 1.75).abs() < 0.05, "{:?}", removed);
+        assert_eq!(removed[1].end, 7.0);
+
+        // Uploads are compressed when libopus is available
+        let (trimmed, format, _) = trim_silence(&wav, Some("wav"), false).unwrap().unwrap();
+        let expected = if cfg!(feature = "transcode") {
+            AudioFormat::Ogg
+        } else {
+            AudioFormat::Wav
+        };
+        assert_eq!(format, expected);
+        assert_eq!(AudioFormat::detect(&trimmed), Some(expected));
+
+        // Nothing to remove from continuous audio
+        let wav = wav_file(&fixtures::tone(24_000, 2.0, 300.0, 0.3), 24_000);
+        assert!(trim_silence(&wav, Some("wav"), true).unwrap().is_none());
+    }
+}
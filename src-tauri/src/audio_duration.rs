@@ -0,0 +1,332 @@
+//! Duration probing for imported audio files.
+//!
+//! The duration is read from container headers rather than by decoding, so
+//! it works without the local transcription decoder and stays fast for long
+//! recordings. Containers without a stored duration are estimated from their
+//! last timestamp (WebM, Ogg) or bitrate (CBR MP3).
+
+use crate::audio_chunking::webm_duration_secs;
+use crate::audio_format::{skip_id3_tag, AudioFormat};
+
+/// Duration of an audio file in seconds, if it can be determined
+pub fn probe_duration(format: AudioFormat, data: &[u8]) -> Option<f64> {
+    let duration = match format {
+        AudioFormat::WebM => webm_duration_secs(data),
+        AudioFormat::Mp3 => mp3_duration(data),
+        AudioFormat::M4a => mp4_duration(data),
+        AudioFormat::Wav => wav_duration(data),
+        AudioFormat::Ogg => ogg_duration(data),
+        AudioFormat::Flac => flac_duration(skip_id3_tag(data).unwrap_or(data)),
+    }?;
+    Some(duration).filter(|d| d.is_finite() && *d >= 0.0)
+}
+
+/// WAV: size of the `data` chunk divided by the byte rate from `fmt `
+fn wav_duration(data: &[u8]) -> Option<f64> {
+    let mut byte_rate = None;
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let id = &data[pos..pos + 4];
+        let size = u32_le(data, pos + 4)? as usize;
+        let body = pos + 8;
+        if id == b"fmt " {
+            byte_rate = u32_le(data, body + 8);
+        } else if id == b"data" {
+            // Streaming writers leave the size at 0 or 0xFFFFFFFF
+            let available = data.len() - body;
+            let size = if size == 0 || size > available {
+                available
+            } else {
+                size
+            };
+            return Some(size as f64 / f64::from(byte_rate.filter(|&r| r > 0)?));
+        }
+        // Chunks are padded to an even size
+        pos = body + size + (size & 1);
+    }
+    None
+}
+
+/// FLAC: total samples and sample rate from the STREAMINFO block
+fn flac_duration(data: &[u8]) -> Option<f64> {
+    // "fLaC", a 4-byte block header, then STREAMINFO with the sample rate
+    // (20 bits), channels (3), bits per sample (5) and total samples (36)
+    // packed into the 8 bytes at offset 10
+    if !data.starts_with(b"fLaC") || data.get(4)? & 0x7F != 0 {
+        return None;
+    }
+    let packed = u64::from_be_bytes(data.get(18..26)?.try_into().ok()?);
+    let sample_rate = packed >> 44;
+    let total_samples = packed & 0xF_FFFF_FFFF;
+    if sample_rate == 0 || total_samples == 0 {
+        return None;
+    }
+    Some(total_samples as f64 / sample_rate as f64)
+}
+
+/// MPEG-4: duration and timescale from the `mvhd` box inside `moov`
+fn mp4_duration(data: &[u8]) -> Option<f64> {
+    let moov = find_box(data, b"moov")?;
+    let mvhd = find_box(moov, b"mvhd")?;
+    let (timescale, duration) = match mvhd.first()? {
+        0 => (u32_be(mvhd, 12)?, u64::from(u32_be(mvhd, 16)?)),
+        1 => (
+            u32_be(mvhd, 20)?,
+            u64::from_be_bytes(mvhd.get(24..32)?.try_into().ok()?),
+        ),
+        _ => return None,
+    };
+    if timescale == 0 {
+        return None;
+    }
+    Some(duration as f64 / f64::from(timescale))
+}
+
+/// Find a box among the children in `data` and return its payload
+fn find_box<'a>(data: &'a [u8], name: &[u8; 4]) -> Option<&'a [u8]> {
+    let mut pos = 0;
+    while pos + 8 <= data.len() {
+        let (header, size) = match u32_be(data, pos)? {
+            // 64-bit size follows the type
+            1 => (
+                16,
+                u64::from_be_bytes(data.get(pos + 8..pos + 16)?.try_into().ok()?),
+            ),
+            // Box extends to the end of its parent
+            0 => (8, (data.len() - pos) as u64),
+            size => (8, u64::from(size)),
+        };
+        let end = pos
+            .checked_add(usize::try_from(size).ok()?)?
+            .min(data.len());
+        if end < pos + header {
+            return None;
+        }
+        if &data[pos + 4..pos + 8] == name {
+            return Some(&data[pos + header..end]);
+        }
+        pos = end;
+    }
+    None
+}
+
+/// Ogg: granule position of the last page, at the rate of the first stream
+fn ogg_duration(data: &[u8]) -> Option<f64> {
+    // The first page holds the codec identification header
+    let segments = usize::from(*data.get(26)?);
+    let packet = data.get(27 + segments..)?;
+    let (sample_rate, pre_skip) = if packet.starts_with(b"OpusHead") {
+        // Opus granules always count 48 kHz samples, including the pre-skip
+        (
+            48_000,
+            u16::from_le_bytes(packet.get(10..12)?.try_into().ok()?),
+        )
+    } else if packet.starts_with(b"\x01vorbis") {
+        (u32_le(packet, 12)?, 0)
+    } else {
+        return None;
+    };
+
+    let last_page = data.windows(4).rposition(|w| w == b"OggS")?;
+    let granule = i64::from_le_bytes(data.get(last_page + 6..last_page + 14)?.try_into().ok()?);
+    if granule < 0 || sample_rate == 0 {
+        return None;
+    }
+    let samples = (granule - i64::from(pre_skip)).max(0);
+    Some(samples as f64 / f64::from(sample_rate))
+}
+
+/// MP3: frame count from a Xing/Info header, or an estimate from the bitrate
+fn mp3_duration(data: &[u8]) -> Option<f64> {
+    let audio = skip_id3_tag(data).unwrap_or(data);
+    let frame = Mp3Frame::parse(audio)?;
+
+    if let Some(frames) = frame.xing_frame_count(audio) {
+        return Some(
+            f64::from(frames) * f64::from(frame.samples_per_frame) / f64::from(frame.sample_rate),
+        );
+    }
+
+    // Constant bitrate; an ID3v1 tag at the end is not audio
+    let mut audio_len = audio.len();
+    if audio_len >= 128 && audio[audio_len - 128..].starts_with(b"TAG") {
+        audio_len -= 128;
+    }
+    Some(audio_len as f64 * 8.0 / f64::from(frame.bitrate))
+}
+
+/// Fields of an MPEG audio frame header needed for the duration
+struct Mp3Frame {
+    bitrate: u32,
+    sample_rate: u32,
+    samples_per_frame: u32,
+    /// Offset of the Xing/Info header from the frame start
+    xing_offset: usize,
+}
+
+impl Mp3Frame {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let [0xFF, second, third, fourth, ..] = *data else {
+            return None;
+        };
+        if second & 0xE0 != 0xE0 {
+            return None;
+        }
+        // Version: 0 = MPEG 2.5, 2 = MPEG 2, 3 = MPEG 1; layer: 1 = III, 2 = II, 3 = I
+        let version = (second >> 3) & 0b11;
+        let layer = (second >> 1) & 0b11;
+        let mpeg1 = version == 0b11;
+        let mono = fourth >> 6 == 0b11;
+
+        const BITRATES_V1: [[u32; 16]; 3] = [
+            [
+                0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0,
+            ],
+            [
+                0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 0,
+            ],
+            [
+                0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448, 0,
+            ],
+        ];
+        const BITRATES_V2: [[u32; 16]; 2] = [
+            [
+                0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0,
+            ],
+            [
+                0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256, 0,
+            ],
+        ];
+        let bitrate_index = usize::from(third >> 4);
+        let bitrate_kbps = match (mpeg1, layer) {
+            (_, 0) => return None,
+            (true, layer) => BITRATES_V1[usize::from(layer) - 1][bitrate_index],
+            (false, 3) => BITRATES_V2[1][bitrate_index],
+            (false, _) => BITRATES_V2[0][bitrate_index],
+        };
+
+        let base_rate = match (third >> 2) & 0b11 {
+            0 => 44_100,
+            1 => 48_000,
+            2 => 32_000,
+            _ => return None,
+        };
+        let sample_rate = match version {
+            0b11 => base_rate,
+            0b10 => base_rate / 2,
+            0b00 => base_rate / 4,
+            _ => return None,
+        };
+
+        let samples_per_frame = match (layer, mpeg1) {
+            (3, _) => 384,
+            (1, false) => 576,
+            _ => 1152,
+        };
+        // The Xing header follows the side information
+        let side_info = match (mpeg1, mono) {
+            (true, false) => 32,
+            (true, true) | (false, false) => 17,
+            (false, true) => 9,
+        };
+
+        (bitrate_kbps > 0).then_some(Mp3Frame {
+            bitrate: bitrate_kbps * 1000,
+            sample_rate,
+            samples_per_frame,
+            xing_offset: 4 + side_info,
+        })
+    }
+
+    /// Number of frames from a VBR (Xing) or CBR (Info) header, if present
+    fn xing_frame_count(&self, data: &[u8]) -> Option<u32> {
+        let header = data.get(self.xing_offset..)?;
+        if !header.starts_with(b"Xing") && !header.starts_with(b"Info") {
+            return None;
+        }
+        let flags = u32_be(header, 4)?;
+        if flags & 0x1 == 0 {
+            return None;
+        }
+        u32_be(header, 8).filter(|&frames| frames > 0)
+    }
+}
+
+fn u32_le(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn u32_be(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("duration");
+        assert!(
+            (actual - expected).abs() < 0.001,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_probes_fixture_durations() {
+        let cases = [
+            ("silence.wav", AudioFormat::Wav, 0.05),
+            ("silence.flac", AudioFormat::Flac, 0.01),
+            ("silence.ogg", AudioFormat::Ogg, 0.02),
+            // 1251 bytes of 128 kbit/s CBR audio
+            ("silence.mp3", AudioFormat::Mp3, 0.0782),
+        ];
+        for (name, format, expected) in cases {
+            assert_close(probe_duration(format, &fixtures::audio(name)), expected);
+        }
+    }
+
+    #[test]
+    fn test_mp4_duration_from_movie_header() {
+        let mut data = fixtures::audio("silence.m4a");
+        assert_close(probe_duration(AudioFormat::M4a, &data), 0.0);
+
+        // Set the mvhd duration to 2.5 s at its 16 kHz timescale
+        let mvhd = data.windows(4).position(|w| w == b"mvhd").unwrap();
+        data[mvhd + 20..mvhd + 24].copy_from_slice(&40_000u32.to_be_bytes());
+        assert_close(probe_duration(AudioFormat::M4a, &data), 2.5);
+    }
+
+    #[test]
+    fn test_mp3_duration_from_xing_header() {
+        let mut data = fixtures::audio("silence.mp3");
+        let frame = data.windows(2).position(|w| w == [0xFF, 0xFB]).unwrap();
+        // Mono MPEG-1: the Info header starts after 4 + 17 bytes
+        let header = frame + 21;
+        data[header..header + 4].copy_from_slice(b"Info");
+        data[header + 4..header + 8].copy_from_slice(&1u32.to_be_bytes());
+        data[header + 8..header + 12].copy_from_slice(&100u32.to_be_bytes());
+
+        assert_close(
+            probe_duration(AudioFormat::Mp3, &data),
+            100.0 * 1152.0 / 44_100.0,
+        );
+    }
+
+    #[test]
+    fn test_webm_duration() {
+        let data = crate::audio_chunking::build_test_webm(&[0, 1000, 2000], 10);
+        assert_close(probe_duration(AudioFormat::WebM, &data), 2.0);
+    }
+
+    #[test]
+    fn test_unknown_duration() {
+        assert_eq!(probe_duration(AudioFormat::Wav, b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(probe_duration(AudioFormat::Flac, b"fLaC"), None);
+        assert_eq!(probe_duration(AudioFormat::Ogg, b"OggS"), None);
+        assert_eq!(probe_duration(AudioFormat::Mp3, b"ID3"), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! Audio container detection from magic bytes.
+//!
+//! Recordings are WebM, but imported files may be any container accepted by
+//! the Whisper API. The format is sniffed from the file content rather than
+//! trusted from the extension, so uploads carry a matching MIME type and
+//! file name and unsupported files are rejected before any request is made.
+
+use crate::audio_chunking::is_webm;
+
+/// Audio container supported for transcription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    WebM,
+    Mp3,
+    /// MPEG-4 audio (AAC or ALAC in an `ftyp` container)
+    M4a,
+    Wav,
+    Ogg,
+    Flac,
+}
+
+/// Supported formats for error messages
+pub const SUPPORTED_FORMATS: &str = "WebM, MP3, M4A, WAV, OGG and FLAC";
+
+/// All supported formats
+const ALL_FORMATS: [AudioFormat; 6] = [
+    AudioFormat::WebM,
+    AudioFormat::Mp3,
+    AudioFormat::M4a,
+    AudioFormat::Wav,
+    AudioFormat::Ogg,
+    AudioFormat::Flac,
+];
+
+impl AudioFormat {
+    /// Detect the container from the first bytes of a file
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if is_webm(data) {
+            Some(AudioFormat::WebM)
+        } else if data.starts_with(b"fLaC") {
+            Some(AudioFormat::Flac)
+        } else if data.starts_with(b"OggS") {
+            Some(AudioFormat::Ogg)
+        } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WAVE") {
+            Some(AudioFormat::Wav)
+        } else if data.get(4..8) == Some(b"ftyp") {
+            Some(AudioFormat::M4a)
+        } else if let Some(rest) = skip_id3_tag(data) {
+            // FLAC files may also start with an ID3 tag
+            Some(match AudioFormat::detect(rest) {
+                Some(AudioFormat::Flac) => AudioFormat::Flac,
+                _ => AudioFormat::Mp3,
+            })
+        } else if is_mpeg_audio_frame(data) {
+            Some(AudioFormat::Mp3)
+        } else {
+            None
+        }
+    }
+
+    /// Look up a format by file extension (case-insensitive)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_ascii_lowercase();
+        match extension.as_str() {
+            "mp4" | "aac" => Some(AudioFormat::M4a),
+            "oga" | "opus" => Some(AudioFormat::Ogg),
+            "wave" => Some(AudioFormat::Wav),
+            _ => ALL_FORMATS
+                .into_iter()
+                .find(|format| format.extension() == extension),
+        }
+    }
+
+    /// MIME type sent with uploads
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            AudioFormat::WebM => "audio/webm",
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::M4a => "audio/mp4",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Ogg => "audio/ogg",
+            AudioFormat::Flac => "audio/flac",
+        }
+    }
+
+    /// Canonical file extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::WebM => "webm",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Flac => "flac",
+        }
+    }
+}
+
+/// Return the data after a leading ID3v2 tag, if there is one
+pub(crate) fn skip_id3_tag(data: &[u8]) -> Option<&[u8]> {
+    if !data.starts_with(b"ID3") || data.len() < 10 {
+        return None;
+    }
+    // The tag size is a 28-bit "syncsafe" integer (7 bits per byte)
+    let size = data[6..10]
+        .iter()
+        .fold(0usize, |size, &byte| (size << 7) | usize::from(byte & 0x7F));
+    let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(data.get(10 + size + footer..).unwrap_or_default())
+}
+
+/// Whether the data starts with an MPEG audio frame header (Layer I-III)
+///
+/// ADTS AAC shares the 11-bit frame sync but uses layer `00`, which is
+/// rejected here since Whisper only accepts AAC in an MP4 container.
+fn is_mpeg_audio_frame(data: &[u8]) -> bool {
+    match data {
+        [0xFF, second, third, ..] => {
+            let sync = second & 0xE0 == 0xE0;
+            let version = (second >> 3) & 0b11;
+            let layer = (second >> 1) & 0b11;
+            let bitrate = third >> 4;
+            let sample_rate = (third >> 2) & 0b11;
+            sync && version != 0b01 && layer != 0 && bitrate != 0xF && sample_rate != 0b11
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_detects_fixtures() {
+        let cases = [
+            ("silence.webm", AudioFormat::WebM),
+            ("silence.mp3", AudioFormat::Mp3),
+            ("silence.m4a", AudioFormat::M4a),
+            ("silence.wav", AudioFormat::Wav),
+            ("silence.ogg", AudioFormat::Ogg),
+            ("silence.flac", AudioFormat::Flac),
+        ];
+        for (name, format) in cases {
+            assert_eq!(
+                AudioFormat::detect(&fixtures::audio(name)),
+                Some(format),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_detects_mp3_without_id3_tag() {
+        let data = fixtures::audio("silence.mp3");
+        let first_frame = data.windows(2).position(|w| w == [0xFF, 0xFB]).unwrap();
+        assert_eq!(
+            AudioFormat::detect(&data[first_frame..]),
+            Some(AudioFormat::Mp3)
+        );
+    }
+
+    #[test]
+    fn test_detects_flac_behind_id3_tag() {
+        let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x00".to_vec();
+        data.extend(fixtures::audio("silence.flac"));
+        assert_eq!(AudioFormat::detect(&data), Some(AudioFormat::Flac));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_data() {
+        let adts_aac = [0xFF, 0xF1, 0x50, 0x80, 0x02, 0x1F, 0xFC];
+        let avi = b"RIFF\x00\x00\x00\x00AVI LIST";
+        for data in [&adts_aac[..], &avi[..], b"%PDF-1.7", b"", b"ID3"] {
+            assert_eq!(AudioFormat::detect(data), None, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn test_from_extension() {
+        assert_eq!(AudioFormat::from_extension("MP3"), Some(AudioFormat::Mp3));
+        assert_eq!(AudioFormat::from_extension("mp4"), Some(AudioFormat::M4a));
+        assert_eq!(AudioFormat::from_extension("opus"), Some(AudioFormat::Ogg));
+        assert_eq!(AudioFormat::from_extension("webm"), Some(AudioFormat::WebM));
+        assert_eq!(AudioFormat::from_extension("txt"), None);
+    }
+
+    #[test]
+    fn test_mime_types_and_extensions() {
+        for format in ALL_FORMATS {
+            assert!(format.mime_type().starts_with("audio/"));
+            assert_eq!(
+                AudioFormat::from_extension(format.extension()),
+                Some(format)
+            );
+        }
+    }
+}
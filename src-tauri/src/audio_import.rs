@@ -0,0 +1,180 @@
+//! Import of existing audio files (e.g. phone or meeting recordings) into history.
+//!
+//! Imported files are placed in the recordings directory like new
+//! recordings, so deleting the history item never touches the original.
+
+use crate::audio_duration::probe_duration;
+use crate::audio_format::{AudioFormat, SUPPORTED_FORMATS};
+use crate::file_storage::generate_recording_filename;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// How an imported file is placed in the recordings directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// Copy the file
+    #[default]
+    Copy,
+    /// Hard-link the file to save disk space, copying it if linking fails
+    /// (e.g. when the file is on another drive)
+    Link,
+}
+
+/// An audio file placed in the recordings directory
+#[derive(Debug, Clone)]
+pub struct ImportedAudio {
+    /// Full path of the file in the recordings directory
+    pub file_path: String,
+    /// Duration in seconds (0 if it can't be read from the file)
+    pub duration_seconds: f64,
+    /// Title for the history item (the original file name without extension)
+    pub title: String,
+    /// When the original file was last modified (ISO 8601), used as the creation date
+    pub created_at: String,
+    /// Whether the file was hard-linked rather than copied
+    pub linked: bool,
+}
+
+/// Validate an audio file and place it in `recordings_dir`
+///
+/// The format is detected from the file content; files that aren't in a
+/// supported format are rejected before anything is written.
+pub fn import_audio_file(
+    source: &Path,
+    recordings_dir: &Path,
+    mode: ImportMode,
+) -> Result<ImportedAudio, String> {
+    let metadata = fs::metadata(source).map_err(|e| format!("Failed to read file: {}", e))?;
+    if !metadata.is_file() {
+        return Err("Not a file".to_string());
+    }
+    let data = fs::read(source).map_err(|e| format!("Failed to read file: {}", e))?;
+    import_audio_data(source, &data, &metadata, recordings_dir, mode)
+}
+
+/// Like `import_audio_file`, for a file that has already been read
+pub fn import_audio_data(
+    source: &Path,
+    data: &[u8],
+    metadata: &fs::Metadata,
+    recordings_dir: &Path,
+    mode: ImportMode,
+) -> Result<ImportedAudio, String> {
+    let format = AudioFormat::detect(data).ok_or_else(|| {
+        format!(
+            "Unsupported file type, expected one of: {}",
+            SUPPORTED_FORMATS
+        )
+    })?;
+
+    let duration_seconds = probe_duration(format, data).unwrap_or_else(|| {
+        log::warn!("Could not read the duration of {}", source.display());
+        0.0
+    });
+
+    let target = recordings_dir.join(generate_recording_filename(format.extension()));
+    let linked = mode == ImportMode::Link && fs::hard_link(source, &target).is_ok();
+    if !linked {
+        fs::write(&target, data).map_err(|e| format!("Failed to write recording file: {}", e))?;
+    }
+
+    let file_path = target
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Invalid file path encoding".to_string())?;
+    let title = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let created_at = metadata
+        .modified()
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .unwrap_or_else(|_| chrono::Utc::now())
+        .to_rfc3339();
+
+    Ok(ImportedAudio {
+        file_path,
+        duration_seconds,
+        title,
+        created_at,
+        linked,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_copies_file_with_duration_and_title() {
+        let dir = fixtures::temp_dir("import");
+        let source = fixtures::audio_path("silence.wav");
+
+        let imported = import_audio_file(&source, &dir, ImportMode::Copy).unwrap();
+
+        assert!(!imported.linked);
+        assert_eq!(imported.title, "silence");
+        assert!((imported.duration_seconds - 0.05).abs() < 1e-9);
+        assert!(imported.file_path.ends_with(".wav"));
+        assert!(Path::new(&imported.file_path).starts_with(&dir));
+        assert_eq!(
+            fs::read(&imported.file_path).unwrap(),
+            fs::read(&source).unwrap()
+        );
+        assert!(chrono::DateTime::parse_from_rfc3339(&imported.created_at).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_links_file_on_the_same_file_system() {
+        let dir = fixtures::temp_dir("import");
+        let source = dir.join("Meeting notes.mp3");
+        fs::copy(fixtures::audio_path("silence.mp3"), &source).unwrap();
+        let recordings = dir.join("recordings");
+        fs::create_dir(&recordings).unwrap();
+
+        let imported = import_audio_file(&source, &recordings, ImportMode::Link).unwrap();
+
+        assert!(imported.linked);
+        assert_eq!(imported.title, "Meeting notes");
+        assert!(imported.file_path.ends_with(".mp3"));
+        assert_eq!(
+            fs::read(&imported.file_path).unwrap(),
+            fs::read(&source).unwrap()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_uses_detected_format_for_extension() {
+        let dir = fixtures::temp_dir("import");
+        let source = dir.join("voice memo.bin");
+        fs::copy(fixtures::audio_path("silence.flac"), &source).unwrap();
+
+        let imported = import_audio_file(&source, &dir, ImportMode::Copy).unwrap();
+
+        assert!(imported.file_path.ends_with(".flac"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_unsupported_and_missing_files() {
+        let dir = fixtures::temp_dir("import");
+        let source = dir.join("notes.txt");
+        fs::write(&source, "not audio").unwrap();
+        let recordings = dir.join("recordings");
+        fs::create_dir(&recordings).unwrap();
+
+        let error = import_audio_file(&source, &recordings, ImportMode::Copy).unwrap_err();
+        assert!(error.contains("Unsupported file type"), "{}", error);
+        assert_eq!(fs::read_dir(&recordings).unwrap().count(), 0);
+
+        let missing = dir.join("missing.mp3");
+        assert!(import_audio_file(&missing, &recordings, ImportMode::Copy).is_err());
+        assert!(import_audio_file(&dir, &recordings, ImportMode::Copy).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
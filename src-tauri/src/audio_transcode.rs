@@ -0,0 +1,250 @@
+//! Opus/OGG encoding (`transcode` feature).
+//!
+//! Recordings are decoded to mono (see `audio_decode`) and, for storage and
+//! upload, re-encoded as 16 kHz mono Opus in an Ogg container: MediaRecorder
+//! output differs between platforms (WebM/Opus, MP4/AAC) and is often several
+//! times larger than speech needs. Opus is encoded with libopus as there is no
+//! pure-Rust encoder; the Ogg pages are written here.
+
+use crate::audio_decode::{decode_to_mono, resample, OPUS_SAMPLE_RATE, SPEECH_SAMPLE_RATE};
+
+/// Opus bitrate of transcoded audio in bits per second; keeps the longest
+/// allowed recording (180 minutes) under the 25 MB upload limit
+const OPUS_BITRATE: i32 = 16_000;
+
+/// Samples per 20 ms Opus frame at the speech sample rate
+const FRAME_SAMPLES: usize = 320;
+
+/// Largest Opus packet recommended by libopus
+const MAX_PACKET_BYTES: usize = 4_000;
+
+/// Opus packets per Ogg page (one second of audio)
+const PACKETS_PER_PAGE: usize = 50;
+
+/// Serial number of the single Ogg stream in transcoded files
+const OGG_SERIAL: u32 = 0x4556_4F49;
+
+/// Ogg page flags
+const OGG_FIRST_PAGE: u8 = 0x02;
+const OGG_LAST_PAGE: u8 = 0x04;
+
+/// Transcode a recording to 16 kHz mono Opus in an Ogg container
+///
+/// `extension` (e.g. "webm") helps pick the container format.
+pub fn transcode_to_ogg_opus(data: &[u8], extension: Option<&str>) -> Result<Vec<u8>, String> {
+    let (samples, sample_rate) = decode_to_mono(data, extension)?;
+    if samples.is_empty() {
+        return Err("Recording contains no audio".to_string());
+    }
+    encode_ogg_opus(&resample(&samples, sample_rate, SPEECH_SAMPLE_RATE))
+}
+
+/// Encode 16 kHz mono samples as Ogg Opus
+pub fn encode_ogg_opus(samples: &[f32]) -> Result<Vec<u8>, String> {
+    let opus_error = |e: audiopus::Error| format!("Failed to encode Opus audio: {}", e);
+    let mut encoder = audiopus::coder::Encoder::new(
+        audiopus::SampleRate::Hz16000,
+        audiopus::Channels::Mono,
+        audiopus::Application::Voip,
+    )
+    .map_err(opus_error)?;
+    encoder
+        .set_bitrate(audiopus::Bitrate::BitsPerSecond(OPUS_BITRATE))
+        .map_err(opus_error)?;
+    let lookahead = encoder.lookahead().map_err(opus_error)? as usize;
+
+    // The encoder delays its output by `lookahead` samples, so that much
+    // silence is appended to flush the end of the recording; players skip
+    // the delay at the start (pre-skip) and trim the padding at the end
+    let padded_len = samples.len() + lookahead;
+    let mut packets = Vec::with_capacity(padded_len / FRAME_SAMPLES + 1);
+    let mut output = [0u8; MAX_PACKET_BYTES];
+    for start in (0..padded_len).step_by(FRAME_SAMPLES) {
+        let mut frame = [0f32; FRAME_SAMPLES];
+        let end = samples.len().min(start + FRAME_SAMPLES);
+        if start < end {
+            frame[..end - start].copy_from_slice(&samples[start..end]);
+        }
+        let len = encoder
+            .encode_float(&frame, &mut output)
+            .map_err(opus_error)?;
+        packets.push(output[..len].to_vec());
+    }
+
+    let pre_skip = lookahead * (OPUS_SAMPLE_RATE / SPEECH_SAMPLE_RATE) as usize;
+    Ok(ogg_opus_file(&packets, pre_skip as u16, samples.len()))
+}
+
+/// Wrap 20 ms Opus packets at 16 kHz in an Ogg file
+///
+/// `sample_count` is the number of input samples, used to trim the padding
+/// of the last packet.
+fn ogg_opus_file(packets: &[Vec<u8>], pre_skip: u16, sample_count: usize) -> Vec<u8> {
+    let scale = u64::from(OPUS_SAMPLE_RATE / SPEECH_SAMPLE_RATE);
+
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&SPEECH_SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+
+    let vendor = b"EverVoice";
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+
+    let mut writer = OggWriter::default();
+    writer.page(OGG_FIRST_PAGE, 0, &[&head]);
+    writer.page(0, 0, &[&tags]);
+
+    // Pages end with whole packets and hold at most 255 lacing values
+    let mut page: Vec<&[u8]> = Vec::new();
+    let mut segments = 0;
+    for (index, packet) in packets.iter().enumerate() {
+        let packet_segments = packet.len() / 255 + 1;
+        if page.len() == PACKETS_PER_PAGE || segments + packet_segments > 255 {
+            let granule = index as u64 * FRAME_SAMPLES as u64 * scale;
+            writer.page(0, granule, &page);
+            page.clear();
+            segments = 0;
+        }
+        page.push(packet);
+        segments += packet_segments;
+    }
+    let end_granule = u64::from(pre_skip) + sample_count as u64 * scale;
+    writer.page(OGG_LAST_PAGE, end_granule, &page);
+
+    writer.out
+}
+
+/// Writes the pages of a single Ogg stream
+#[derive(Default)]
+struct OggWriter {
+    out: Vec<u8>,
+    sequence: u32,
+}
+
+impl OggWriter {
+    /// Append a page holding whole `packets`, the last ending at `granule`
+    fn page(&mut self, flags: u8, granule: u64, packets: &[&[u8]]) {
+        let start = self.out.len();
+        self.out.extend_from_slice(b"OggS");
+        self.out.push(0); // version
+        self.out.push(flags);
+        self.out.extend_from_slice(&granule.to_le_bytes());
+        self.out.extend_from_slice(&OGG_SERIAL.to_le_bytes());
+        self.out.extend_from_slice(&self.sequence.to_le_bytes());
+        self.out.extend_from_slice(&[0; 4]); // checksum, filled in below
+
+        // Each packet is split into 255-byte segments plus a shorter final one
+        let mut lacing = Vec::new();
+        for packet in packets {
+            lacing.resize(lacing.len() + packet.len() / 255, 255);
+            lacing.push((packet.len() % 255) as u8);
+        }
+        self.out.push(lacing.len() as u8);
+        self.out.extend_from_slice(&lacing);
+        for packet in packets {
+            self.out.extend_from_slice(packet);
+        }
+
+        let checksum = ogg_crc(&self.out[start..]);
+        self.out[start + 22..start + 26].copy_from_slice(&checksum.to_le_bytes());
+        self.sequence += 1;
+    }
+}
+
+/// CRC-32 of an Ogg page (polynomial 0x04C11DB7, not reflected)
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ (u32::from(byte) << 24), |crc, _| {
+            if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_decode::wav_file;
+    use crate::audio_duration::probe_duration;
+    use crate::audio_format::AudioFormat;
+    use crate::fixtures;
+    use std::io::Cursor;
+    use symphonia::core::codecs::CODEC_TYPE_OPUS;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    /// Read the Opus packets back with Symphonia's Ogg demuxer
+    fn demux_opus_packets(data: &[u8]) -> Vec<Vec<u8>> {
+        let source =
+            MediaSourceStream::new(Box::new(Cursor::new(data.to_vec())), Default::default());
+        let mut format = symphonia::default::get_probe()
+            .format(
+                &Hint::new(),
+                source,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .unwrap()
+            .format;
+        assert_eq!(format.tracks()[0].codec_params.codec, CODEC_TYPE_OPUS);
+
+        let mut packets = Vec::new();
+        while let Ok(packet) = format.next_packet() {
+            packets.push(packet.data.to_vec());
+        }
+        packets
+    }
+
+    #[test]
+    fn test_ogg_opus_file_round_trip() {
+        // 2.5 s of 20 ms packets; the last one is partly padding
+        let packets: Vec<Vec<u8>> = (0..126u8).map(|i| vec![0xF8, i, i]).collect();
+        let data = ogg_opus_file(&packets, 312, 40_000);
+
+        assert_eq!(AudioFormat::detect(&data), Some(AudioFormat::Ogg));
+        assert_eq!(probe_duration(AudioFormat::Ogg, &data), Some(2.5));
+        assert_eq!(demux_opus_packets(&data), packets);
+    }
+
+    #[test]
+    fn test_ogg_pages_split_large_packets() {
+        // 600-byte packets take three lacing values each
+        let packets: Vec<Vec<u8>> = (0..200u8).map(|i| vec![i; 600]).collect();
+        let data = ogg_opus_file(&packets, 0, 200 * FRAME_SAMPLES);
+
+        assert_eq!(demux_opus_packets(&data), packets);
+    }
+
+    #[test]
+    fn test_ogg_crc_matches_reference() {
+        // Checksum of the first page of silence.ogg
+        let fixture = fixtures::audio("silence.ogg");
+        let page_len = 27 + 1 + usize::from(fixture[27]);
+        let mut page = fixture[..page_len].to_vec();
+        let expected = u32::from_le_bytes(page[22..26].try_into().unwrap());
+        page[22..26].copy_from_slice(&[0; 4]);
+        assert_eq!(ogg_crc(&page), expected);
+    }
+
+    #[test]
+    fn test_transcode_wav_to_ogg_opus() {
+        let wav = wav_file(&fixtures::tone(8_000, 1.0, 440.0, 0.25), 8_000);
+        let data = transcode_to_ogg_opus(&wav, Some("wav")).unwrap();
+
+        assert_eq!(AudioFormat::detect(&data), Some(AudioFormat::Ogg));
+        let duration = probe_duration(AudioFormat::Ogg, &data).unwrap();
+        assert!((duration - 1.0).abs() < 1e-6, "{}", duration);
+        assert!(transcode_to_ogg_opus(b"not audio at all", None).is_err());
+    }
+}
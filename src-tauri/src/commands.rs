@@ -1,27 +1,63 @@
+use crate::audio_duration::probe_duration;
+use crate::audio_format::AudioFormat;
+use crate::audio_import::{import_audio_file, ImportMode, ImportedAudio};
+use crate::export::{export_to_file, ExportFormat};
+use crate::external_service::{
+    call_custom_action_impl, validate_url, ActionContext, ExternalServiceError,
+    ExternalServiceResponse, ReqwestClient,
+};
 use crate::file_storage;
-use crate::history::{sort_history_descending, truncate_history, HistoryItem};
-use crate::settings::Settings;
-use crate::summarization::{summarize_text, SummarizationError, SummarizationResult};
-use crate::transcription::{transcribe_audio_file, TranscriptionError, TranscriptionResult};
-use tauri::AppHandle;
+use crate::history::{HistoryItem, PipelineRun};
+use crate::history_repository::{HistoryRepository, HistorySearchQuery, HistorySearchResult};
+use crate::jobs::{Job, JobError, JobKind, JobManager, JobOutput};
+use crate::local_models::{self, LocalModelInfo};
+use crate::pending_queue::{PendingQueue, PendingTranscription};
+use crate::pipeline::{run_pipeline, StepRunner};
+use crate::secret_store::{self, SecretStore, OPENAI_API_KEY, TRANSCRIPTION_API_KEY};
+use crate::settings::{CustomAction, Pipeline, PipelineStep, ResponseAction, Settings};
+use crate::summarization::{
+    generate_title, summarize_text, summarize_text_streaming, PromptContext, SummarizationError,
+    SummarizationOptions, SummarizationResult, TitleResult,
+};
+use crate::transcription::{
+    provider_from_settings, transcribe_audio_file, transcribe_audio_file_with_progress,
+    TranscriptionError, TranscriptionResult, TranscriptionSegment,
+};
+use crate::vad::SilenceRange;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_store::StoreExt;
 
 /// The settings store file name
 const SETTINGS_STORE_FILE: &str = "settings.json";
 /// The key used to store settings in the store
 const SETTINGS_KEY: &str = "settings";
-/// The key used to store history in the store
+/// The legacy key history was stored under before moving to the history database
 const HISTORY_KEY: &str = "history";
 
+/// Event emitted with each piece of a streamed summary
+const SUMMARY_CHUNK_EVENT: &str = "summary-chunk";
+
+/// Event emitted once a streamed summary has finished or failed
+const SUMMARY_COMPLETE_EVENT: &str = "summary-complete";
+
+/// Event emitted with the `Job` whenever a background job changes
+pub(crate) const JOB_UPDATED_EVENT: &str = "job-updated";
+
+/// Event emitted while a local transcription model is downloading
+const MODEL_DOWNLOAD_PROGRESS_EVENT: &str = "model-download-progress";
+
 /// Retrieves settings from the store, returning defaults if not found.
 /// Automatically migrates old settings formats by re-saving with all fields.
+///
+/// The API keys and custom action credentials are never returned in full;
+/// they hold a masked hint (e.g., "••••••••3xyz") when configured.
 #[tauri::command]
-pub fn get_settings(app: AppHandle) -> Result<Settings, String> {
+pub fn get_settings(app: AppHandle, secrets: State<'_, SecretStore>) -> Result<Settings, String> {
     let store = app
         .store(SETTINGS_STORE_FILE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
-    match store.get(SETTINGS_KEY) {
+    let mut settings = match store.get(SETTINGS_KEY) {
         Some(value) => {
             // Try to parse settings - serde(default) will fill in missing fields
             let settings: Settings = serde_json::from_value(value.clone())
@@ -33,15 +69,29 @@ pub fn get_settings(app: AppHandle) -> Result<Settings, String> {
             store.set(SETTINGS_KEY, updated_value);
             let _ = store.save(); // Ignore save errors during migration
 
-            Ok(settings)
+            settings
         }
-        None => Ok(Settings::default()),
+        None => Settings::default(),
+    };
+
+    for (name, secret) in settings_secrets_mut(&mut settings) {
+        *secret =
+            resolve_secret(&secrets, &name, secret.take()).map(|s| secret_store::mask_secret(&s));
     }
+    Ok(settings)
 }
 
 /// Saves settings to the store after validation
+///
+/// The API keys and custom action credentials go to the secret store instead
+/// of the settings file: a new value replaces the stored one, the masked hint
+/// from `get_settings` keeps it, and an empty value removes it.
 #[tauri::command]
-pub fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
+pub fn save_settings(
+    app: AppHandle,
+    secrets: State<'_, SecretStore>,
+    settings: Settings,
+) -> Result<(), String> {
     // Validate settings before saving
     settings.validate()?;
 
@@ -49,8 +99,35 @@ pub fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
         .store(SETTINGS_STORE_FILE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
-    let value = serde_json::to_value(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    save_secret(&secrets, OPENAI_API_KEY, settings.api_key.as_deref())?;
+    // Removed when switching to a provider without a key of its own
+    save_secret(
+        &secrets,
+        TRANSCRIPTION_API_KEY,
+        settings
+            .transcription_provider
+            .api_key()
+            .and_then(|key| key.as_deref()),
+    )?;
+    for action in &settings.custom_actions {
+        for (name, secret) in action_secrets(action) {
+            save_secret(&secrets, &name, secret)?;
+        }
+    }
+
+    // Forget credentials of actions that were removed
+    let previous: Option<Settings> = store
+        .get(SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok());
+    for old in previous.iter().flat_map(|p| &p.custom_actions) {
+        if !settings.custom_actions.iter().any(|a| a.id == old.id) {
+            for (name, _) in action_secrets(old) {
+                save_secret(&secrets, &name, None)?;
+            }
+        }
+    }
+
+    let value = stored_settings_value(&settings)?;
 
     store.set(SETTINGS_KEY, value);
 
@@ -61,82 +138,190 @@ pub fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
     Ok(())
 }
 
-// ============================================================================
-// History Commands
-// ============================================================================
+/// Serializes settings for the settings store, leaving out all secrets
+fn stored_settings_value(settings: &Settings) -> Result<serde_json::Value, String> {
+    let mut settings = settings.clone();
+    for (_, secret) in settings_secrets_mut(&mut settings) {
+        *secret = None;
+    }
+    serde_json::to_value(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+}
 
-/// Retrieves all history items from the store, ordered by createdAt DESC (newest first)
-#[tauri::command]
-pub fn get_history(app: AppHandle) -> Result<Vec<HistoryItem>, String> {
+/// All secrets of the settings with their secret store names
+fn settings_secrets_mut(settings: &mut Settings) -> Vec<(String, &mut Option<String>)> {
+    let mut secrets = vec![(OPENAI_API_KEY.to_string(), &mut settings.api_key)];
+    if let Some(api_key) = settings.transcription_provider.api_key_mut() {
+        secrets.push((TRANSCRIPTION_API_KEY.to_string(), api_key));
+    }
+    for action in &mut settings.custom_actions {
+        secrets.extend(action_secrets_mut(action));
+    }
+    secrets
+}
+
+/// Secret store names and submitted values of a custom action's credentials
+///
+/// The auth entry is `None` when the action doesn't authenticate, so a token
+/// left over from an earlier configuration is removed on save.
+fn action_secrets(action: &CustomAction) -> [(String, Option<&str>); 2] {
+    [
+        (
+            secret_store::custom_action_secret(&action.id),
+            action.auth.secret().and_then(|secret| secret.as_deref()),
+        ),
+        (
+            secret_store::custom_action_signing_secret(&action.id),
+            action.signing_secret.as_deref(),
+        ),
+    ]
+}
+
+/// Credentials of a custom action with their secret store names
+fn action_secrets_mut(action: &mut CustomAction) -> Vec<(String, &mut Option<String>)> {
+    let mut secrets = vec![(
+        secret_store::custom_action_signing_secret(&action.id),
+        &mut action.signing_secret,
+    )];
+    if let Some(secret) = action.auth.secret_mut() {
+        secrets.push((secret_store::custom_action_secret(&action.id), secret));
+    }
+    secrets
+}
+
+/// Stores a secret submitted with the settings
+///
+/// A masked hint leaves the stored value unchanged; `None` or an empty value
+/// removes it.
+fn save_secret(secrets: &SecretStore, name: &str, value: Option<&str>) -> Result<(), String> {
+    match value.map(str::trim) {
+        Some(value) if secret_store::is_masked(value) => Ok(()),
+        Some(value) if !value.is_empty() => secrets.set(name, value),
+        // Skip the keyring round trip for secrets that were never stored
+        _ => match secrets.get(name) {
+            Ok(None) => Ok(()),
+            _ => secrets.delete(name),
+        },
+    }
+}
+
+/// Looks up a secret in the secret store
+///
+/// Falls back to `stored`, the value from the settings store, which is only
+/// set while the API keys' migration to the secret store hasn't succeeded yet.
+fn resolve_secret(secrets: &SecretStore, name: &str, stored: Option<String>) -> Option<String> {
+    match secrets.get(name) {
+        Ok(Some(value)) => Some(value),
+        Ok(None) => stored,
+        Err(e) => {
+            log::error!("Failed to read secret {}: {}", name, e);
+            stored
+        }
+    }
+}
+
+/// Moves plaintext API keys from `settings.json` into the secret store
+///
+/// Covers the OpenAI API key and the key of an OpenAI-compatible
+/// transcription server. Runs once on startup. The keys are removed from the
+/// settings store only after they were stored securely, so a failed
+/// migration is retried on the next launch.
+///
+/// # Returns
+/// Whether any key was migrated
+pub fn migrate_api_keys_from_store(app: &AppHandle, secrets: &SecretStore) -> Result<bool, String> {
     let store = app
         .store(SETTINGS_STORE_FILE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
-    match store.get(HISTORY_KEY) {
-        Some(value) => {
-            let mut items: Vec<HistoryItem> = serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to parse history: {}", e))?;
-
-            // Sort by createdAt descending (newest first)
-            sort_history_descending(&mut items);
+    let Some(value) = store.get(SETTINGS_KEY) else {
+        return Ok(false);
+    };
+    let settings: Settings =
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse settings: {}", e))?;
+    let plaintext: Vec<(&str, &str)> = [
+        (OPENAI_API_KEY, settings.api_key.as_ref()),
+        (
+            TRANSCRIPTION_API_KEY,
+            settings
+                .transcription_provider
+                .api_key()
+                .and_then(Option::as_ref),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(name, key)| Some((name, key?.as_str())))
+    .collect();
+    if plaintext.is_empty() {
+        return Ok(false);
+    }
 
-            Ok(items)
+    for (name, api_key) in plaintext {
+        // Keep a key that was already moved over, e.g. by a newer version
+        if !api_key.trim().is_empty() && secrets.get(name)?.is_none() {
+            secrets.set(name, api_key.trim())?;
         }
-        None => Ok(Vec::new()),
     }
+
+    store.set(SETTINGS_KEY, stored_settings_value(&settings)?);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings store: {}", e))?;
+
+    Ok(true)
+}
+
+// ============================================================================
+// History Commands
+// ============================================================================
+
+/// Retrieves all history items, ordered by createdAt DESC (newest first)
+#[tauri::command]
+pub fn get_history(history: State<'_, HistoryRepository>) -> Result<Vec<HistoryItem>, String> {
+    history.list()
+}
+
+/// Full-text search over transcriptions and summaries
+///
+/// # Arguments
+/// * `query` - Search terms, optional date range and result limit
+///
+/// # Returns
+/// Matching history items, best match first, with highlighted snippets
+#[tauri::command]
+pub fn search_history(
+    history: State<'_, HistoryRepository>,
+    query: HistorySearchQuery,
+) -> Result<Vec<HistorySearchResult>, String> {
+    history.search(&query)
 }
 
 /// Saves a new recording to history
 ///
-/// Creates a new history item with a generated UUID and timestamp,
-/// adds it to the history, and limits the total to MAX_HISTORY_ITEMS.
+/// Creates a new history item with a generated UUID and timestamp
+/// and inserts it into the history database.
 ///
 /// # Arguments
 /// * `file_path` - Full path to the recording file
 /// * `duration_seconds` - Duration of the recording in seconds
 /// * `transcription` - The transcribed text
+/// * `segments` - Timestamped segments from the transcription (optional)
 ///
 /// # Returns
 /// The ID of the newly created history item
 #[tauri::command]
 pub fn save_recording_history(
-    app: AppHandle,
+    history: State<'_, HistoryRepository>,
     file_path: String,
     duration_seconds: f64,
     transcription: String,
+    segments: Option<Vec<TranscriptionSegment>>,
 ) -> Result<String, String> {
-    let store = app
-        .store(SETTINGS_STORE_FILE)
-        .map_err(|e| format!("Failed to open settings store: {}", e))?;
-
-    // Get existing history or create empty array
-    let mut history: Vec<HistoryItem> = match store.get(HISTORY_KEY) {
-        Some(value) => serde_json::from_value(value.clone())
-            .map_err(|e| format!("Failed to parse history: {}", e))?,
-        None => Vec::new(),
-    };
-
-    // Create new history item
-    let new_item = HistoryItem::new(file_path, duration_seconds, transcription);
-    let new_id = new_item.id.clone();
-
-    // Add new item at the beginning (newest first)
-    history.insert(0, new_item);
-
-    // Limit to MAX_HISTORY_ITEMS
-    truncate_history(&mut history);
-
-    // Save back to store
-    let value = serde_json::to_value(&history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    let mut new_item = HistoryItem::new(file_path, duration_seconds, transcription);
+    new_item.segments = segments;
 
-    store.set(HISTORY_KEY, value);
+    history.insert(&new_item)?;
 
-    store
-        .save()
-        .map_err(|e| format!("Failed to save history: {}", e))?;
-
-    Ok(new_id)
+    Ok(new_item.id)
 }
 
 /// Deletes a recording from history by its ID and removes the associated audio file
@@ -144,42 +329,20 @@ pub fn save_recording_history(
 /// # Arguments
 /// * `id` - The UUID of the history item to delete
 #[tauri::command]
-pub fn delete_recording_history(app: AppHandle, id: String) -> Result<(), String> {
-    let store = app
-        .store(SETTINGS_STORE_FILE)
-        .map_err(|e| format!("Failed to open settings store: {}", e))?;
-
-    // Get existing history
-    let mut history: Vec<HistoryItem> = match store.get(HISTORY_KEY) {
-        Some(value) => serde_json::from_value(value.clone())
-            .map_err(|e| format!("Failed to parse history: {}", e))?,
-        None => return Ok(()), // Nothing to delete
-    };
-
-    // Find the item to get its file path before removing
-    if let Some(item) = history.iter().find(|item| item.id == id) {
+pub fn delete_recording_history(
+    history: State<'_, HistoryRepository>,
+    id: String,
+) -> Result<(), String> {
+    if let Some(item) = history.delete(&id)? {
         let file_path = std::path::Path::new(&item.file_path);
         if file_path.exists() {
             if let Err(e) = std::fs::remove_file(file_path) {
-                // Log error but continue with history removal
-                eprintln!("Warning: Failed to delete audio file {}: {}", item.file_path, e);
+                // History entry is already gone; just report the orphaned file
+                log::warn!("Failed to delete audio file {}: {}", item.file_path, e);
             }
         }
     }
 
-    // Remove item with matching ID
-    history.retain(|item| item.id != id);
-
-    // Save back to store
-    let value = serde_json::to_value(&history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
-
-    store.set(HISTORY_KEY, value);
-
-    store
-        .save()
-        .map_err(|e| format!("Failed to save history: {}", e))?;
-
     Ok(())
 }
 
@@ -189,37 +352,143 @@ pub fn delete_recording_history(app: AppHandle, id: String) -> Result<(), String
 /// * `id` - The UUID of the history item to update
 /// * `summary` - The AI-generated summary text to save
 #[tauri::command]
-pub fn update_history_summary(app: AppHandle, id: String, summary: String) -> Result<(), String> {
+pub fn update_history_summary(
+    history: State<'_, HistoryRepository>,
+    id: String,
+    summary: String,
+) -> Result<(), String> {
+    history.update_summary(&id, &summary)
+}
+
+/// Sets the title of a history item
+///
+/// # Arguments
+/// * `id` - The UUID of the history item to rename
+/// * `title` - The new title, or null/blank to clear it
+#[tauri::command]
+pub fn rename_history_item(
+    history: State<'_, HistoryRepository>,
+    id: String,
+    title: Option<String>,
+) -> Result<(), String> {
+    history.rename(&id, title.as_deref())
+}
+
+/// Replaces the tags of a history item
+///
+/// # Arguments
+/// * `id` - The UUID of the history item to tag
+/// * `tags` - The complete list of tags (blank and duplicate tags are dropped)
+#[tauri::command]
+pub fn set_history_tags(
+    history: State<'_, HistoryRepository>,
+    id: String,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    history.set_tags(&id, &tags)
+}
+
+/// Moves a history item into a folder
+///
+/// # Arguments
+/// * `id` - The UUID of the history item to move
+/// * `folder` - The folder name, or null/blank to unfile the item
+#[tauri::command]
+pub fn move_history_item(
+    history: State<'_, HistoryRepository>,
+    id: String,
+    folder: Option<String>,
+) -> Result<(), String> {
+    history.move_to_folder(&id, folder.as_deref())
+}
+
+/// Retrieves the history items carrying a tag (case-insensitive), newest first
+#[tauri::command]
+pub fn list_history_by_tag(
+    history: State<'_, HistoryRepository>,
+    tag: String,
+) -> Result<Vec<HistoryItem>, String> {
+    history.list_by_tag(&tag)
+}
+
+/// Retrieves the history items in a folder, newest first
+///
+/// # Arguments
+/// * `folder` - The folder name, or null to list unfiled items
+#[tauri::command]
+pub fn list_history_by_folder(
+    history: State<'_, HistoryRepository>,
+    folder: Option<String>,
+) -> Result<Vec<HistoryItem>, String> {
+    history.list_by_folder(folder.as_deref())
+}
+
+/// Retrieves all tags in use, sorted alphabetically
+#[tauri::command]
+pub fn list_history_tags(history: State<'_, HistoryRepository>) -> Result<Vec<String>, String> {
+    history.list_tags()
+}
+
+/// Retrieves all folders in use, sorted alphabetically
+#[tauri::command]
+pub fn list_history_folders(history: State<'_, HistoryRepository>) -> Result<Vec<String>, String> {
+    history.list_folders()
+}
+
+/// Moves history from the legacy `settings.json` store key into the history database
+///
+/// Runs once on startup. The store key is removed only after all items were
+/// imported, so a failed migration is retried on the next launch.
+///
+/// # Returns
+/// The number of imported items
+pub fn migrate_history_from_store(
+    app: &AppHandle,
+    history: &HistoryRepository,
+) -> Result<usize, String> {
     let store = app
         .store(SETTINGS_STORE_FILE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
-    // Get existing history
-    let mut history: Vec<HistoryItem> = match store.get(HISTORY_KEY) {
-        Some(value) => serde_json::from_value(value.clone())
-            .map_err(|e| format!("Failed to parse history: {}", e))?,
-        None => return Err("History not found".to_string()),
+    let Some(value) = store.get(HISTORY_KEY) else {
+        return Ok(0);
     };
 
-    // Find and update the item with matching ID
-    let item = history
-        .iter_mut()
-        .find(|item| item.id == id)
-        .ok_or_else(|| format!("History item not found: {}", id))?;
-
-    item.summary = Some(summary);
+    let items: Vec<HistoryItem> =
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse history: {}", e))?;
 
-    // Save back to store
-    let value = serde_json::to_value(&history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
-
-    store.set(HISTORY_KEY, value);
+    let imported = history.import(&items)?;
 
+    store.delete(HISTORY_KEY);
     store
         .save()
-        .map_err(|e| format!("Failed to save history: {}", e))?;
+        .map_err(|e| format!("Failed to save settings store: {}", e))?;
 
-    Ok(())
+    Ok(imported)
+}
+
+// ============================================================================
+// Export Commands
+// ============================================================================
+
+/// Exports a history item to a file in the given format
+///
+/// # Arguments
+/// * `id` - The UUID of the history item to export
+/// * `format` - One of "srt", "vtt", "txt", "markdown" or "json"
+/// * `path` - Destination file path chosen by the user
+#[tauri::command]
+pub fn export_history_item(
+    history: State<'_, HistoryRepository>,
+    id: String,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    let item = history
+        .get(&id)?
+        .ok_or_else(|| format!("History item not found: {}", id))?;
+
+    export_to_file(&item, format, &path)
 }
 
 // ============================================================================
@@ -230,7 +499,7 @@ pub fn update_history_summary(app: AppHandle, id: String, summary: String) -> Re
 ///
 /// - Windows: `%APPDATA%/EverVoice/recordings/`
 /// - macOS: `~/Library/Application Support/EverVoice/recordings/`
-/// - Linux: `~/.config/EverVoice/recordings/`
+/// - Linux: `~/.local/share/EverVoice/recordings/`
 #[tauri::command]
 pub fn get_recordings_directory() -> Result<String, String> {
     let dir = file_storage::get_recordings_dir()?;
@@ -249,13 +518,69 @@ pub fn ensure_directory_exists() -> Result<String, String> {
         .ok_or_else(|| "Invalid path encoding".to_string())
 }
 
-/// Saves a recording from binary data and returns the full file path
+/// Result of saving a recording, returned to the frontend
+#[derive(Debug, serde::Serialize)]
+pub struct SaveRecordingResponse {
+    /// Full path of the saved file
+    pub file_path: String,
+    /// History item created for the automatic pipeline (if one was started)
+    pub history_id: Option<String>,
+    /// Job running the automatic pipeline (if one was started)
+    pub job_id: Option<String>,
+}
+
+/// Saves a recording from binary data
 ///
 /// The file is saved with a unique name containing an ISO timestamp and UUID:
-/// `recording-{YYYY-MM-DDTHH-mm-ss}-{uuid}.webm`
+/// `recording-{YYYY-MM-DDTHH-mm-ss}-{uuid}.webm`. Builds with the `transcode`
+/// feature compress it to mono Opus first and save it as `.ogg`.
+///
+/// If settings select an automatic pipeline, a history item for the recording
+/// is created right away and the pipeline is started as a background job (see
+/// `start_pipeline_job`). The response then carries the history item and job
+/// IDs, and the frontend should leave transcription to the pipeline instead
+/// of calling `transcribe_audio` and `save_recording_history`.
+///
+/// # Arguments
+/// * `data` - The recorded audio
+/// * `duration_seconds` - Duration of the recording, stored with the history
+///   item (optional, read from the audio when missing)
 #[tauri::command]
-pub fn save_recording(data: Vec<u8>) -> Result<String, String> {
-    file_storage::save_recording_to_file(&data)
+pub async fn save_recording(
+    app: AppHandle,
+    data: Vec<u8>,
+    duration_seconds: Option<f64>,
+) -> Result<SaveRecordingResponse, String> {
+    // Transcoding a long recording shouldn't block the async runtime
+    let (file_path, duration_seconds) = tauri::async_runtime::spawn_blocking(move || {
+        let duration_seconds = duration_seconds.unwrap_or_else(|| recording_duration(&data));
+        file_storage::save_recording_to_file(&data).map(|path| (path, duration_seconds))
+    })
+    .await
+    .map_err(|e| format!("Failed to save recording: {}", e))??;
+
+    // The recording is saved either way; a pipeline that can't start is only logged
+    let pipeline = start_auto_pipeline(&app, &file_path, duration_seconds).unwrap_or_else(|e| {
+        log::error!("Failed to start pipeline for {}: {}", file_path, e);
+        None
+    });
+    let (history_id, job_id) = pipeline.unzip();
+
+    Ok(SaveRecordingResponse {
+        file_path,
+        history_id,
+        job_id,
+    })
+}
+
+/// Duration of recorded audio in seconds, or 0 if it can't be read
+fn recording_duration(data: &[u8]) -> f64 {
+    AudioFormat::detect(data)
+        .and_then(|format| probe_duration(format, data))
+        .unwrap_or_else(|| {
+            log::warn!("Could not read the duration of the recording");
+            0.0
+        })
 }
 
 /// Deletes a recording file by its full path
@@ -266,6 +591,156 @@ pub fn delete_recording(file_path: String) -> Result<(), String> {
     file_storage::delete_recording_file(&file_path)
 }
 
+/// Result of importing one audio file
+#[derive(serde::Serialize)]
+pub struct ImportResponse {
+    /// The path the file was imported from
+    pub source_path: String,
+    /// Whether the file was imported
+    pub success: bool,
+    /// The ID of the new history item (if successful)
+    pub history_id: Option<String>,
+    /// The transcription or pipeline job queued for the file (if requested)
+    pub job_id: Option<String>,
+    /// Whether the file was hard-linked rather than copied
+    pub linked: bool,
+    /// Error message (if failed)
+    pub error_message: Option<String>,
+}
+
+/// Imports existing audio files into history
+///
+/// Every file is copied (or hard-linked) into the recordings directory and
+/// gets a history item with an empty transcription, titled after the file
+/// name and dated by the file's modification time. The format is detected
+/// from the file content, so files that can't be transcribed are rejected
+/// up front. Files are imported independently; one that fails doesn't stop
+/// the others.
+///
+/// With `transcribe`, a background job is queued for every imported file:
+/// the automatic pipeline if settings select one, otherwise a transcription
+/// that fills in the history item.
+///
+/// # Arguments
+/// * `app` - Tauri app handle for accessing settings, history and jobs
+/// * `paths` - Full paths of the audio files to import
+/// * `mode` - Copy (default) or hard-link the files into the recordings directory
+/// * `transcribe` - Whether to queue transcription for the imported files (default: false)
+///
+/// # Returns
+/// One response per path, in the same order
+#[tauri::command]
+pub async fn import_audio_files(
+    app: AppHandle,
+    paths: Vec<String>,
+    mode: Option<ImportMode>,
+    transcribe: Option<bool>,
+) -> Result<Vec<ImportResponse>, String> {
+    let mode = mode.unwrap_or_default();
+    let transcribe = transcribe.unwrap_or(false);
+
+    // Reading and copying large files shouldn't block the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let recordings_dir = file_storage::ensure_recordings_dir_exists()?;
+        let pipeline = if transcribe {
+            auto_pipeline(&app)?
+        } else {
+            None
+        };
+
+        Ok(paths
+            .into_iter()
+            .map(|source_path| {
+                let result =
+                    import_audio_file(std::path::Path::new(&source_path), &recordings_dir, mode)
+                        .and_then(|imported| {
+                            let (history_id, job_id) = add_imported_to_history(
+                                &app,
+                                &imported,
+                                transcribe,
+                                pipeline.as_ref(),
+                            )?;
+                            Ok((history_id, job_id, imported.linked))
+                        });
+                ImportResponse::from_result(source_path, result)
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("Import failed: {}", e))?
+}
+
+impl ImportResponse {
+    /// Builds the response for a file from its history item ID, job ID and
+    /// whether it was linked, or the error that stopped the import
+    pub(crate) fn from_result(
+        source_path: String,
+        result: Result<(String, Option<String>, bool), String>,
+    ) -> Self {
+        match result {
+            Ok((history_id, job_id, linked)) => ImportResponse {
+                source_path,
+                success: true,
+                history_id: Some(history_id),
+                job_id,
+                linked,
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("Failed to import {}: {}", source_path, e);
+                ImportResponse {
+                    source_path,
+                    success: false,
+                    history_id: None,
+                    job_id: None,
+                    linked: false,
+                    error_message: Some(e),
+                }
+            }
+        }
+    }
+}
+
+/// Creates the history item for an imported file and queues its
+/// transcription, returning the item ID and the job ID
+///
+/// With a `pipeline`, the item runs it instead of a plain transcription.
+pub(crate) fn add_imported_to_history(
+    app: &AppHandle,
+    imported: &ImportedAudio,
+    transcribe: bool,
+    pipeline: Option<&Pipeline>,
+) -> Result<(String, Option<String>), String> {
+    let mut item = HistoryItem::new(
+        imported.file_path.clone(),
+        imported.duration_seconds,
+        String::new(),
+    );
+    item.created_at = imported.created_at.clone();
+    item.title = Some(imported.title.clone()).filter(|title| !title.is_empty());
+    if transcribe {
+        item.pipeline = pipeline.map(PipelineRun::new);
+    }
+
+    if let Err(e) = app.state::<HistoryRepository>().insert(&item) {
+        if let Err(e) = file_storage::delete_recording_file(&imported.file_path) {
+            log::warn!(
+                "Failed to delete imported file {}: {}",
+                imported.file_path,
+                e
+            );
+        }
+        return Err(e);
+    }
+
+    let job_id = match (transcribe, &item.pipeline) {
+        (false, _) => None,
+        (true, Some(_)) => Some(submit_pipeline_job(app, item.id.clone())),
+        (true, None) => Some(submit_history_transcription_job(app, item.id.clone())),
+    };
+    Ok((item.id, job_id))
+}
+
 // ============================================================================
 // Transcription Commands
 // ============================================================================
@@ -277,12 +752,18 @@ pub struct TranscriptionResponse {
     pub success: bool,
     /// The transcribed text (if successful)
     pub text: Option<String>,
+    /// Timestamped segments (if successful and returned by the provider)
+    pub segments: Option<Vec<TranscriptionSegment>>,
+    /// Silences cut out before transcription, in seconds of the recording
+    pub removed_silence: Vec<SilenceRange>,
     /// Error type (if failed)
     pub error_type: Option<String>,
     /// User-friendly error message (if failed)
     pub error_message: Option<String>,
     /// Whether the error is retryable
     pub retryable: Option<bool>,
+    /// Whether the recording was queued to be transcribed once the network is back
+    pub queued: bool,
 }
 
 impl From<Result<TranscriptionResult, TranscriptionError>> for TranscriptionResponse {
@@ -291,52 +772,72 @@ impl From<Result<TranscriptionResult, TranscriptionError>> for TranscriptionResp
             Ok(transcription) => TranscriptionResponse {
                 success: true,
                 text: Some(transcription.text),
+                segments: transcription.segments,
+                removed_silence: transcription.removed_silence,
                 error_type: None,
                 error_message: None,
                 retryable: None,
+                queued: false,
+            },
+            Err(error) => TranscriptionResponse {
+                success: false,
+                text: None,
+                segments: None,
+                removed_silence: Vec::new(),
+                error_type: Some(transcription_error_type(&error).to_string()),
+                error_message: Some(error.user_message()),
+                retryable: Some(error.is_transient()),
+                queued: false,
             },
-            Err(error) => {
-                let error_type = match &error {
-                    TranscriptionError::ApiKeyNotConfigured => "api_key_not_configured",
-                    TranscriptionError::InvalidApiKey => "invalid_api_key",
-                    TranscriptionError::FileNotFound(_) => "file_not_found",
-                    TranscriptionError::FileReadError(_) => "file_read_error",
-                    TranscriptionError::InvalidAudioFormat(_) => "invalid_audio_format",
-                    TranscriptionError::NetworkError(_) => "network_error",
-                    TranscriptionError::RateLimitExceeded => "rate_limit_exceeded",
-                    TranscriptionError::ApiError(_) => "api_error",
-                    TranscriptionError::Unknown(_) => "unknown",
-                };
-
-                TranscriptionResponse {
-                    success: false,
-                    text: None,
-                    error_type: Some(error_type.to_string()),
-                    error_message: Some(error.user_message()),
-                    retryable: Some(error.is_transient()),
-                }
-            }
         }
     }
 }
 
-/// Transcribes an audio file using OpenAI Whisper API
+/// Error type identifier sent to the frontend for a transcription error
+fn transcription_error_type(error: &TranscriptionError) -> &'static str {
+    match error {
+        TranscriptionError::ApiKeyNotConfigured => "api_key_not_configured",
+        TranscriptionError::InvalidApiKey => "invalid_api_key",
+        TranscriptionError::FileNotFound(_) => "file_not_found",
+        TranscriptionError::FileReadError(_) => "file_read_error",
+        TranscriptionError::InvalidAudioFormat(_) => "invalid_audio_format",
+        TranscriptionError::NetworkError(_) => "network_error",
+        TranscriptionError::RateLimitExceeded => "rate_limit_exceeded",
+        TranscriptionError::ApiError(_) => "api_error",
+        TranscriptionError::LocalEngine(_) => "local_engine_error",
+        TranscriptionError::Unknown(_) => "unknown",
+    }
+}
+
+/// Transcribes an audio file using the transcription provider selected in settings
 ///
 /// This command:
 /// 1. Reads the audio file from the specified path
-/// 2. Retrieves the API key from settings
-/// 3. Calls OpenAI Whisper API with exponential backoff retry
+/// 2. Builds the transcription provider (OpenAI or OpenAI-compatible) from settings
+/// 3. Calls the provider with exponential backoff retry
 /// 4. Returns the transcription text or a structured error
 ///
+/// If the network is still unreachable after the retries, the recording is
+/// added to the pending transcription queue and `queued` is set in the
+/// response; it is transcribed and saved to history once the network is back.
+/// A successful transcription takes the recording off the queue, so a manual
+/// retry doesn't end up in history twice.
+///
 /// # Arguments
 /// * `app` - Tauri app handle for accessing settings
 /// * `file_path` - Full path to the audio file to transcribe
+/// * `duration_seconds` - Duration of the recording, stored with a queued transcription
+///   (optional, read from the file if missing)
 ///
 /// # Returns
 /// A `TranscriptionResponse` containing either the transcribed text or error details
 #[tauri::command]
-pub async fn transcribe_audio(app: AppHandle, file_path: String) -> TranscriptionResponse {
-    // Get settings for API key and language
+pub async fn transcribe_audio(
+    app: AppHandle,
+    file_path: String,
+    duration_seconds: Option<f64>,
+) -> TranscriptionResponse {
+    // Get settings for provider and language
     let settings = match get_settings_internal(&app) {
         Ok(s) => s,
         Err(e) => {
@@ -344,18 +845,148 @@ pub async fn transcribe_audio(app: AppHandle, file_path: String) -> Transcriptio
         }
     };
 
-    // Check if API key is set
-    let api_key = match settings.api_key {
-        Some(key) if !key.trim().is_empty() => key,
-        _ => {
-            return TranscriptionResponse::from(Err(TranscriptionError::ApiKeyNotConfigured));
+    // Build the configured provider (fails if the API key is missing)
+    let provider = match provider_from_settings(&settings) {
+        Ok(p) => p,
+        Err(e) => {
+            return TranscriptionResponse::from(Err(e));
         }
     };
 
     // Call transcription function with language
-    let result = transcribe_audio_file(&file_path, &api_key, &settings.language).await;
+    let result = transcribe_audio_file(&file_path, &settings.language, provider.as_ref()).await;
+
+    let queued = match &result {
+        Err(error) => {
+            let duration = duration_seconds.unwrap_or_else(|| file_duration(&file_path));
+            queue_if_offline(&app, &file_path, duration, None, error)
+        }
+        Ok(_) => {
+            remove_from_queue(&app, &file_path);
+            false
+        }
+    };
+
+    TranscriptionResponse {
+        queued,
+        ..TranscriptionResponse::from(result)
+    }
+}
+
+/// Duration of a recording file in seconds, or 0 if it can't be read
+fn file_duration(file_path: &str) -> f64 {
+    match std::fs::read(file_path) {
+        Ok(data) => recording_duration(&data),
+        Err(e) => {
+            log::warn!("Could not read {}: {}", file_path, e);
+            0.0
+        }
+    }
+}
+
+/// Removes a transcribed recording from the pending queue, if it was queued
+///
+/// Otherwise the background worker would save a second history item for it.
+fn remove_from_queue(app: &AppHandle, file_path: &str) {
+    if let Err(e) = app.state::<HistoryRepository>().remove_pending(file_path) {
+        log::warn!(
+            "Failed to remove {} from the transcription queue: {}",
+            file_path,
+            e
+        );
+    }
+}
+
+/// Adds a recording to the pending queue if its transcription failed for lack of network
+///
+/// `history_id` is the history item waiting for the transcription; without
+/// one, the queue creates a new item once the recording is transcribed.
+///
+/// Returns whether the recording was queued.
+fn queue_if_offline(
+    app: &AppHandle,
+    file_path: &str,
+    duration_seconds: f64,
+    history_id: Option<&str>,
+    error: &TranscriptionError,
+) -> bool {
+    if !matches!(error, TranscriptionError::NetworkError(_)) {
+        return false;
+    }
+
+    let pending = PendingTranscription {
+        history_id: history_id.map(str::to_string),
+        ..PendingTranscription::new(file_path.to_string(), duration_seconds, &error.to_string())
+    };
+    match app.state::<HistoryRepository>().enqueue_pending(&pending) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("Failed to queue transcription: {}", e);
+            false
+        }
+    }
+}
+
+// ============================================================================
+// Local Transcription Model Commands
+// ============================================================================
+
+/// Returns whether this build can transcribe on-device (`local-whisper` feature)
+#[tauri::command]
+pub fn is_local_transcription_available() -> bool {
+    cfg!(feature = "local-whisper")
+}
+
+/// Returns the local transcription models and whether each is downloaded
+#[tauri::command]
+pub fn list_local_models() -> Result<Vec<LocalModelInfo>, String> {
+    let dir = file_storage::get_models_dir()?;
+    Ok(local_models::list_models(&dir))
+}
+
+/// Payload of the `model-download-progress` event
+#[derive(Clone, serde::Serialize)]
+pub struct ModelDownloadProgressEvent {
+    pub model_id: String,
+    pub downloaded_bytes: u64,
+    /// Size of the model file, if the server reported it
+    pub total_bytes: Option<u64>,
+}
+
+/// Downloads a local transcription model into the models directory
+///
+/// Progress is reported with `model-download-progress` events. Returns the
+/// model's updated info once the download has finished.
+#[tauri::command]
+pub async fn download_local_model(
+    app: AppHandle,
+    model_id: String,
+) -> Result<LocalModelInfo, String> {
+    let dir = file_storage::ensure_models_dir_exists()?;
+
+    local_models::download_model(&dir, &model_id, |downloaded_bytes, total_bytes| {
+        let event = ModelDownloadProgressEvent {
+            model_id: model_id.clone(),
+            downloaded_bytes,
+            total_bytes,
+        };
+        if let Err(e) = app.emit(MODEL_DOWNLOAD_PROGRESS_EVENT, event) {
+            log::warn!("Failed to emit model download progress: {}", e);
+        }
+    })
+    .await?;
+
+    local_models::list_models(&dir)
+        .into_iter()
+        .find(|model| model.id == model_id)
+        .ok_or_else(|| format!("Unknown transcription model: {}", model_id))
+}
 
-    TranscriptionResponse::from(result)
+/// Deletes a downloaded local transcription model
+#[tauri::command]
+pub fn delete_local_model(model_id: String) -> Result<(), String> {
+    let dir = file_storage::get_models_dir()?;
+    local_models::delete_model(&dir, &model_id).map(|_| ())
 }
 
 // ============================================================================
@@ -363,7 +994,7 @@ pub async fn transcribe_audio(app: AppHandle, file_path: String) -> Transcriptio
 // ============================================================================
 
 /// Summarization response returned to the frontend
-#[derive(serde::Serialize)]
+#[derive(Clone, serde::Serialize)]
 pub struct SummarizationResponse {
     /// Whether the summarization was successful
     pub success: bool,
@@ -384,77 +1015,808 @@ impl From<Result<SummarizationResult, SummarizationError>> for SummarizationResp
                 error_type: None,
                 error_message: None,
             },
-            Err(error) => {
-                let error_type = match &error {
-                    SummarizationError::ApiKeyNotConfigured => "api_key_not_configured",
-                    SummarizationError::InvalidApiKey => "invalid_api_key",
-                    SummarizationError::NetworkError(_) => "network_error",
-                    SummarizationError::RateLimitExceeded => "rate_limit_exceeded",
-                    SummarizationError::ApiError(_) => "api_error",
-                    SummarizationError::EmptyText => "empty_text",
-                };
-
-                SummarizationResponse {
-                    success: false,
-                    summary: None,
-                    error_type: Some(error_type.to_string()),
-                    error_message: Some(error.user_message()),
-                }
-            }
+            Err(error) => SummarizationResponse {
+                success: false,
+                summary: None,
+                error_type: Some(summarization_error_type(&error).to_string()),
+                error_message: Some(error.user_message()),
+            },
         }
     }
 }
 
+/// Title generation response returned to the frontend
+#[derive(serde::Serialize)]
+pub struct TitleResponse {
+    /// Whether the title was generated and saved
+    pub success: bool,
+    /// The generated title (if successful)
+    pub title: Option<String>,
+    /// Error type (if failed)
+    pub error_type: Option<String>,
+    /// User-friendly error message (if failed)
+    pub error_message: Option<String>,
+}
+
+impl From<Result<TitleResult, SummarizationError>> for TitleResponse {
+    fn from(result: Result<TitleResult, SummarizationError>) -> Self {
+        match result {
+            Ok(result) => TitleResponse {
+                success: true,
+                title: Some(result.title),
+                error_type: None,
+                error_message: None,
+            },
+            Err(error) => TitleResponse {
+                success: false,
+                title: None,
+                error_type: Some(summarization_error_type(&error).to_string()),
+                error_message: Some(error.user_message()),
+            },
+        }
+    }
+}
+
+/// Error type identifier sent to the frontend for a summarization error
+fn summarization_error_type(error: &SummarizationError) -> &'static str {
+    match error {
+        SummarizationError::ApiKeyNotConfigured => "api_key_not_configured",
+        SummarizationError::InvalidApiKey => "invalid_api_key",
+        SummarizationError::NetworkError(_) => "network_error",
+        SummarizationError::RateLimitExceeded => "rate_limit_exceeded",
+        SummarizationError::ApiError(_) => "api_error",
+        SummarizationError::EmptyText => "empty_text",
+        SummarizationError::TemplateNotFound(_) => "template_not_found",
+    }
+}
+
 /// Summarizes transcription text using OpenAI Chat Completions API
 ///
 /// This command:
-/// 1. Retrieves the API key from settings
-/// 2. Calls OpenAI Chat Completions API (gpt-4o-mini) with a system prompt
-/// 3. Returns the markdown-formatted summary or a structured error
+/// 1. Retrieves the API key and prompt templates from settings
+/// 2. Renders the selected template (or the default one) with the recording's
+///    language, date and duration
+/// 3. Calls OpenAI Chat Completions API with the template's model
+/// 4. Returns the markdown-formatted summary or a structured error
 ///
 /// # Arguments
 /// * `app` - Tauri app handle for accessing settings
 /// * `text` - The transcription text to summarize
+/// * `template_id` - Prompt template to use (optional, defaults to the settings' default)
+/// * `duration_seconds` - Recording duration for the `{duration}` placeholder (optional)
+/// * `recorded_at` - RFC 3339 recording time for the `{date}` placeholder (optional, defaults to today)
 ///
 /// # Returns
 /// A `SummarizationResponse` containing either the markdown summary or error details
 #[tauri::command]
-pub async fn summarize_transcription(app: AppHandle, text: String) -> SummarizationResponse {
-    // Get settings for API key and language
+pub async fn summarize_transcription(
+    app: AppHandle,
+    text: String,
+    template_id: Option<String>,
+    duration_seconds: Option<f64>,
+    recorded_at: Option<String>,
+) -> SummarizationResponse {
+    let (api_key, options) = match summarization_request(
+        &app,
+        template_id.as_deref(),
+        duration_seconds,
+        recorded_at.as_deref(),
+    ) {
+        Ok(request) => request,
+        Err(e) => return SummarizationResponse::from(Err(e)),
+    };
+
+    // Call summarization function with the rendered template
+    let result = summarize_text(&text, &api_key, &options).await;
+
+    SummarizationResponse::from(result)
+}
+
+/// Payload of the `summary-chunk` event
+#[derive(Clone, serde::Serialize)]
+pub struct SummaryChunkEvent {
+    /// Job ID passed to `summarize_transcription_stream`
+    pub job_id: String,
+    /// Newly generated text to append
+    pub delta: String,
+}
+
+/// Payload of the `summary-complete` event
+#[derive(Clone, serde::Serialize)]
+pub struct SummaryCompleteEvent {
+    /// Job ID passed to `summarize_transcription_stream`
+    pub job_id: String,
+    /// History item the summary was saved to (if any)
+    pub history_id: Option<String>,
+    /// The complete summary or error details
+    #[serde(flatten)]
+    pub response: SummarizationResponse,
+}
+
+/// Summarizes transcription text, streaming the summary as Tauri events
+///
+/// Same as `summarize_transcription`, but returns immediately. The summary is
+/// then delivered as `summary-chunk` events while it is generated, followed
+/// by one `summary-complete` event with the full text or the error. Events
+/// carry the caller's `job_id`, so the caller can subscribe before invoking
+/// and not miss events of a summary that fails right away. When `history_id`
+/// is given, the finished summary is saved to that history item (like
+/// `update_history_summary`) before the completion event is sent.
+///
+/// # Arguments
+/// * `app` - Tauri app handle for accessing settings, history and events
+/// * `job_id` - ID chosen by the caller to tell this summary's events apart
+/// * `text` - The transcription text to summarize
+/// * `template_id` - Prompt template to use (optional, defaults to the settings' default)
+/// * `duration_seconds` - Recording duration for the `{duration}` placeholder (optional)
+/// * `recorded_at` - RFC 3339 recording time for the `{date}` placeholder (optional)
+/// * `history_id` - History item to save the summary to (optional)
+#[tauri::command]
+pub fn summarize_transcription_stream(
+    app: AppHandle,
+    job_id: String,
+    text: String,
+    template_id: Option<String>,
+    duration_seconds: Option<f64>,
+    recorded_at: Option<String>,
+    history_id: Option<String>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let result = match summarization_request(
+            &app,
+            template_id.as_deref(),
+            duration_seconds,
+            recorded_at.as_deref(),
+        ) {
+            Ok((api_key, options)) => {
+                summarize_text_streaming(&text, &api_key, &options, |delta| {
+                    let event = SummaryChunkEvent {
+                        job_id: job_id.clone(),
+                        delta: delta.to_string(),
+                    };
+                    if let Err(e) = app.emit(SUMMARY_CHUNK_EVENT, event) {
+                        log::warn!("Failed to emit summary chunk: {}", e);
+                    }
+                })
+                .await
+            }
+            Err(e) => Err(e),
+        };
+
+        // Persist before reporting completion so the frontend can reload history
+        let result = match (result, &history_id) {
+            (Ok(summary), Some(id)) => app
+                .state::<HistoryRepository>()
+                .update_summary(id, &summary.summary)
+                .map(|_| summary)
+                .map_err(SummarizationError::ApiError),
+            (result, _) => result,
+        };
+
+        let event = SummaryCompleteEvent {
+            job_id,
+            history_id,
+            response: SummarizationResponse::from(result),
+        };
+        if let Err(e) = app.emit(SUMMARY_COMPLETE_EVENT, event) {
+            log::error!("Failed to emit summary completion: {}", e);
+        }
+    });
+}
+
+/// Reads settings and resolves the API key and prompt template for a summary
+fn summarization_request(
+    app: &AppHandle,
+    template_id: Option<&str>,
+    duration_seconds: Option<f64>,
+    recorded_at: Option<&str>,
+) -> Result<(String, SummarizationOptions), SummarizationError> {
+    // Get settings for API key, language and prompt templates
+    let settings = get_settings_internal(app).map_err(SummarizationError::ApiError)?;
+
+    // Resolve the prompt template
+    let context = PromptContext::new(&settings.language, recorded_at, duration_seconds);
+    let options = SummarizationOptions::from_settings(&settings, template_id, &context)?;
+
+    // Check if API key is set
+    match settings.api_key {
+        Some(key) if !key.trim().is_empty() => Ok((key, options)),
+        _ => Err(SummarizationError::ApiKeyNotConfigured),
+    }
+}
+
+/// Generates a title for a history item from its transcription and saves it
+///
+/// Uses the same model and API key as summarization. The title is only
+/// stored if generation succeeds; an existing title is overwritten.
+///
+/// # Arguments
+/// * `app` - Tauri app handle for accessing settings and history
+/// * `id` - The UUID of the history item to title
+///
+/// # Returns
+/// A `TitleResponse` containing either the new title or error details
+#[tauri::command]
+pub async fn generate_history_title(app: AppHandle, id: String) -> TitleResponse {
     let settings = match get_settings_internal(&app) {
         Ok(s) => s,
-        Err(e) => {
-            return SummarizationResponse::from(Err(SummarizationError::ApiError(e)));
-        }
+        Err(e) => return TitleResponse::from(Err(SummarizationError::ApiError(e))),
     };
 
-    // Check if API key is set
     let api_key = match settings.api_key {
         Some(key) if !key.trim().is_empty() => key,
-        _ => {
-            return SummarizationResponse::from(Err(SummarizationError::ApiKeyNotConfigured));
+        _ => return TitleResponse::from(Err(SummarizationError::ApiKeyNotConfigured)),
+    };
+
+    let history = app.state::<HistoryRepository>();
+    let item = match history.get(&id) {
+        Ok(Some(item)) => item,
+        Ok(None) => {
+            return TitleResponse::from(Err(SummarizationError::ApiError(format!(
+                "History item not found: {}",
+                id
+            ))))
         }
+        Err(e) => return TitleResponse::from(Err(SummarizationError::ApiError(e))),
     };
 
-    // Call summarization function with language
-    let result = summarize_text(&text, &api_key, &settings.language).await;
+    let result = generate_title(
+        &item.transcription,
+        &api_key,
+        &settings.summarization_model,
+        &settings.language,
+    )
+    .await;
+
+    if let Ok(title) = &result {
+        if let Err(e) = history.rename(&id, Some(&title.title)) {
+            return TitleResponse::from(Err(SummarizationError::ApiError(e)));
+        }
+    }
 
-    SummarizationResponse::from(result)
+    TitleResponse::from(result)
+}
+
+// ============================================================================
+// Background Job Commands
+// ============================================================================
+
+/// Queues a transcription of an audio file as a background job
+///
+/// Unlike `transcribe_audio`, the result does not depend on the window
+/// waiting for it: a successful transcription is saved as a new history
+/// item (with its segments) by the job itself. Progress and the final state
+/// are reported through `job-updated` events. Like `transcribe_audio`, a
+/// recording that fails for lack of network is added to the pending queue,
+/// and one that succeeds is taken off it.
+///
+/// # Arguments
+/// * `app` - Tauri app handle for accessing settings, history and jobs
+/// * `file_path` - Full path to the audio file to transcribe
+/// * `duration_seconds` - Duration of the recording, stored with the history item
+///
+/// # Returns
+/// The job ID
+#[tauri::command]
+pub fn start_transcription_job(app: AppHandle, file_path: String, duration_seconds: f64) -> String {
+    let jobs = app.state::<JobManager>().inner().clone();
+    jobs.submit(JobKind::Transcription, None, move |progress| async move {
+        let settings = get_settings_internal(&app)
+            .map_err(|e| transcription_job_error(TranscriptionError::Unknown(e)))?;
+        let provider = provider_from_settings(&settings).map_err(transcription_job_error)?;
+
+        let report = |done: usize, total: usize| progress.set(done as f64 / total as f64);
+        let transcription = transcribe_audio_file_with_progress(
+            &file_path,
+            &settings.language,
+            provider.as_ref(),
+            &report,
+        )
+        .await
+        .map_err(|e| {
+            queue_if_offline(&app, &file_path, duration_seconds, None, &e);
+            transcription_job_error(e)
+        })?;
+
+        let mut item = HistoryItem::new(file_path, duration_seconds, transcription.text);
+        item.segments = transcription.segments;
+        app.state::<HistoryRepository>()
+            .insert(&item)
+            .map_err(|e| JobError::new("history_error", e))?;
+        remove_from_queue(&app, &item.file_path);
+
+        Ok(JobOutput {
+            text: item.transcription,
+            history_id: Some(item.id),
+        })
+    })
+}
+
+/// Queues a summary of a history item as a background job
+///
+/// The summary is generated with the given prompt template (or the default
+/// one) and saved to the history item when the job completes.
+///
+/// # Arguments
+/// * `app` - Tauri app handle for accessing settings, history and jobs
+/// * `history_id` - The UUID of the history item to summarize
+/// * `template_id` - Prompt template to use (optional, defaults to the settings' default)
+///
+/// # Returns
+/// The job ID
+#[tauri::command]
+pub fn start_summarization_job(
+    app: AppHandle,
+    history_id: String,
+    template_id: Option<String>,
+) -> String {
+    let jobs = app.state::<JobManager>().inner().clone();
+    let job_history_id = Some(history_id.clone());
+    jobs.submit(
+        JobKind::Summarization,
+        job_history_id,
+        move |_| async move {
+            let history = app.state::<HistoryRepository>();
+            let item = history
+                .get(&history_id)
+                .map_err(|e| JobError::new("history_error", e))?
+                .ok_or_else(|| {
+                    JobError::new(
+                        "history_not_found",
+                        format!("History item not found: {}", history_id),
+                    )
+                })?;
+
+            let (api_key, options) = summarization_request(
+                &app,
+                template_id.as_deref(),
+                Some(item.duration_seconds),
+                Some(&item.created_at),
+            )
+            .map_err(summarization_job_error)?;
+            let summary = summarize_text(&item.transcription, &api_key, &options)
+                .await
+                .map_err(summarization_job_error)?;
+
+            history
+                .update_summary(&history_id, &summary.summary)
+                .map_err(|e| JobError::new("history_error", e))?;
+
+            Ok(JobOutput {
+                text: summary.summary,
+                history_id: Some(history_id),
+            })
+        },
+    )
+}
+
+fn transcription_job_error(error: TranscriptionError) -> JobError {
+    JobError::new(transcription_error_type(&error), error.user_message())
+}
+
+fn summarization_job_error(error: SummarizationError) -> JobError {
+    JobError::new(summarization_error_type(&error), error.user_message())
+}
+
+/// Returns a background job by ID
+#[tauri::command]
+pub fn get_job(jobs: State<'_, JobManager>, id: String) -> Result<Job, String> {
+    jobs.get(&id)
+        .ok_or_else(|| format!("Job not found: {}", id))
+}
+
+/// Returns all queued, running and recently finished jobs, newest first
+#[tauri::command]
+pub fn list_jobs(jobs: State<'_, JobManager>) -> Vec<Job> {
+    jobs.list()
+}
+
+/// Cancels a queued or running job
+///
+/// The job's in-flight request (including pending retries) is aborted and
+/// nothing is saved to history.
+#[tauri::command]
+pub fn cancel_job(jobs: State<'_, JobManager>, id: String) -> Result<(), String> {
+    jobs.cancel(&id)
+}
+
+/// Removes completed, failed and cancelled jobs from the job list
+#[tauri::command]
+pub fn clear_finished_jobs(jobs: State<'_, JobManager>) {
+    jobs.clear_finished()
+}
+
+// ============================================================================
+// Pipeline Commands
+// ============================================================================
+
+/// Creates a history item for a new recording and starts the automatic
+/// pipeline on it, if settings select one
+///
+/// Returns the history item and job IDs, or `None` when no pipeline is configured.
+fn start_auto_pipeline(
+    app: &AppHandle,
+    file_path: &str,
+    duration_seconds: f64,
+) -> Result<Option<(String, String)>, String> {
+    let Some(pipeline) = auto_pipeline(app)? else {
+        return Ok(None);
+    };
+
+    let mut item = HistoryItem::new(file_path.to_string(), duration_seconds, String::new());
+    item.pipeline = Some(PipelineRun::new(&pipeline));
+    app.state::<HistoryRepository>().insert(&item)?;
+
+    let job_id = submit_pipeline_job(app, item.id.clone());
+    Ok(Some((item.id, job_id)))
+}
+
+/// The pipeline settings select to run on new recordings, if any
+pub(crate) fn auto_pipeline(app: &AppHandle) -> Result<Option<Pipeline>, String> {
+    let settings = get_settings_internal(app)?;
+    let Some(pipeline_id) = settings.auto_pipeline.as_deref() else {
+        return Ok(None);
+    };
+    settings
+        .pipeline(pipeline_id)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))
+}
+
+/// Resumes the pipeline of a history item from its first unfinished step
+///
+/// Use this after a step failed (e.g. a custom action's server was down) or
+/// the app quit while the pipeline was running. A transcription that failed
+/// for lack of network is queued and resumes the pipeline on its own. Progress is
+/// reported through `job-updated` events and the status of every step is
+/// stored in the history item's `pipeline` field.
+///
+/// # Arguments
+/// * `app` - Tauri app handle for accessing settings, history and jobs
+/// * `history_id` - The UUID of the history item
+///
+/// # Returns
+/// The job ID
+#[tauri::command]
+pub fn start_pipeline_job(app: AppHandle, history_id: String) -> Result<String, String> {
+    let item = app
+        .state::<HistoryRepository>()
+        .get(&history_id)?
+        .ok_or_else(|| format!("History item not found: {}", history_id))?;
+    match item.pipeline {
+        Some(run) if run.next_step().is_some() => Ok(submit_pipeline_job(&app, history_id)),
+        Some(_) => Err(format!("Pipeline already completed: {}", history_id)),
+        None => Err(format!("History item has no pipeline: {}", history_id)),
+    }
+}
+
+/// Resumes the pipeline of a history item whose queued transcription was saved
+///
+/// Does nothing for items without a pipeline or with a completed one.
+pub(crate) fn resume_pipeline(app: &AppHandle, history_id: &str) {
+    let item = match app.state::<HistoryRepository>().get(history_id) {
+        Ok(item) => item,
+        Err(e) => {
+            log::warn!("Failed to resume pipeline of {}: {}", history_id, e);
+            return;
+        }
+    };
+    let run = item.and_then(|item| item.pipeline);
+    if run.is_some_and(|run| run.next_step().is_some()) {
+        submit_pipeline_job(app, history_id.to_string());
+    }
+}
+
+/// Queues a job running the pipeline of a history item
+fn submit_pipeline_job(app: &AppHandle, history_id: String) -> String {
+    let jobs = app.state::<JobManager>().inner().clone();
+    let runner = AppStepRunner { app: app.clone() };
+    jobs.submit(
+        JobKind::Pipeline,
+        Some(history_id.clone()),
+        move |progress| async move {
+            let history = runner.app.state::<HistoryRepository>();
+            run_pipeline(history.inner(), &runner, &history_id, |fraction| {
+                progress.set(fraction)
+            })
+            .await
+        },
+    )
+}
+
+/// Queues a job transcribing the audio file of an existing history item
+fn submit_history_transcription_job(app: &AppHandle, history_id: String) -> String {
+    let jobs = app.state::<JobManager>().inner().clone();
+    let runner = AppStepRunner { app: app.clone() };
+    jobs.submit(
+        JobKind::Transcription,
+        Some(history_id.clone()),
+        move |_| async move {
+            runner.run(&PipelineStep::Transcribe, &history_id).await?;
+            let item = runner
+                .app
+                .state::<HistoryRepository>()
+                .get(&history_id)
+                .map_err(|e| JobError::new("history_error", e))?;
+            Ok(JobOutput {
+                text: item.map(|item| item.transcription).unwrap_or_default(),
+                history_id: Some(history_id),
+            })
+        },
+    )
+}
+
+/// Runs pipeline steps with the providers and custom actions from settings
+struct AppStepRunner {
+    app: AppHandle,
+}
+
+impl StepRunner for AppStepRunner {
+    async fn run(&self, step: &PipelineStep, history_id: &str) -> Result<Option<String>, JobError> {
+        let item = self
+            .app
+            .state::<HistoryRepository>()
+            .get(history_id)
+            .map_err(|e| JobError::new("history_error", e))?
+            .ok_or_else(|| {
+                JobError::new(
+                    "history_not_found",
+                    format!("History item not found: {}", history_id),
+                )
+            })?;
+
+        match step {
+            PipelineStep::Transcribe => {
+                let settings = get_settings_internal(&self.app)
+                    .map_err(|e| transcription_job_error(TranscriptionError::Unknown(e)))?;
+                let provider =
+                    provider_from_settings(&settings).map_err(transcription_job_error)?;
+                let transcription =
+                    transcribe_audio_file(&item.file_path, &settings.language, provider.as_ref())
+                        .await
+                        .map_err(|e| {
+                            // The queue fills in the item and resumes the pipeline later
+                            let queued = queue_if_offline(
+                                &self.app,
+                                &item.file_path,
+                                item.duration_seconds,
+                                Some(history_id),
+                                &e,
+                            );
+                            if queued {
+                                JobError::new(
+                                    "queued_offline",
+                                    "No internet connection. The recording will be transcribed when the connection is back.",
+                                )
+                            } else {
+                                transcription_job_error(e)
+                            }
+                        })?;
+
+                self.app
+                    .state::<HistoryRepository>()
+                    .update_transcription(
+                        history_id,
+                        &transcription.text,
+                        transcription.segments.as_deref(),
+                    )
+                    .map_err(|e| JobError::new("history_error", e))?;
+                remove_from_queue(&self.app, &item.file_path);
+                Ok(None)
+            }
+            PipelineStep::Summarize { template_id } => {
+                let (api_key, options) = summarization_request(
+                    &self.app,
+                    template_id.as_deref(),
+                    Some(item.duration_seconds),
+                    Some(&item.created_at),
+                )
+                .map_err(summarization_job_error)?;
+                let summary = summarize_text(&item.transcription, &api_key, &options)
+                    .await
+                    .map_err(summarization_job_error)?;
+
+                self.app
+                    .state::<HistoryRepository>()
+                    .update_summary(history_id, &summary.summary)
+                    .map_err(|e| JobError::new("history_error", e))?;
+                Ok(None)
+            }
+            PipelineStep::CustomAction { action_id } => {
+                let response = execute_custom_action(
+                    &self.app,
+                    action_id,
+                    item.transcription,
+                    Some(history_id),
+                )
+                .await;
+                if response.success {
+                    Ok(response.message)
+                } else {
+                    Err(JobError::new(
+                        response.error_type.as_deref().unwrap_or("unknown"),
+                        response.message.unwrap_or_default(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Pending Transcription Queue Commands
+// ============================================================================
+
+/// Returns the recordings waiting to be transcribed once the network is back
+#[tauri::command]
+pub fn list_pending_transcriptions(
+    history: State<'_, HistoryRepository>,
+) -> Result<Vec<PendingTranscription>, String> {
+    history.list_pending()
+}
+
+/// Retries the pending transcription queue now
+///
+/// Call this when connectivity returns instead of waiting for the next
+/// backoff round. Results are reported with `pending-transcription-completed`
+/// events.
+#[tauri::command]
+pub fn retry_pending_transcriptions(queue: State<'_, PendingQueue>) {
+    queue.wake()
+}
+
+/// Removes a recording from the pending transcription queue without transcribing it
+#[tauri::command]
+pub fn remove_pending_transcription(
+    history: State<'_, HistoryRepository>,
+    file_path: String,
+) -> Result<(), String> {
+    history.remove_pending(&file_path).map(|_| ())
+}
+
+// ============================================================================
+// Custom Action Commands
+// ============================================================================
+
+/// Runs a custom action from settings with the given transcription
+///
+/// # Arguments
+/// * `action_id` - The ID of the custom action to run
+/// * `text` - The transcription text to send
+/// * `history_id` - History item that fills the `{summary}`, `{title}`,
+///   `{duration}` and `{created_at}` placeholders (optional). Required for
+///   actions that store their result as the item's transcription or summary.
+///
+/// # Returns
+/// An `ExternalServiceResponse` indicating success or failure
+#[tauri::command]
+pub async fn run_custom_action(
+    app: AppHandle,
+    action_id: String,
+    text: String,
+    history_id: Option<String>,
+) -> ExternalServiceResponse {
+    execute_custom_action(&app, &action_id, text, history_id.as_deref()).await
+}
+
+/// Runs a custom action and stores its result on the history item if the
+/// action's response handling asks for it
+async fn execute_custom_action(
+    app: &AppHandle,
+    action_id: &str,
+    text: String,
+    history_id: Option<&str>,
+) -> ExternalServiceResponse {
+    let (action, context) = match custom_action_request(app, action_id, text, history_id) {
+        Ok(request) => request,
+        Err(e) => return ExternalServiceResponse::error(e),
+    };
+
+    let response = call_custom_action_impl(&ReqwestClient, &action, &context).await;
+    match (response.success, &response.message, history_id) {
+        (true, Some(result), Some(id)) => {
+            match store_action_result(app, action.response_action, id, result) {
+                Ok(()) => response,
+                Err(e) => ExternalServiceResponse::error(ExternalServiceError::ServiceError(e)),
+            }
+        }
+        _ => response,
+    }
+}
+
+/// Stores the result of a custom action on the history item, if its
+/// response action asks for it
+fn store_action_result(
+    app: &AppHandle,
+    response_action: ResponseAction,
+    history_id: &str,
+    result: &str,
+) -> Result<(), String> {
+    let history = app.state::<HistoryRepository>();
+    match response_action {
+        ResponseAction::ReplaceTranscription => {
+            history.update_transcription(history_id, result, None)
+        }
+        ResponseAction::ReplaceSummary => history.update_summary(history_id, result),
+        ResponseAction::Show | ResponseAction::OpenUrl => Ok(()),
+    }
+}
+
+/// Looks up a custom action and the values for its body template
+fn custom_action_request(
+    app: &AppHandle,
+    action_id: &str,
+    text: String,
+    history_id: Option<&str>,
+) -> Result<(CustomAction, ActionContext), ExternalServiceError> {
+    let settings = get_settings_internal(app).map_err(ExternalServiceError::ServiceError)?;
+    let action = settings
+        .custom_actions
+        .into_iter()
+        .find(|action| action.id == action_id)
+        .ok_or_else(|| {
+            ExternalServiceError::InvalidConfig(format!("action {} not found", action_id))
+        })?;
+
+    if action.response_action.updates_history() && history_id.is_none() {
+        return Err(ExternalServiceError::InvalidConfig(format!(
+            "action {} stores its result on a history item, but none was given",
+            action_id
+        )));
+    }
+
+    let item = match history_id {
+        Some(id) => app
+            .state::<HistoryRepository>()
+            .get(id)
+            .map_err(ExternalServiceError::ServiceError)?,
+        None => None,
+    };
+
+    let context = ActionContext {
+        transcription: text,
+        summary: item.as_ref().and_then(|item| item.summary.clone()),
+        title: item.as_ref().and_then(|item| item.title.clone()),
+        language: settings.language,
+        duration_seconds: item.as_ref().map(|item| item.duration_seconds),
+        created_at: item.map(|item| item.created_at),
+    };
+    Ok((action, context))
+}
+
+/// Opens a link returned by a custom action in the default browser
+///
+/// Only http(s) URLs are opened, so a response can't launch local files or
+/// programs.
+#[tauri::command]
+pub fn open_url(url: String) -> Result<(), String> {
+    validate_url(&url).map_err(|e| e.user_message())?;
+    open::that_detached(&url).map_err(|e| format!("Failed to open {}: {}", url, e))
 }
 
 /// Helper function to retrieve settings from store
-fn get_settings_internal(app: &AppHandle) -> Result<Settings, String> {
+///
+/// Unlike `get_settings`, this fills in the full API keys and custom action
+/// credentials from the secret store, for backend code that sends requests.
+pub(crate) fn get_settings_internal(app: &AppHandle) -> Result<Settings, String> {
     let store = app
         .store(SETTINGS_STORE_FILE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
-    match store.get(SETTINGS_KEY) {
+    let mut settings: Settings = match store.get(SETTINGS_KEY) {
         Some(value) => {
             // serde(default) will fill in missing fields
             serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to parse settings: {}", e))
+                .map_err(|e| format!("Failed to parse settings: {}", e))?
         }
-        None => Ok(Settings::default()),
+        None => Settings::default(),
+    };
+
+    let secrets = app.state::<SecretStore>();
+    for (name, secret) in settings_secrets_mut(&mut settings) {
+        *secret = resolve_secret(&secrets, &name, secret.take());
     }
+    Ok(settings)
 }
 
@@ -0,0 +1,376 @@
+//! Export of history items to subtitle and document formats.
+//!
+//! Subtitle formats (SRT, WebVTT) use the timestamped segments stored with the
+//! transcription. Items recorded before segments were available are exported
+//! as a single cue spanning the whole recording.
+
+use crate::history::HistoryItem;
+use crate::transcription::TranscriptionSegment;
+use crate::util::format_duration;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Supported export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// SubRip subtitles (.srt)
+    Srt,
+    /// WebVTT subtitles (.vtt)
+    Vtt,
+    /// Plain transcript text (.txt)
+    Txt,
+    /// Markdown document with metadata, summary and transcript (.md)
+    Markdown,
+    /// The full history item as JSON (.json)
+    Json,
+}
+
+impl ExportFormat {
+    /// Default file extension for the format (without the dot)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+            ExportFormat::Txt => "txt",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Render a history item in the given format
+pub fn render_history_item(item: &HistoryItem, format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Srt => Ok(render_srt(&cues(item))),
+        ExportFormat::Vtt => Ok(render_vtt(&cues(item))),
+        ExportFormat::Txt => Ok(format!("{}\n", item.transcription.trim())),
+        ExportFormat::Markdown => Ok(render_markdown(item)),
+        ExportFormat::Json => serde_json::to_string_pretty(item)
+            .map_err(|e| format!("Failed to serialize history item: {}", e)),
+    }
+}
+
+/// Render a history item and write it to `path`
+pub fn export_to_file(item: &HistoryItem, format: ExportFormat, path: &str) -> Result<(), String> {
+    let content = render_history_item(item, format)?;
+
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            return Err(format!("Directory does not exist: {}", parent.display()));
+        }
+    }
+
+    fs::write(path, content).map_err(|e| format!("Failed to write export file: {}", e))
+}
+
+/// Segments to use as subtitle cues (falls back to one cue for the whole recording)
+fn cues(item: &HistoryItem) -> Vec<TranscriptionSegment> {
+    match &item.segments {
+        Some(segments) if !segments.is_empty() => segments.clone(),
+        _ => vec![TranscriptionSegment {
+            start: 0.0,
+            end: item.duration_seconds,
+            text: item.transcription.trim().to_string(),
+            words: None,
+        }],
+    }
+}
+
+fn render_srt(cues: &[TranscriptionSegment]) -> String {
+    let mut out = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            cue_text(&cue.text)
+        ));
+    }
+    out
+}
+
+fn render_vtt(cues: &[TranscriptionSegment]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            cue_text(&cue.text)
+        ));
+    }
+    out
+}
+
+/// Text of a subtitle cue
+///
+/// A blank line ends the cue and `-->` would be read as a timing line, so
+/// paragraphs are joined line by line and arrows shortened.
+fn cue_text(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.replace("-->", "->"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_markdown(item: &HistoryItem) -> String {
+    let mut out = match &item.title {
+        Some(title) => format!("# {}\n\n", title),
+        None => format!("# Recording {}\n\n", item.created_at),
+    };
+    out.push_str(&format!("- **Date:** {}\n", item.created_at));
+    out.push_str(&format!(
+        "- **Duration:** {}\n",
+        format_duration(item.duration_seconds)
+    ));
+    out.push_str(&format!("- **File:** {}\n", item.file_path));
+    if let Some(folder) = &item.folder {
+        out.push_str(&format!("- **Folder:** {}\n", folder));
+    }
+    if !item.tags.is_empty() {
+        out.push_str(&format!("- **Tags:** {}\n", item.tags.join(", ")));
+    }
+
+    if let Some(summary) = item.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        out.push_str(&format!("\n## Summary\n\n{}\n", summary.trim()));
+    }
+
+    out.push_str(&format!(
+        "\n## Transcript\n\n{}\n",
+        item.transcription.trim()
+    ));
+    out
+}
+
+/// Format seconds as `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT)
+fn format_timestamp(seconds: f64, separator: char) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        separator,
+        total_ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_item() -> HistoryItem {
+        HistoryItem {
+            id: "test-uuid".to_string(),
+            file_path: "/path/to/file.webm".to_string(),
+            duration_seconds: 3725.4,
+            transcription: "Hello world. How are you?".to_string(),
+            created_at: "2024-01-21T10:30:00Z".to_string(),
+            summary: Some("- Greeting\n- Question".to_string()),
+            segments: Some(vec![
+                TranscriptionSegment {
+                    start: 0.0,
+                    end: 1.25,
+                    text: "Hello world.".to_string(),
+                    words: None,
+                },
+                TranscriptionSegment {
+                    start: 3600.5,
+                    end: 3602.042,
+                    text: "How are you?".to_string(),
+                    words: None,
+                },
+            ]),
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+            pipeline: None,
+        }
+    }
+
+    /// Parse `HH:MM:SS,mmm` / `HH:MM:SS.mmm` back into seconds
+    fn parse_timestamp(value: &str) -> f64 {
+        let value = value.replace(',', ".");
+        let parts: Vec<&str> = value.split(':').collect();
+        let hours: f64 = parts[0].parse().unwrap();
+        let minutes: f64 = parts[1].parse().unwrap();
+        let seconds: f64 = parts[2].parse().unwrap();
+        hours * 3600.0 + minutes * 60.0 + seconds
+    }
+
+    /// Parse subtitle cues (SRT or WebVTT) into (start, end, text)
+    fn parse_cues(content: &str) -> Vec<(f64, f64, String)> {
+        content
+            .split("\n\n")
+            .filter_map(|block| {
+                let mut lines = block.lines().skip_while(|l| !l.contains(" --> "));
+                let (start, end) = lines.next()?.split_once(" --> ")?;
+                let text = lines.collect::<Vec<_>>().join("\n");
+                Some((parse_timestamp(start), parse_timestamp(end), text))
+            })
+            .collect()
+    }
+
+    fn assert_cues_match(content: &str, item: &HistoryItem) {
+        let parsed = parse_cues(content);
+        let segments = item.segments.as_ref().unwrap();
+        assert_eq!(parsed.len(), segments.len());
+        for (cue, segment) in parsed.iter().zip(segments) {
+            assert!((cue.0 - segment.start).abs() < 0.001);
+            assert!((cue.1 - segment.end).abs() < 0.001);
+            assert_eq!(cue.2, segment.text);
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(3661.5, ','), "01:01:01,500");
+        assert_eq!(format_timestamp(59.9999, '.'), "00:01:00.000");
+    }
+
+    #[test]
+    fn test_srt_round_trip() {
+        let item = sample_item();
+        let srt = render_history_item(&item, ExportFormat::Srt).unwrap();
+
+        assert!(srt.starts_with("1\n00:00:00,000 --> 00:00:01,250\nHello world.\n"));
+        assert!(srt.contains("\n2\n01:00:00,500 --> 01:00:02,042\n"));
+        assert_cues_match(&srt, &item);
+    }
+
+    #[test]
+    fn test_vtt_round_trip() {
+        let item = sample_item();
+        let vtt = render_history_item(&item, ExportFormat::Vtt).unwrap();
+
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.250\n"));
+        assert_cues_match(&vtt, &item);
+    }
+
+    #[test]
+    fn test_subtitles_without_segments_use_single_cue() {
+        let item = HistoryItem {
+            segments: None,
+            duration_seconds: 12.0,
+            ..sample_item()
+        };
+        let srt = render_history_item(&item, ExportFormat::Srt).unwrap();
+
+        let parsed = parse_cues(&srt);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].1, 12.0);
+        assert_eq!(parsed[0].2, item.transcription);
+    }
+
+    #[test]
+    fn test_subtitles_keep_multi_paragraph_text_in_one_cue() {
+        let item = HistoryItem {
+            segments: None,
+            duration_seconds: 12.0,
+            transcription: "First paragraph.\n\n  Second --> third.\n\n\nLast one.\n".to_string(),
+            ..sample_item()
+        };
+
+        for format in [ExportFormat::Srt, ExportFormat::Vtt] {
+            let content = render_history_item(&item, format).unwrap();
+            let parsed = parse_cues(&content);
+            assert_eq!(parsed.len(), 1, "{}", content);
+            assert_eq!(parsed[0].0, 0.0);
+            assert_eq!(parsed[0].1, 12.0);
+            assert_eq!(parsed[0].2, "First paragraph.\nSecond -> third.\nLast one.");
+        }
+    }
+
+    #[test]
+    fn test_txt_round_trip() {
+        let item = sample_item();
+        let txt = render_history_item(&item, ExportFormat::Txt).unwrap();
+        assert_eq!(txt.trim_end(), item.transcription);
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let item = sample_item();
+        let md = render_history_item(&item, ExportFormat::Markdown).unwrap();
+
+        assert!(md.starts_with("# Recording 2024-01-21T10:30:00Z\n"));
+        assert!(md.contains("- **Duration:** 1:02:05\n"));
+        assert!(md.contains("- **File:** /path/to/file.webm\n"));
+
+        let (before_transcript, transcript) = md.split_once("\n## Transcript\n\n").unwrap();
+        let (_, summary) = before_transcript.split_once("\n## Summary\n\n").unwrap();
+        assert_eq!(summary.trim_end(), item.summary.as_deref().unwrap());
+        assert_eq!(transcript.trim_end(), item.transcription);
+    }
+
+    #[test]
+    fn test_markdown_uses_title_tags_and_folder() {
+        let item = HistoryItem {
+            title: Some("Weekly sync".to_string()),
+            tags: vec!["team".to_string(), "work".to_string()],
+            folder: Some("Meetings".to_string()),
+            ..sample_item()
+        };
+        let md = render_history_item(&item, ExportFormat::Markdown).unwrap();
+
+        assert!(md.starts_with("# Weekly sync\n"));
+        assert!(md.contains("- **Folder:** Meetings\n"));
+        assert!(md.contains("- **Tags:** team, work\n"));
+    }
+
+    #[test]
+    fn test_markdown_without_summary_omits_section() {
+        let item = HistoryItem {
+            summary: None,
+            ..sample_item()
+        };
+        let md = render_history_item(&item, ExportFormat::Markdown).unwrap();
+        assert!(!md.contains("## Summary"));
+        assert!(md.contains("## Transcript"));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let item = sample_item();
+        let json = render_history_item(&item, ExportFormat::Json).unwrap();
+
+        let deserialized: HistoryItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, item);
+    }
+
+    #[test]
+    fn test_export_format_deserializes_lowercase() {
+        let format: ExportFormat = serde_json::from_str("\"markdown\"").unwrap();
+        assert_eq!(format, ExportFormat::Markdown);
+        assert_eq!(format.extension(), "md");
+    }
+
+    #[test]
+    fn test_export_to_file_writes_content() {
+        let item = sample_item();
+        let path = std::env::temp_dir().join(format!("evervoice-export-{}.srt", item.id));
+        let path_str = path.to_str().unwrap();
+
+        export_to_file(&item, ExportFormat::Srt, path_str).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert_cues_match(&content, &item);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_export_to_file_missing_directory_fails() {
+        let result = export_to_file(
+            &sample_item(),
+            ExportFormat::Txt,
+            "/nonexistent/evervoice/export.txt",
+        );
+        assert!(result.is_err());
+    }
+}
@@ -1,11 +1,38 @@
 //! External service proxy module for calling custom user-configured endpoints.
 //!
-//! This module provides functionality to POST transcription text to external
-//! services configured by the user in settings.
+//! This module provides functionality to send transcription text to external
+//! services configured by the user in settings. Each custom action chooses
+//! its HTTP method, headers, authentication and a body template, and can
+//! sign its requests with a shared secret.
 
+use crate::retry::{with_retry, RetryPolicy, Retryable};
+use crate::settings::{CustomAction, CustomActionAuth, HttpMethod, PayloadField, ResponseAction};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
 use thiserror::Error;
 
+/// Timeout for a request when the custom action doesn't set one
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Header with the Unix timestamp (seconds) a signed request was created at
+pub const TIMESTAMP_HEADER: &str = "X-EverVoice-Timestamp";
+
+/// Header with the HMAC-SHA256 signature of a signed request
+pub const SIGNATURE_HEADER: &str = "X-EverVoice-Signature";
+
+/// Retries for rate limits and server errors: 3 attempts, 1s then 2s apart
+/// unless the service sends `Retry-After`
+const RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 3,
+    base_delay: Duration::from_millis(1000),
+    max_delay: Duration::from_secs(60),
+};
+
 /// External service call error types
 #[derive(Error, Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "message")]
@@ -18,6 +45,31 @@ pub enum ExternalServiceError {
 
     #[error("Service error: {0}")]
     ServiceError(String),
+
+    #[error("Invalid custom action: {0}")]
+    InvalidConfig(String),
+
+    #[error("Request timed out after {0} seconds")]
+    Timeout(u64),
+
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
+
+    #[error("Request rejected with status {status}: {message}")]
+    ClientError { status: u16, message: String },
+
+    #[error("Rate limit exceeded")]
+    RateLimited { retry_after_secs: Option<u64> },
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Server error {status}: {message}")]
+    ServerError {
+        status: u16,
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
 }
 
 impl ExternalServiceError {
@@ -37,10 +89,92 @@ impl ExternalServiceError {
             ExternalServiceError::ServiceError(msg) => {
                 format!("External service returned an error: {}", msg)
             }
+            ExternalServiceError::InvalidConfig(msg) => {
+                format!("Custom action is not configured correctly: {}", msg)
+            }
+            ExternalServiceError::Timeout(secs) => {
+                format!("External service did not respond within {} seconds.", secs)
+            }
+            ExternalServiceError::AuthFailed(_) => {
+                "External service rejected the credentials. Please check the action's authentication settings."
+                    .to_string()
+            }
+            ExternalServiceError::ClientError { status, message } => {
+                format!("External service rejected the request ({}): {}", status, message)
+            }
+            ExternalServiceError::RateLimited {
+                retry_after_secs: Some(secs),
+            } => format!(
+                "External service is receiving too many requests. Please try again in {} seconds.",
+                secs
+            ),
+            ExternalServiceError::RateLimited { .. } => {
+                "External service is receiving too many requests. Please try again later."
+                    .to_string()
+            }
+            ExternalServiceError::InvalidResponse(msg) => {
+                format!("External service returned an unexpected response: {}", msg)
+            }
+            ExternalServiceError::ServerError {
+                status, message, ..
+            } => format!("External service returned an error ({}): {}", status, message),
+        }
+    }
+
+    /// Map an unsuccessful HTTP response to an error
+    ///
+    /// `retry_after` is the raw `Retry-After` header, if the service sent one.
+    pub fn from_status(status: u16, body: String, retry_after: Option<&str>) -> Self {
+        let retry_after_secs = retry_after.and_then(|value| parse_retry_after(value, Utc::now()));
+        let message = if body.trim().is_empty() {
+            format!("HTTP {}", status)
+        } else {
+            body
+        };
+
+        match status {
+            401 | 403 => ExternalServiceError::AuthFailed(message),
+            429 => ExternalServiceError::RateLimited { retry_after_secs },
+            400..=499 => ExternalServiceError::ClientError { status, message },
+            _ => ExternalServiceError::ServerError {
+                status,
+                message,
+                retry_after_secs,
+            },
         }
     }
 }
 
+impl Retryable for ExternalServiceError {
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExternalServiceError::RateLimited { .. } | ExternalServiceError::ServerError { .. }
+        )
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ExternalServiceError::RateLimited { retry_after_secs }
+            | ExternalServiceError::ServerError {
+                retry_after_secs, ..
+            } => retry_after_secs.map(Duration::from_secs),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a `Retry-After` header: delay in seconds or an HTTP date
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - now).num_seconds().max(0) as u64)
+}
+
 /// Response from external service call
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExternalServiceResponse {
@@ -50,6 +184,10 @@ pub struct ExternalServiceResponse {
     pub message: Option<String>,
     /// Error type if failed
     pub error_type: Option<String>,
+    /// How `message` should be handled, for successful custom actions.
+    /// With `openUrl` the frontend opens `message`; with `replaceTranscription`
+    /// and `replaceSummary` it has already been stored on the history item.
+    pub response_action: Option<ResponseAction>,
 }
 
 impl ExternalServiceResponse {
@@ -59,6 +197,7 @@ impl ExternalServiceResponse {
             success: true,
             message,
             error_type: None,
+            response_action: None,
         }
     }
 
@@ -68,12 +207,20 @@ impl ExternalServiceResponse {
             ExternalServiceError::InvalidUrl(_) => "invalid_url",
             ExternalServiceError::NetworkError(_) => "network_error",
             ExternalServiceError::ServiceError(_) => "service_error",
+            ExternalServiceError::InvalidConfig(_) => "invalid_config",
+            ExternalServiceError::Timeout(_) => "timeout",
+            ExternalServiceError::AuthFailed(_) => "auth_failed",
+            ExternalServiceError::ClientError { .. } => "client_error",
+            ExternalServiceError::RateLimited { .. } => "rate_limited",
+            ExternalServiceError::InvalidResponse(_) => "invalid_response",
+            ExternalServiceError::ServerError { .. } => "server_error",
         };
 
         Self {
             success: false,
             message: Some(err.user_message()),
             error_type: Some(error_type.to_string()),
+            response_action: None,
         }
     }
 }
@@ -88,15 +235,7 @@ struct ExternalServiceRequest {
 /// Replaces newlines and other control characters with spaces
 fn sanitize_text_for_json(text: &str) -> String {
     text.chars()
-        .map(|c| {
-            if c == '\n' || c == '\r' || c == '\t' {
-                ' '
-            } else if c.is_control() {
-                ' '
-            } else {
-                c
-            }
-        })
+        .map(|c| if c.is_control() { ' ' } else { c })
         .collect::<String>()
         // Collapse multiple spaces into one
         .split_whitespace()
@@ -114,13 +253,34 @@ pub fn validate_url(url: &str) -> Result<(), ExternalServiceError> {
     }
 }
 
+/// HTTP request to an external service
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    /// Header names and values, in the order they are sent
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Time allowed for the whole request, including reading the response
+    pub timeout: Duration,
+}
+
+impl ExternalRequest {
+    /// Looks up a header value by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
 /// Trait for HTTP client to enable testing with mocks
 pub trait HttpClient: Send + Sync {
-    /// POST JSON to a URL and return the response body
-    fn post_json(
+    /// Send a request once and return the response body
+    fn send(
         &self,
-        url: &str,
-        body: &str,
+        request: &ExternalRequest,
     ) -> impl std::future::Future<Output = Result<String, ExternalServiceError>> + Send;
 }
 
@@ -128,22 +288,33 @@ pub trait HttpClient: Send + Sync {
 pub struct ReqwestClient;
 
 impl HttpClient for ReqwestClient {
-    async fn post_json(&self, url: &str, body: &str) -> Result<String, ExternalServiceError> {
+    async fn send(&self, request: &ExternalRequest) -> Result<String, ExternalServiceError> {
         let client = reqwest::Client::new();
 
-        let response = client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .body(body.to_string())
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_connect() || e.is_timeout() {
-                    ExternalServiceError::NetworkError(e.to_string())
-                } else {
-                    ExternalServiceError::NetworkError(e.to_string())
-                }
-            })?;
+        let method = match request.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Patch => reqwest::Method::PATCH,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        };
+        let mut builder = client
+            .request(method, &request.url)
+            .timeout(request.timeout);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+
+        let response = builder.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ExternalServiceError::Timeout(request.timeout.as_secs())
+            } else {
+                ExternalServiceError::NetworkError(e.to_string())
+            }
+        })?;
 
         let status = response.status();
 
@@ -154,15 +325,319 @@ impl HttpClient for ReqwestClient {
                 .unwrap_or_else(|_| "OK".to_string());
             Ok(body)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| format!("HTTP {}", status.as_u16()));
-            Err(ExternalServiceError::ServiceError(error_text))
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let error_text = response.text().await.unwrap_or_default();
+            Err(ExternalServiceError::from_status(
+                status.as_u16(),
+                error_text,
+                retry_after.as_deref(),
+            ))
+        }
+    }
+}
+
+/// Send a request, retrying rate limits and server errors with backoff
+pub async fn send_with_retry<C: HttpClient>(
+    client: &C,
+    request: &ExternalRequest,
+) -> Result<String, ExternalServiceError> {
+    with_retry(&RETRY_POLICY, "External service call", || {
+        client.send(request)
+    })
+    .await
+}
+
+/// Values for the placeholders of a custom action's body template
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionContext {
+    pub transcription: String,
+    pub summary: Option<String>,
+    pub title: Option<String>,
+    /// Language code (e.g., "de")
+    pub language: String,
+    pub duration_seconds: Option<f64>,
+    /// RFC 3339 timestamp of the recording
+    pub created_at: Option<String>,
+}
+
+impl ActionContext {
+    /// Copy with line breaks and repeated whitespace collapsed in all text
+    pub fn sanitized(&self) -> Self {
+        Self {
+            transcription: sanitize_text_for_json(&self.transcription),
+            summary: self.summary.as_deref().map(sanitize_text_for_json),
+            title: self.title.as_deref().map(sanitize_text_for_json),
+            ..self.clone()
+        }
+    }
+
+    /// JSON value of a field of the default body; unknown values are `null`
+    fn field_value(&self, field: PayloadField) -> serde_json::Value {
+        match field {
+            PayloadField::Text => self.transcription.clone().into(),
+            PayloadField::Summary => self.summary.clone().into(),
+            PayloadField::Title => self.title.clone().into(),
+            PayloadField::Language => self.language.clone().into(),
+            PayloadField::Duration => self.duration_seconds.map(f64::round).into(),
+            PayloadField::CreatedAt => self.created_at.clone().into(),
         }
     }
 }
 
+/// JSON key of a field of the default body
+fn field_key(field: PayloadField) -> &'static str {
+    match field {
+        PayloadField::Text => "text",
+        PayloadField::Summary => "summary",
+        PayloadField::Title => "title",
+        PayloadField::Language => "language",
+        PayloadField::Duration => "duration",
+        PayloadField::CreatedAt => "createdAt",
+    }
+}
+
+/// Build the default body: a JSON object with the action's payload fields
+pub fn render_payload(fields: &[PayloadField], context: &ActionContext) -> String {
+    let object: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .map(|&field| (field_key(field).to_string(), context.field_value(field)))
+        .collect();
+    serde_json::Value::Object(object).to_string()
+}
+
+/// Replace the placeholders in a body template
+///
+/// Values are escaped for use inside JSON strings, so a template like
+/// `{"text": "{transcription}"}` stays valid JSON whatever the transcription
+/// contains. Unknown values (e.g. `{summary}` before summarizing) become
+/// empty; `{duration}` is the duration in whole seconds.
+pub fn render_body_template(template: &str, context: &ActionContext) -> String {
+    let duration = context
+        .duration_seconds
+        .map(|seconds| format!("{:.0}", seconds))
+        .unwrap_or_default();
+
+    // One pass over the template, so placeholders inside substituted text
+    // (a transcription that says "{summary}") are left alone
+    let mut body = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        body.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        let value = rest.find('}').and_then(|close| {
+            let value = match &rest[1..close] {
+                "transcription" => escape_json(&context.transcription),
+                "summary" => escape_json(context.summary.as_deref().unwrap_or_default()),
+                "title" => escape_json(context.title.as_deref().unwrap_or_default()),
+                "language" => escape_json(&context.language),
+                "duration" => duration.clone(),
+                "created_at" => escape_json(context.created_at.as_deref().unwrap_or_default()),
+                _ => return None,
+            };
+            Some((value, close))
+        });
+        match value {
+            Some((value, close)) => {
+                body.push_str(&value);
+                rest = &rest[close + 1..];
+            }
+            None => {
+                body.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    body.push_str(rest);
+    body
+}
+
+/// Escape a value for use inside a JSON string literal
+fn escape_json(value: &str) -> String {
+    let quoted = serde_json::Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Build the request for a custom action
+pub fn build_action_request(
+    action: &CustomAction,
+    context: &ActionContext,
+) -> Result<ExternalRequest, ExternalServiceError> {
+    validate_url(&action.url)?;
+
+    // JSON escaping keeps verbatim text valid, so sanitizing is only about looks
+    let sanitized;
+    let context = if action.preserve_formatting {
+        context
+    } else {
+        sanitized = context.sanitized();
+        &sanitized
+    };
+
+    let body = match (&action.method, &action.body_template) {
+        (HttpMethod::Get, _) => None,
+        (_, Some(template)) => Some(render_body_template(template, context)),
+        (_, None) => Some(render_payload(&action.payload_fields, context)),
+    };
+
+    let mut headers: Vec<(String, String)> = action
+        .headers
+        .iter()
+        .map(|header| (header.name.clone(), header.value.clone()))
+        .collect();
+
+    match &action.auth {
+        CustomActionAuth::None => {}
+        CustomActionAuth::Bearer { token } => {
+            let token = token
+                .as_deref()
+                .filter(|token| !token.trim().is_empty())
+                .ok_or_else(|| {
+                    ExternalServiceError::InvalidConfig(format!(
+                        "no bearer token is set for {}",
+                        action.name
+                    ))
+                })?;
+            headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+        CustomActionAuth::Basic { username, password } => {
+            let credentials = format!("{}:{}", username, password.as_deref().unwrap_or_default());
+            headers.push((
+                "Authorization".to_string(),
+                format!("Basic {}", BASE64.encode(credentials)),
+            ));
+        }
+    }
+
+    let mut request = ExternalRequest {
+        method: action.method,
+        url: action.url.clone(),
+        headers,
+        body,
+        timeout: Duration::from_secs(action.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+    };
+    if request.body.is_some() && request.header("Content-Type").is_none() {
+        request
+            .headers
+            .push(("Content-Type".to_string(), "application/json".to_string()));
+    }
+    if let Some(secret) = action.signing_secret.as_deref() {
+        if !secret.trim().is_empty() {
+            sign_request(&mut request, secret, Utc::now().timestamp());
+        }
+    }
+    Ok(request)
+}
+
+/// Add timestamp and signature headers to a request
+///
+/// The signature is `sha256=<hex>`, the HMAC-SHA256 of `{timestamp}.{body}`
+/// keyed with the shared secret (an empty body for requests without one).
+/// Receivers recompute it and should reject timestamps more than a few
+/// minutes away from their clock, so captured requests can't be replayed.
+/// Retries resend the original signature.
+pub fn sign_request(request: &mut ExternalRequest, secret: &str, timestamp: i64) {
+    let signature = signature(
+        secret,
+        timestamp,
+        request.body.as_deref().unwrap_or_default(),
+    );
+    request
+        .headers
+        .push((TIMESTAMP_HEADER.to_string(), timestamp.to_string()));
+    request.headers.push((
+        SIGNATURE_HEADER.to_string(),
+        format!("sha256={}", signature),
+    ));
+}
+
+/// Hex-encoded HMAC-SHA256 of `{timestamp}.{body}`
+fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Interpret a successful response body as the action declares
+///
+/// Selects the result with the action's JSON pointer (strings are used as is,
+/// other values as JSON text) and checks it suits the response action.
+pub fn interpret_response(
+    action: &CustomAction,
+    body: &str,
+) -> Result<String, ExternalServiceError> {
+    let result = match action.response_pointer.as_deref() {
+        None => body.to_string(),
+        Some(pointer) => {
+            let value: serde_json::Value = serde_json::from_str(body).map_err(|e| {
+                ExternalServiceError::InvalidResponse(format!("expected JSON ({})", e))
+            })?;
+            match value.pointer(pointer) {
+                Some(serde_json::Value::String(text)) => text.clone(),
+                Some(other) => other.to_string(),
+                None => {
+                    return Err(ExternalServiceError::InvalidResponse(format!(
+                        "no value at {}",
+                        pointer
+                    )))
+                }
+            }
+        }
+    };
+
+    match action.response_action {
+        ResponseAction::Show => {}
+        ResponseAction::OpenUrl => {
+            validate_url(result.trim()).map_err(|_| {
+                ExternalServiceError::InvalidResponse(format!("not a web URL: {}", result))
+            })?;
+            return Ok(result.trim().to_string());
+        }
+        ResponseAction::ReplaceTranscription | ResponseAction::ReplaceSummary => {
+            if result.trim().is_empty() {
+                return Err(ExternalServiceError::InvalidResponse(
+                    "the result is empty".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Run a custom action with the given transcription and recording details
+pub async fn call_custom_action_impl<C: HttpClient>(
+    client: &C,
+    action: &CustomAction,
+    context: &ActionContext,
+) -> ExternalServiceResponse {
+    let request = match build_action_request(action, context) {
+        Ok(request) => request,
+        Err(e) => return ExternalServiceResponse::error(e),
+    };
+
+    let result = send_with_retry(client, &request)
+        .await
+        .and_then(|body| interpret_response(action, &body));
+    match result {
+        Ok(result) => ExternalServiceResponse {
+            response_action: Some(action.response_action),
+            ..ExternalServiceResponse::success(Some(result))
+        },
+        Err(e) => ExternalServiceResponse::error(e),
+    }
+}
+
 /// Call an external service with the given URL and transcription text
 ///
 /// This function:
@@ -195,7 +670,14 @@ pub async fn call_external_service_impl<C: HttpClient>(
     };
 
     // Make the POST request
-    match client.post_json(url, &body).await {
+    let request = ExternalRequest {
+        method: HttpMethod::Post,
+        url: url.to_string(),
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body: Some(body),
+        timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+    };
+    match send_with_retry(client, &request).await {
         Ok(response_body) => ExternalServiceResponse::success(Some(response_body)),
         Err(e) => ExternalServiceResponse::error(e),
     }
@@ -247,13 +729,12 @@ mod tests {
         }
 
         impl HttpClient for MockClient {
-            async fn post_json(
+            async fn send(
                 &self,
-                url: &str,
-                body: &str,
+                request: &ExternalRequest,
             ) -> Result<String, ExternalServiceError> {
-                *self.captured_url.lock().unwrap() = url.to_string();
-                *self.captured_body.lock().unwrap() = body.to_string();
+                *self.captured_url.lock().unwrap() = request.url.clone();
+                *self.captured_body.lock().unwrap() = request.body.clone().unwrap();
                 Ok("OK".to_string())
             }
         }
@@ -288,10 +769,9 @@ mod tests {
         struct MockClient;
 
         impl HttpClient for MockClient {
-            async fn post_json(
+            async fn send(
                 &self,
-                _url: &str,
-                _body: &str,
+                _request: &ExternalRequest,
             ) -> Result<String, ExternalServiceError> {
                 Ok("Request processed successfully".to_string())
             }
@@ -316,10 +796,9 @@ mod tests {
         struct MockClient;
 
         impl HttpClient for MockClient {
-            async fn post_json(
+            async fn send(
                 &self,
-                _url: &str,
-                _body: &str,
+                _request: &ExternalRequest,
             ) -> Result<String, ExternalServiceError> {
                 Err(ExternalServiceError::NetworkError(
                     "Connection refused".to_string(),
@@ -347,10 +826,9 @@ mod tests {
         struct MockClient;
 
         impl HttpClient for MockClient {
-            async fn post_json(
+            async fn send(
                 &self,
-                _url: &str,
-                _body: &str,
+                _request: &ExternalRequest,
             ) -> Result<String, ExternalServiceError> {
                 // Should not be called for invalid URL
                 panic!("Should not make request for invalid URL");
@@ -369,4 +847,485 @@ mod tests {
             .unwrap()
             .contains("must start with http:// or https://"));
     }
+
+    fn webhook_action() -> CustomAction {
+        CustomAction {
+            id: "notion".to_string(),
+            name: "Notion".to_string(),
+            url: "https://api.example.com/pages".to_string(),
+            method: HttpMethod::Post,
+            headers: Vec::new(),
+            auth: CustomActionAuth::None,
+            signing_secret: None,
+            body_template: None,
+            payload_fields: vec![PayloadField::Text],
+            preserve_formatting: false,
+            timeout_secs: None,
+            response_pointer: None,
+            response_action: ResponseAction::Show,
+        }
+    }
+
+    fn meeting_context() -> ActionContext {
+        ActionContext {
+            transcription: "He said \"hi\"\nthen left".to_string(),
+            summary: Some("- greeting".to_string()),
+            title: None,
+            language: "en".to_string(),
+            duration_seconds: Some(83.4),
+            created_at: Some("2024-05-01T10:00:00Z".to_string()),
+        }
+    }
+
+    // Test 6: Body templates escape values and leave unknown values empty
+    #[test]
+    fn test_render_body_template() {
+        let template = r#"{"text": "{transcription}", "summary": "{summary}", "title": "{title}", "lang": "{language}", "seconds": {duration}, "at": "{created_at}"}"#;
+        let body = render_body_template(template, &meeting_context());
+
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["text"], "He said \"hi\"\nthen left");
+        assert_eq!(value["summary"], "- greeting");
+        assert_eq!(value["title"], "");
+        assert_eq!(value["lang"], "en");
+        assert_eq!(value["seconds"], 83);
+        assert_eq!(value["at"], "2024-05-01T10:00:00Z");
+    }
+
+    #[test]
+    fn test_render_body_template_ignores_placeholders_in_values() {
+        let context = ActionContext {
+            transcription: "Type {summary} or {title} here".to_string(),
+            summary: Some("{transcription}".to_string()),
+            ..meeting_context()
+        };
+        let body = render_body_template(
+            r#"{"t": "{transcription}", "s": "{summary}", "x": "{other}"}"#,
+            &context,
+        );
+
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["t"], "Type {summary} or {title} here");
+        assert_eq!(value["s"], "{transcription}");
+        assert_eq!(value["x"], "{other}");
+    }
+
+    // Test 7: Custom actions send their method, headers, auth and body
+    #[test]
+    fn test_build_action_request() {
+        let action = CustomAction {
+            method: HttpMethod::Put,
+            headers: vec![crate::settings::HttpHeader {
+                name: "Notion-Version".to_string(),
+                value: "2022-06-28".to_string(),
+            }],
+            auth: CustomActionAuth::Bearer {
+                token: Some("secret-token".to_string()),
+            },
+            body_template: Some(r#"{"title": "{title}"}"#.to_string()),
+            ..webhook_action()
+        };
+        let request = build_action_request(&action, &meeting_context()).unwrap();
+
+        assert_eq!(request.method, HttpMethod::Put);
+        assert_eq!(request.url, "https://api.example.com/pages");
+        assert_eq!(request.header("notion-version"), Some("2022-06-28"));
+        assert_eq!(request.header("Authorization"), Some("Bearer secret-token"));
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+        assert_eq!(request.body.as_deref(), Some(r#"{"title": ""}"#));
+        assert_eq!(request.timeout, Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+
+        // Without a template the legacy `{"text": ...}` body is sent
+        let request = build_action_request(&webhook_action(), &meeting_context()).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body.unwrap()).unwrap();
+        assert_eq!(body["text"], "He said \"hi\" then left");
+
+        let basic = CustomAction {
+            method: HttpMethod::Get,
+            headers: vec![crate::settings::HttpHeader {
+                name: "content-type".to_string(),
+                value: "text/plain".to_string(),
+            }],
+            auth: CustomActionAuth::Basic {
+                username: "bot".to_string(),
+                password: Some("pass".to_string()),
+            },
+            timeout_secs: Some(5),
+            ..webhook_action()
+        };
+        let request = build_action_request(&basic, &meeting_context()).unwrap();
+        assert_eq!(request.header("Authorization"), Some("Basic Ym90OnBhc3M="));
+        assert_eq!(request.header("Content-Type"), Some("text/plain"));
+        assert!(request.body.is_none());
+        assert_eq!(request.timeout, Duration::from_secs(5));
+    }
+
+    // Test 8: Preserved formatting is sent verbatim and stays valid JSON
+    #[test]
+    fn test_preserve_formatting_keeps_text_verbatim() {
+        let summary = "## Notes\n\n- first\n\t- nested \"quoted\"\r\n- bell \u{7} and \\ backslash";
+        let context = ActionContext {
+            summary: Some(summary.to_string()),
+            ..meeting_context()
+        };
+        let action = CustomAction {
+            payload_fields: vec![
+                PayloadField::Text,
+                PayloadField::Summary,
+                PayloadField::Title,
+                PayloadField::Duration,
+            ],
+            preserve_formatting: true,
+            ..webhook_action()
+        };
+
+        let request = build_action_request(&action, &context).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body.unwrap()).unwrap();
+        assert_eq!(body["text"], "He said \"hi\"\nthen left");
+        assert_eq!(body["summary"], summary);
+        assert_eq!(body["title"], serde_json::Value::Null);
+        assert_eq!(body["duration"], 83.0);
+        assert_eq!(body.as_object().unwrap().len(), 4);
+
+        let template = CustomAction {
+            body_template: Some(r#"{"content": "{summary}\n\n{transcription}"}"#.to_string()),
+            ..action.clone()
+        };
+        let request = build_action_request(&template, &context).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body.unwrap()).unwrap();
+        assert_eq!(
+            body["content"],
+            format!("{}\n\nHe said \"hi\"\nthen left", summary)
+        );
+
+        // Without the option, whitespace is collapsed as before
+        let collapsed = CustomAction {
+            preserve_formatting: false,
+            ..action
+        };
+        let request = build_action_request(&collapsed, &context).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body.unwrap()).unwrap();
+        assert_eq!(
+            body["summary"],
+            "## Notes - first - nested \"quoted\" - bell and \\ backslash"
+        );
+    }
+
+    // Test 9: A bearer action without a stored token is not sent
+    #[tokio::test]
+    async fn test_custom_action_without_token_returns_error() {
+        struct MockClient;
+
+        impl HttpClient for MockClient {
+            async fn send(
+                &self,
+                _request: &ExternalRequest,
+            ) -> Result<String, ExternalServiceError> {
+                panic!("Should not make request without a token");
+            }
+        }
+
+        let action = CustomAction {
+            auth: CustomActionAuth::Bearer { token: None },
+            ..webhook_action()
+        };
+        let response = call_custom_action_impl(&MockClient, &action, &meeting_context()).await;
+
+        assert!(!response.success);
+        assert_eq!(response.error_type, Some("invalid_config".to_string()));
+    }
+
+    // Test 10: Unsuccessful responses map to distinct errors
+    #[test]
+    fn test_error_from_status() {
+        assert!(matches!(
+            ExternalServiceError::from_status(401, "bad token".to_string(), None),
+            ExternalServiceError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            ExternalServiceError::from_status(403, String::new(), None),
+            ExternalServiceError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            ExternalServiceError::from_status(404, "missing".to_string(), None),
+            ExternalServiceError::ClientError { status: 404, .. }
+        ));
+        assert!(matches!(
+            ExternalServiceError::from_status(429, String::new(), Some("5")),
+            ExternalServiceError::RateLimited {
+                retry_after_secs: Some(5)
+            }
+        ));
+
+        let error = ExternalServiceError::from_status(503, String::new(), None);
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), None);
+        assert_eq!(error.to_string(), "Server error 503: HTTP 503".to_string());
+        assert!(!ExternalServiceError::from_status(400, String::new(), None).is_retryable());
+        assert!(!ExternalServiceError::Timeout(30).is_retryable());
+    }
+
+    // Test 11: Retry-After accepts seconds and HTTP dates
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("120", now), Some(120));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:29:30 GMT", now),
+            Some(90)
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    /// Client answering with the given results in order, then "OK"
+    struct SequenceClient {
+        results: std::sync::Mutex<Vec<Result<String, ExternalServiceError>>>,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl SequenceClient {
+        fn new(mut results: Vec<Result<String, ExternalServiceError>>) -> Self {
+            results.reverse();
+            Self {
+                results: std::sync::Mutex::new(results),
+                calls: std::sync::atomic::AtomicUsize::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    impl HttpClient for SequenceClient {
+        async fn send(&self, _request: &ExternalRequest) -> Result<String, ExternalServiceError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.results
+                .lock()
+                .unwrap()
+                .pop()
+                .unwrap_or_else(|| Ok("OK".to_string()))
+        }
+    }
+
+    // Test 12: Server errors are retried, honoring Retry-After
+    #[tokio::test(start_paused = true)]
+    async fn test_server_errors_are_retried() {
+        let client = SequenceClient::new(vec![Err(ExternalServiceError::ServerError {
+            status: 503,
+            message: "maintenance".to_string(),
+            retry_after_secs: Some(4),
+        })]);
+        let start = tokio::time::Instant::now();
+        let response =
+            call_custom_action_impl(&client, &webhook_action(), &meeting_context()).await;
+
+        assert!(response.success);
+        assert_eq!(client.calls(), 2);
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+    }
+
+    // Test 13: Client errors fail at once; rate limits give up after the last attempt
+    #[tokio::test(start_paused = true)]
+    async fn test_client_errors_and_rate_limits() {
+        let client = SequenceClient::new(vec![Err(ExternalServiceError::ClientError {
+            status: 422,
+            message: "invalid".to_string(),
+        })]);
+        let response =
+            call_custom_action_impl(&client, &webhook_action(), &meeting_context()).await;
+        assert_eq!(response.error_type, Some("client_error".to_string()));
+        assert_eq!(client.calls(), 1);
+
+        let rate_limited = || {
+            Err(ExternalServiceError::RateLimited {
+                retry_after_secs: None,
+            })
+        };
+        let client = SequenceClient::new(vec![rate_limited(), rate_limited(), rate_limited()]);
+        let response = call_external_service_impl(&client, "https://example.com", "Hi").await;
+        assert_eq!(response.error_type, Some("rate_limited".to_string()));
+        assert_eq!(client.calls(), 3);
+    }
+
+    /// Receiver-side check, documenting how services should verify requests:
+    /// recompute the signature over `{timestamp}.{body}` in constant time and
+    /// reject timestamps outside a five-minute window to stop replays.
+    fn receiver_accepts(secret: &str, request: &ExternalRequest, now: i64) -> bool {
+        const REPLAY_WINDOW_SECS: i64 = 5 * 60;
+
+        let Some(timestamp) = request
+            .header(TIMESTAMP_HEADER)
+            .and_then(|value| value.parse::<i64>().ok())
+        else {
+            return false;
+        };
+        if (now - timestamp).abs() > REPLAY_WINDOW_SECS {
+            return false;
+        }
+        let Some(received) = request
+            .header(SIGNATURE_HEADER)
+            .and_then(|value| value.strip_prefix("sha256="))
+        else {
+            return false;
+        };
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(request.body.as_deref().unwrap_or_default().as_bytes());
+        let Ok(received) = (0..received.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(received.get(i..i + 2).unwrap_or("zz"), 16))
+            .collect::<Result<Vec<u8>, _>>()
+        else {
+            return false;
+        };
+        mac.verify_slice(&received).is_ok()
+    }
+
+    // Test 14: Signed requests carry a timestamp and HMAC-SHA256 signature
+    #[test]
+    fn test_sign_request() {
+        let mut request = ExternalRequest {
+            method: HttpMethod::Post,
+            url: "https://api.example.com/hook".to_string(),
+            headers: Vec::new(),
+            body: Some(r#"{"text":"Hello"}"#.to_string()),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        };
+        sign_request(&mut request, "shared-secret", 1_700_000_000);
+
+        assert_eq!(request.header(TIMESTAMP_HEADER), Some("1700000000"));
+        // HMAC-SHA256("shared-secret", "1700000000.{\"text\":\"Hello\"}")
+        assert_eq!(
+            request.header(SIGNATURE_HEADER),
+            Some("sha256=ad997c21825f54f8965062e43cb3869425bfb9cd4b93fba42445a19d4d29d787")
+        );
+
+        let action = CustomAction {
+            signing_secret: Some("shared-secret".to_string()),
+            ..webhook_action()
+        };
+        let request = build_action_request(&action, &meeting_context()).unwrap();
+        assert!(request.header(SIGNATURE_HEADER).is_some());
+        assert!(build_action_request(&webhook_action(), &meeting_context())
+            .unwrap()
+            .header(SIGNATURE_HEADER)
+            .is_none());
+    }
+
+    // Test 15: Receivers can detect forged, tampered and replayed requests
+    #[test]
+    fn test_signature_replay_window() {
+        let now = 1_700_000_000;
+        let mut request = ExternalRequest {
+            method: HttpMethod::Post,
+            url: "https://api.example.com/hook".to_string(),
+            headers: Vec::new(),
+            body: Some(r#"{"text":"Hello"}"#.to_string()),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        };
+        sign_request(&mut request, "shared-secret", now);
+
+        assert!(receiver_accepts("shared-secret", &request, now));
+        // Small clock skew in either direction is fine
+        assert!(receiver_accepts("shared-secret", &request, now + 299));
+        assert!(receiver_accepts("shared-secret", &request, now - 60));
+
+        // Forged with another secret
+        assert!(!receiver_accepts("other-secret", &request, now));
+
+        // Replayed after the window closed
+        assert!(!receiver_accepts("shared-secret", &request, now + 301));
+
+        // Tampered body
+        let mut tampered = request.clone();
+        tampered.body = Some(r#"{"text":"Goodbye"}"#.to_string());
+        assert!(!receiver_accepts("shared-secret", &tampered, now));
+
+        // Re-stamped with a fresh timestamp but the old signature
+        let mut restamped = request.clone();
+        restamped
+            .headers
+            .retain(|(name, _)| name != TIMESTAMP_HEADER);
+        restamped
+            .headers
+            .push((TIMESTAMP_HEADER.to_string(), (now + 600).to_string()));
+        assert!(!receiver_accepts("shared-secret", &restamped, now + 600));
+    }
+
+    // Test 16: Responses are interpreted as the action declares
+    #[test]
+    fn test_interpret_response() {
+        let body = r#"{"data": {"url": "https://notion.so/page-1", "id": 7, "text": "  "}}"#;
+        let with = |pointer: Option<&str>, response_action| CustomAction {
+            response_pointer: pointer.map(str::to_string),
+            response_action,
+            ..webhook_action()
+        };
+
+        assert_eq!(
+            interpret_response(&with(None, ResponseAction::Show), "plain text").unwrap(),
+            "plain text"
+        );
+        assert_eq!(
+            interpret_response(&with(Some("/data/id"), ResponseAction::Show), body).unwrap(),
+            "7"
+        );
+        assert_eq!(
+            interpret_response(&with(Some("/data/url"), ResponseAction::OpenUrl), body).unwrap(),
+            "https://notion.so/page-1"
+        );
+
+        let invalid = [
+            // Not JSON
+            interpret_response(&with(Some("/data"), ResponseAction::Show), "OK"),
+            // Missing field
+            interpret_response(&with(Some("/data/missing"), ResponseAction::Show), body),
+            // Not a URL
+            interpret_response(&with(Some("/data/id"), ResponseAction::OpenUrl), body),
+            // Nothing to store
+            interpret_response(
+                &with(Some("/data/text"), ResponseAction::ReplaceSummary),
+                body,
+            ),
+        ];
+        for result in invalid {
+            assert!(matches!(
+                result,
+                Err(ExternalServiceError::InvalidResponse(_))
+            ));
+        }
+    }
+
+    // Test 17: Successful custom actions report their response action
+    #[tokio::test]
+    async fn test_custom_action_returns_extracted_result() {
+        let client = SequenceClient::new(vec![Ok(r#"{"summary": "- cleaned up"}"#.to_string())]);
+        let action = CustomAction {
+            response_pointer: Some("/summary".to_string()),
+            response_action: ResponseAction::ReplaceSummary,
+            ..webhook_action()
+        };
+        let response = call_custom_action_impl(&client, &action, &meeting_context()).await;
+
+        assert!(response.success);
+        assert_eq!(response.message.as_deref(), Some("- cleaned up"));
+        assert_eq!(
+            response.response_action,
+            Some(ResponseAction::ReplaceSummary)
+        );
+
+        let client = SequenceClient::new(vec![Ok("not json".to_string())]);
+        let response = call_custom_action_impl(&client, &action, &meeting_context()).await;
+        assert!(!response.success);
+        assert_eq!(response.error_type, Some("invalid_response".to_string()));
+        assert!(response.response_action.is_none());
+    }
 }
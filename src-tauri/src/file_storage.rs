@@ -1,18 +1,52 @@
+use crate::audio_format::AudioFormat;
 use std::fs;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+/// History database file name inside the application data directory
+const HISTORY_DB_FILE: &str = "history.db";
+
+/// Get the platform-specific application data directory path
+///
+/// - Windows: `%APPDATA%/EverVoice/`
+/// - macOS: `~/Library/Application Support/EverVoice/`
+/// - Linux: `~/.local/share/EverVoice/`
+pub fn get_app_data_dir() -> Result<PathBuf, String> {
+    let base_dir = dirs::data_dir()
+        .or_else(dirs::config_dir)
+        .ok_or_else(|| "Could not determine application data directory".to_string())?;
+
+    Ok(base_dir.join("EverVoice"))
+}
+
 /// Get the platform-specific recordings directory path
 ///
 /// - Windows: `%APPDATA%/EverVoice/recordings/`
 /// - macOS: `~/Library/Application Support/EverVoice/recordings/`
-/// - Linux: `~/.config/EverVoice/recordings/`
+/// - Linux: `~/.local/share/EverVoice/recordings/`
 pub fn get_recordings_dir() -> Result<PathBuf, String> {
-    let base_dir = dirs::data_dir()
-        .or_else(|| dirs::config_dir())
-        .ok_or_else(|| "Could not determine application data directory".to_string())?;
+    Ok(get_app_data_dir()?.join("recordings"))
+}
 
-    Ok(base_dir.join("EverVoice").join("recordings"))
+/// Get the platform-specific directory for local transcription models
+///
+/// - Windows: `%APPDATA%/EverVoice/models/`
+/// - macOS: `~/Library/Application Support/EverVoice/models/`
+/// - Linux: `~/.local/share/EverVoice/models/`
+pub fn get_models_dir() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("models"))
+}
+
+/// Get the history database path, creating the application data directory if necessary
+pub fn get_history_db_path() -> Result<PathBuf, String> {
+    let dir = get_app_data_dir()?;
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create application data directory: {}", e))?;
+    }
+
+    Ok(dir.join(HISTORY_DB_FILE))
 }
 
 /// Ensure the recordings directory exists, creating it if necessary
@@ -27,19 +61,63 @@ pub fn ensure_recordings_dir_exists() -> Result<PathBuf, String> {
     Ok(dir)
 }
 
+/// Ensure the models directory exists, creating it if necessary
+pub fn ensure_models_dir_exists() -> Result<PathBuf, String> {
+    let dir = get_models_dir()?;
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create models directory: {}", e))?;
+    }
+
+    Ok(dir)
+}
+
 /// Generate a unique filename with ISO timestamp and UUID
 ///
-/// Format: `recording-{YYYY-MM-DDTHH-mm-ss}-{uuid}.webm`
-pub fn generate_recording_filename() -> String {
+/// Format: `recording-{YYYY-MM-DDTHH-mm-ss}-{uuid}.{extension}`
+pub fn generate_recording_filename(extension: &str) -> String {
     let timestamp = chrono::Local::now().format("%Y-%m-%dT%H-%M-%S");
     let uuid = Uuid::new_v4();
-    format!("recording-{}-{}.webm", timestamp, uuid)
+    format!("recording-{}-{}.{}", timestamp, uuid, extension)
+}
+
+/// Re-encode audio as mono Opus/OGG if that makes it smaller
+///
+/// Returns `None` when the audio can't be decoded, would not shrink, or the
+/// app was built without the `transcode` feature.
+#[cfg(feature = "transcode")]
+pub fn compress_audio(data: &[u8]) -> Option<Vec<u8>> {
+    let extension = AudioFormat::detect(data).map(AudioFormat::extension);
+    match crate::audio_transcode::transcode_to_ogg_opus(data, extension) {
+        Ok(compressed) if compressed.len() < data.len() => Some(compressed),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("Keeping recording as recorded, transcoding failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Re-encode audio as mono Opus/OGG if that makes it smaller
+///
+/// Always `None`: the app was built without the `transcode` feature.
+#[cfg(not(feature = "transcode"))]
+pub fn compress_audio(_data: &[u8]) -> Option<Vec<u8>> {
+    None
 }
 
 /// Save recording binary data to a file and return the full file path
+///
+/// The recording is compressed to mono Opus/OGG when possible (see
+/// `compress_audio`) and otherwise stored as recorded. The extension follows
+/// the detected audio format; data that isn't recognized is saved as
+/// `.webm`, the format MediaRecorder produces.
 pub fn save_recording_to_file(data: &[u8]) -> Result<String, String> {
     let dir = ensure_recordings_dir_exists()?;
-    let filename = generate_recording_filename();
+    let compressed = compress_audio(data);
+    let data = compressed.as_deref().unwrap_or(data);
+    let format = AudioFormat::detect(data).unwrap_or(AudioFormat::WebM);
+    let filename = generate_recording_filename(format.extension());
     let file_path = dir.join(&filename);
 
     fs::write(&file_path, data)
@@ -64,6 +142,7 @@ pub fn delete_recording_file(file_path: &str) -> Result<(), String> {
 }
 
 /// Clean up orphaned recording files that are older than the specified duration
+/// Files of every supported audio format are considered.
 /// Returns the number of files cleaned up
 #[allow(dead_code)]
 pub fn cleanup_old_recordings(max_age_hours: u32) -> Result<u32, String> {
@@ -85,7 +164,11 @@ pub fn cleanup_old_recordings(max_age_hours: u32) -> Result<u32, String> {
         let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
         let path = entry.path();
 
-        if path.extension().and_then(|s| s.to_str()) == Some("webm") {
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        if AudioFormat::from_extension(extension).is_some() {
             if let Ok(metadata) = fs::metadata(&path) {
                 if let Ok(modified) = metadata.modified() {
                     if modified < cutoff {
@@ -104,6 +187,7 @@ pub fn cleanup_old_recordings(max_age_hours: u32) -> Result<u32, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures;
     use std::fs;
     use std::path::Path;
 
@@ -116,6 +200,21 @@ mod tests {
         assert!(path.ends_with("EverVoice/recordings") || path.ends_with("EverVoice\\recordings"));
     }
 
+    #[test]
+    fn test_models_dir_is_next_to_recordings() {
+        let models = get_models_dir().unwrap();
+        let recordings = get_recordings_dir().unwrap();
+        assert_eq!(models.parent(), recordings.parent());
+        assert!(models.ends_with("EverVoice/models") || models.ends_with("EverVoice\\models"));
+    }
+
+    #[test]
+    fn test_history_db_path_is_in_app_data_dir() {
+        let path = get_history_db_path().unwrap();
+        assert!(path.ends_with("EverVoice/history.db") || path.ends_with("EverVoice\\history.db"));
+        assert!(path.parent().unwrap().exists());
+    }
+
     #[test]
     fn test_ensure_directory_creates_if_not_exists() {
         let result = ensure_recordings_dir_exists();
@@ -128,7 +227,7 @@ mod tests {
 
     #[test]
     fn test_file_naming_convention() {
-        let filename = generate_recording_filename();
+        let filename = generate_recording_filename("webm");
 
         // Should start with "recording-"
         assert!(filename.starts_with("recording-"));
@@ -153,8 +252,8 @@ mod tests {
 
     #[test]
     fn test_file_naming_generates_unique_names() {
-        let name1 = generate_recording_filename();
-        let name2 = generate_recording_filename();
+        let name1 = generate_recording_filename("webm");
+        let name2 = generate_recording_filename("webm");
 
         // Names should be different due to UUID
         assert_ne!(name1, name2);
@@ -181,6 +280,22 @@ mod tests {
         let _ = fs::remove_file(&file_path);
     }
 
+    #[test]
+    fn test_save_recording_uses_detected_extension() {
+        let data = fixtures::audio("silence.m4a");
+
+        // Builds with the `transcode` feature store it as Opus/OGG if smaller
+        let expected = if compress_audio(&data).is_some() {
+            ".ogg"
+        } else {
+            ".m4a"
+        };
+        let file_path = save_recording_to_file(&data).unwrap();
+        assert!(file_path.ends_with(expected));
+
+        let _ = fs::remove_file(&file_path);
+    }
+
     #[test]
     fn test_delete_recording_file() {
         // First create a test file
@@ -1,8 +1,7 @@
+use crate::settings::{Pipeline, PipelineStep};
+use crate::transcription::TranscriptionSegment;
 use serde::{Deserialize, Serialize};
 
-/// Maximum number of history items to keep in storage
-pub const MAX_HISTORY_ITEMS: usize = 100;
-
 /// A recording history item stored in the application data
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +19,21 @@ pub struct HistoryItem {
     /// AI-generated summary (optional, for backward compatibility)
     #[serde(default)]
     pub summary: Option<String>,
+    /// Timestamped transcription segments (optional, for backward compatibility)
+    #[serde(default)]
+    pub segments: Option<Vec<TranscriptionSegment>>,
+    /// User-editable or AI-generated title (optional, for backward compatibility)
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Free-form tags, sorted case-insensitively
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Folder (notebook) the item is filed in, `None` for unfiled items
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Status of the pipeline processing this recording, if one was run
+    #[serde(default)]
+    pub pipeline: Option<PipelineRun>,
 }
 
 impl HistoryItem {
@@ -32,20 +46,84 @@ impl HistoryItem {
             transcription,
             created_at: chrono::Utc::now().to_rfc3339(),
             summary: None,
+            segments: None,
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+            pipeline: None,
         }
     }
 }
 
-/// Sorts history items by created_at in descending order (newest first)
-pub fn sort_history_descending(items: &mut Vec<HistoryItem>) {
-    items.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+/// Progress of a pipeline on a history item
+///
+/// The steps are copied from the pipeline when the run starts, so editing the
+/// pipeline in settings doesn't change runs that already exist.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineRun {
+    /// ID of the pipeline in settings
+    pub pipeline_id: String,
+    /// Name of the pipeline when the run started
+    pub pipeline_name: String,
+    pub steps: Vec<PipelineStepState>,
 }
 
-/// Truncates history to the maximum allowed items
-pub fn truncate_history(items: &mut Vec<HistoryItem>) {
-    if items.len() > MAX_HISTORY_ITEMS {
-        items.truncate(MAX_HISTORY_ITEMS);
+impl PipelineRun {
+    /// Creates a run with every step pending
+    pub fn new(pipeline: &Pipeline) -> Self {
+        Self {
+            pipeline_id: pipeline.id.clone(),
+            pipeline_name: pipeline.name.clone(),
+            steps: pipeline
+                .steps
+                .iter()
+                .map(|step| PipelineStepState {
+                    step: step.clone(),
+                    status: PipelineStepStatus::Pending,
+                    result: None,
+                    error_type: None,
+                    error_message: None,
+                })
+                .collect(),
+        }
     }
+
+    /// Index of the first step that hasn't completed, `None` once all have
+    pub fn next_step(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|state| state.status != PipelineStepStatus::Completed)
+    }
+}
+
+/// Status and outcome of one pipeline step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStepState {
+    pub step: PipelineStep,
+    pub status: PipelineStepStatus,
+    /// Result of a custom action step (if completed)
+    #[serde(default)]
+    pub result: Option<String>,
+    /// Error type (if failed)
+    #[serde(default)]
+    pub error_type: Option<String>,
+    /// User-friendly error message (if failed)
+    #[serde(default)]
+    pub error_message: Option<String>,
+}
+
+/// Lifecycle state of a pipeline step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PipelineStepStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Not run because an earlier step failed
+    Skipped,
 }
 
 #[cfg(test)]
@@ -87,6 +165,11 @@ mod tests {
             transcription: "Hello world".to_string(),
             created_at: "2024-01-21T10:30:00Z".to_string(),
             summary: None,
+            segments: None,
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+            pipeline: None,
         };
 
         let json = serde_json::to_string(&item).unwrap();
@@ -106,6 +189,11 @@ mod tests {
             transcription: "Hello world".to_string(),
             created_at: "2024-01-21T10:30:00Z".to_string(),
             summary: Some("This is a test summary.".to_string()),
+            segments: None,
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+            pipeline: None,
         };
 
         let json = serde_json::to_string(&item).unwrap();
@@ -127,89 +215,68 @@ mod tests {
 
         let item: HistoryItem = serde_json::from_str(json).unwrap();
 
-        // Summary should be None due to serde(default)
+        // Optional fields should be empty due to serde(default)
         assert_eq!(item.summary, None);
+        assert_eq!(item.segments, None);
+        assert_eq!(item.title, None);
+        assert!(item.tags.is_empty());
+        assert_eq!(item.folder, None);
+        assert_eq!(item.pipeline, None);
         assert_eq!(item.id, "test-uuid");
         assert_eq!(item.transcription, "Hello world");
     }
 
     #[test]
-    fn test_sort_history_descending_orders_by_created_at() {
-        let mut items = vec![
-            HistoryItem {
-                id: "1".to_string(),
-                file_path: "/path/1.webm".to_string(),
-                duration_seconds: 60.0,
-                transcription: "First".to_string(),
-                created_at: "2024-01-21T10:00:00Z".to_string(),
-                summary: None,
-            },
-            HistoryItem {
-                id: "3".to_string(),
-                file_path: "/path/3.webm".to_string(),
-                duration_seconds: 60.0,
-                transcription: "Third".to_string(),
-                created_at: "2024-01-21T12:00:00Z".to_string(),
-                summary: None,
-            },
-            HistoryItem {
-                id: "2".to_string(),
-                file_path: "/path/2.webm".to_string(),
-                duration_seconds: 60.0,
-                transcription: "Second".to_string(),
-                created_at: "2024-01-21T11:00:00Z".to_string(),
-                summary: None,
-            },
-        ];
-
-        sort_history_descending(&mut items);
-
-        // Should be sorted newest first (descending by created_at)
-        assert_eq!(items[0].id, "3"); // 12:00 - newest
-        assert_eq!(items[1].id, "2"); // 11:00
-        assert_eq!(items[2].id, "1"); // 10:00 - oldest
-    }
+    fn test_history_item_with_segments_round_trips() {
+        let item = HistoryItem {
+            id: "test-uuid".to_string(),
+            file_path: "/path/to/file.webm".to_string(),
+            duration_seconds: 4.0,
+            transcription: "Hello world".to_string(),
+            created_at: "2024-01-21T10:30:00Z".to_string(),
+            summary: None,
+            segments: Some(vec![TranscriptionSegment {
+                start: 0.0,
+                end: 1.5,
+                text: "Hello world".to_string(),
+                words: None,
+            }]),
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+            pipeline: None,
+        };
 
-    #[test]
-    fn test_truncate_history_limits_to_max_items() {
-        // Create 105 items (more than MAX_HISTORY_ITEMS)
-        let mut items: Vec<HistoryItem> = (0..105)
-            .map(|i| HistoryItem {
-                id: format!("item-{}", i),
-                file_path: format!("/path/{}.webm", i),
-                duration_seconds: 60.0,
-                transcription: format!("Transcription {}", i),
-                created_at: format!("2024-01-21T{:02}:00:00Z", i % 24),
-                summary: None,
-            })
-            .collect();
-
-        assert_eq!(items.len(), 105);
-
-        truncate_history(&mut items);
-
-        // Should be limited to MAX_HISTORY_ITEMS (100)
-        assert_eq!(items.len(), MAX_HISTORY_ITEMS);
-        assert_eq!(items.len(), 100);
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains("\"segments\":[{\"start\":0.0,\"end\":1.5"));
+
+        let deserialized: HistoryItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, item);
     }
 
     #[test]
-    fn test_truncate_history_does_not_truncate_small_list() {
-        let mut items: Vec<HistoryItem> = (0..50)
-            .map(|i| HistoryItem {
-                id: format!("item-{}", i),
-                file_path: format!("/path/{}.webm", i),
-                duration_seconds: 60.0,
-                transcription: format!("Transcription {}", i),
-                created_at: format!("2024-01-21T{:02}:00:00Z", i % 24),
-                summary: None,
-            })
-            .collect();
-
-        truncate_history(&mut items);
-
-        // Should remain at 50 items
-        assert_eq!(items.len(), 50);
-    }
+    fn test_pipeline_run_next_step() {
+        let pipeline = Pipeline {
+            id: "notes".to_string(),
+            name: "Notes".to_string(),
+            steps: vec![
+                PipelineStep::Transcribe,
+                PipelineStep::Summarize { template_id: None },
+            ],
+        };
+        let mut run = PipelineRun::new(&pipeline);
+        assert_eq!(run.pipeline_name, "Notes");
+        assert_eq!(run.next_step(), Some(0));
+
+        run.steps[0].status = PipelineStepStatus::Completed;
+        run.steps[1].status = PipelineStepStatus::Failed;
+        assert_eq!(run.next_step(), Some(1));
 
+        run.steps[1].status = PipelineStepStatus::Completed;
+        assert_eq!(run.next_step(), None);
+
+        let json = serde_json::to_value(&run).unwrap();
+        assert_eq!(json["steps"][1]["step"]["type"], "summarize");
+        assert_eq!(json["steps"][1]["status"], "completed");
+    }
 }
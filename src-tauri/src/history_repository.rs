@@ -0,0 +1,1372 @@
+//! SQLite-backed history repository.
+//!
+//! History used to be stored as a single JSON array in `settings.json`. It now
+//! lives in its own database so items can be added, updated and deleted
+//! individually and the number of items is no longer capped.
+//!
+//! Transcriptions and summaries are mirrored into an FTS5 index by triggers
+//! so history can be searched with ranked, highlighted results.
+//!
+//! The database also holds the queue of recordings whose transcription failed
+//! for lack of network, so they survive app restarts, and the files already
+//! imported from the inbox folder.
+
+use crate::history::{HistoryItem, PipelineRun};
+use crate::inbox::InboxFile;
+use crate::pending_queue::PendingTranscription;
+use crate::transcription::TranscriptionSegment;
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Schema migrations, applied in order. The number of applied migrations is
+/// tracked in `PRAGMA user_version`; never edit an existing entry.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial history table
+    "CREATE TABLE history (
+        id TEXT PRIMARY KEY NOT NULL,
+        file_path TEXT NOT NULL,
+        duration_seconds REAL NOT NULL,
+        transcription TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        summary TEXT,
+        segments TEXT
+    );
+    CREATE INDEX idx_history_created_at ON history(created_at);",
+    // 2: full-text index over transcription and summary
+    "CREATE VIRTUAL TABLE history_fts USING fts5(
+        id UNINDEXED,
+        transcription,
+        summary,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    INSERT INTO history_fts (id, transcription, summary)
+        SELECT id, transcription, COALESCE(summary, '') FROM history;
+    CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+        INSERT INTO history_fts (id, transcription, summary)
+        VALUES (new.id, new.transcription, COALESCE(new.summary, ''));
+    END;
+    CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+        DELETE FROM history_fts WHERE id = old.id;
+    END;
+    CREATE TRIGGER history_fts_update AFTER UPDATE OF transcription, summary ON history BEGIN
+        UPDATE history_fts
+        SET transcription = new.transcription, summary = COALESCE(new.summary, '')
+        WHERE id = old.id;
+    END;",
+    // 3: titles, folders and tags
+    "ALTER TABLE history ADD COLUMN title TEXT;
+    ALTER TABLE history ADD COLUMN folder TEXT;
+    CREATE INDEX idx_history_folder ON history(folder);
+    CREATE TABLE history_tags (
+        history_id TEXT NOT NULL REFERENCES history(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL COLLATE NOCASE,
+        PRIMARY KEY (history_id, tag)
+    );
+    CREATE INDEX idx_history_tags_tag ON history_tags(tag);",
+    // 4: recordings waiting to be transcribed once the network is back
+    "CREATE TABLE pending_transcriptions (
+        file_path TEXT PRIMARY KEY NOT NULL,
+        duration_seconds REAL NOT NULL,
+        created_at TEXT NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        last_attempt_at TEXT
+    );",
+    // 5: status of the pipeline processing each recording (JSON)
+    "ALTER TABLE history ADD COLUMN pipeline TEXT;",
+    // 6: files already imported from the inbox folder
+    "CREATE TABLE inbox_files (
+        source_path TEXT PRIMARY KEY NOT NULL,
+        file_size INTEGER NOT NULL,
+        modified_at TEXT NOT NULL,
+        content_hash TEXT NOT NULL,
+        history_id TEXT,
+        imported_at TEXT NOT NULL
+    );
+    CREATE INDEX idx_inbox_files_hash ON inbox_files(content_hash);",
+    // 7: history item waiting for a queued transcription (pipelines and imports)
+    "ALTER TABLE pending_transcriptions ADD COLUMN history_id TEXT;",
+];
+
+/// Columns selected for a full `HistoryItem` (the history table is aliased `h`)
+const ITEM_COLUMNS: &str = "h.id, h.file_path, h.duration_seconds, h.transcription, h.created_at,
+    h.summary, h.segments, h.title, h.folder,
+    (SELECT json_group_array(tag)
+     FROM (SELECT tag FROM history_tags WHERE history_id = h.id ORDER BY tag)),
+    h.pipeline";
+
+/// Number of columns in `ITEM_COLUMNS`
+const ITEM_COLUMN_COUNT: usize = 11;
+
+/// Columns written when inserting a history row
+const INSERT_COLUMNS: &str =
+    "id, file_path, duration_seconds, transcription, created_at, summary, segments, title, folder,
+    pipeline";
+
+/// Columns selected for an `InboxFile`
+const INBOX_COLUMNS: &str = "source_path, file_size, modified_at, content_hash, history_id";
+
+/// Number of search results returned when the query does not set a limit
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+
+/// Number of tokens in a highlighted snippet
+const SNIPPET_TOKENS: u32 = 16;
+
+/// Markers passed to FTS5 `snippet()`; replaced with `<mark>` after escaping.
+/// Private-use characters so they can't collide with transcribed text.
+const HIGHLIGHT_OPEN: char = '\u{E000}';
+const HIGHLIGHT_CLOSE: char = '\u{E001}';
+
+/// Full-text search request
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchQuery {
+    /// Search terms. Words are ANDed, `"quoted text"` matches a phrase and a
+    /// trailing `*` matches a prefix.
+    pub query: String,
+    /// Only items created at or after this time (RFC 3339 or `YYYY-MM-DD`)
+    pub from: Option<String>,
+    /// Only items created at or before this time (RFC 3339 or `YYYY-MM-DD`,
+    /// a date includes the whole day)
+    pub to: Option<String>,
+    /// Maximum number of results (defaults to 50)
+    pub limit: Option<u32>,
+}
+
+/// A single search hit, best matches first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchResult {
+    pub item: HistoryItem,
+    /// HTML-escaped excerpt of the transcription with matches wrapped in `<mark>`
+    pub transcription_snippet: String,
+    /// HTML-escaped excerpt of the summary, present when the summary matched
+    pub summary_snippet: Option<String>,
+}
+
+/// Repository for recording history, shared as Tauri managed state
+pub struct HistoryRepository {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryRepository {
+    /// Open (or create) the history database at the given path
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open history database: {}", e))?;
+        Self::with_connection(conn)
+    }
+
+    /// Open a temporary in-memory database (for tests)
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open history database: {}", e))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self, String> {
+        conn.pragma_update(None, "foreign_keys", true)
+            .map_err(|e| format!("Failed to configure history database: {}", e))?;
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|_| "History database lock poisoned".to_string())
+    }
+
+    /// Returns all history items ordered by createdAt DESC (newest first)
+    pub fn list(&self) -> Result<Vec<HistoryItem>, String> {
+        self.list_where("1", params![])
+    }
+
+    /// Returns the items carrying a tag (case-insensitive), newest first
+    pub fn list_by_tag(&self, tag: &str) -> Result<Vec<HistoryItem>, String> {
+        self.list_where(
+            "EXISTS (SELECT 1 FROM history_tags t WHERE t.history_id = h.id AND t.tag = ?1)",
+            params![tag.trim()],
+        )
+    }
+
+    /// Returns the items in a folder, newest first; `None` lists unfiled items
+    pub fn list_by_folder(&self, folder: Option<&str>) -> Result<Vec<HistoryItem>, String> {
+        self.list_where("h.folder IS ?1", params![normalize_name(folder)])
+    }
+
+    /// Returns all tags in use, sorted case-insensitively
+    pub fn list_tags(&self) -> Result<Vec<String>, String> {
+        self.list_names("SELECT DISTINCT tag FROM history_tags ORDER BY tag")
+    }
+
+    /// Returns all folders in use, sorted case-insensitively
+    pub fn list_folders(&self) -> Result<Vec<String>, String> {
+        self.list_names(
+            "SELECT DISTINCT folder FROM history WHERE folder IS NOT NULL
+             ORDER BY folder COLLATE NOCASE",
+        )
+    }
+
+    fn list_where(
+        &self,
+        condition: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<HistoryItem>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM history h WHERE {} ORDER BY h.created_at DESC",
+                ITEM_COLUMNS, condition
+            ))
+            .map_err(|e| format!("Failed to query history: {}", e))?;
+
+        let items = stmt
+            .query_map(params, row_to_item)
+            .map_err(|e| format!("Failed to query history: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+
+        Ok(items)
+    }
+
+    fn list_names(&self, sql: &str) -> Result<Vec<String>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed to query history: {}", e))?;
+
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to query history: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+
+        Ok(names)
+    }
+
+    /// Returns a single history item by ID
+    pub fn get(&self, id: &str) -> Result<Option<HistoryItem>, String> {
+        let conn = self.conn()?;
+        conn.query_row(
+            &format!("SELECT {} FROM history h WHERE h.id = ?1", ITEM_COLUMNS),
+            params![id],
+            row_to_item,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read history item: {}", e))
+    }
+
+    /// Inserts a new history item
+    pub fn insert(&self, item: &HistoryItem) -> Result<(), String> {
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to save history: {}", e))?;
+        insert_item(&tx, item, false)
+            .and_then(|_| tx.commit())
+            .map_err(|e| format!("Failed to save history: {}", e))
+    }
+
+    /// Deletes a history item, returning the removed item if it existed
+    pub fn delete(&self, id: &str) -> Result<Option<HistoryItem>, String> {
+        let item = self.get(id)?;
+        if item.is_some() {
+            let conn = self.conn()?;
+            conn.execute("DELETE FROM history WHERE id = ?1", params![id])
+                .map_err(|e| format!("Failed to delete history item: {}", e))?;
+        }
+        Ok(item)
+    }
+
+    /// Updates the summary of a history item
+    pub fn update_summary(&self, id: &str, summary: &str) -> Result<(), String> {
+        self.update_column(id, "summary", Some(summary))
+    }
+
+    /// Replaces the transcription of a history item and its segments
+    ///
+    /// Pass `None` for text that doesn't come with segments; the old ones are
+    /// cleared, since their timestamps belong to the old text.
+    pub fn update_transcription(
+        &self,
+        id: &str,
+        transcription: &str,
+        segments: Option<&[TranscriptionSegment]>,
+    ) -> Result<(), String> {
+        let segments = to_json(segments)?;
+        let conn = self.conn()?;
+        let updated = conn
+            .execute(
+                "UPDATE history SET transcription = ?1, segments = ?2 WHERE id = ?3",
+                params![transcription, segments, id],
+            )
+            .map_err(|e| format!("Failed to save history: {}", e))?;
+
+        if updated == 0 {
+            return Err(format!("History item not found: {}", id));
+        }
+        Ok(())
+    }
+
+    /// Stores the pipeline status of a history item
+    pub fn update_pipeline(&self, id: &str, run: &PipelineRun) -> Result<(), String> {
+        let run = to_json(Some(run))?;
+        self.update_column(id, "pipeline", run.as_deref())
+    }
+
+    /// Sets or clears (`None` or blank) the title of a history item
+    pub fn rename(&self, id: &str, title: Option<&str>) -> Result<(), String> {
+        self.update_column(id, "title", normalize_name(title))
+    }
+
+    /// Moves a history item into a folder, or out of any folder with `None`
+    pub fn move_to_folder(&self, id: &str, folder: Option<&str>) -> Result<(), String> {
+        self.update_column(id, "folder", normalize_name(folder))
+    }
+
+    /// Replaces the tags of a history item
+    ///
+    /// Tags are trimmed; blank tags and case-insensitive duplicates are dropped.
+    pub fn set_tags(&self, id: &str, tags: &[String]) -> Result<(), String> {
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to save tags: {}", e))?;
+
+        let exists = tx
+            .query_row("SELECT 1 FROM history WHERE id = ?1", params![id], |_| {
+                Ok(())
+            })
+            .optional()
+            .map_err(|e| format!("Failed to save tags: {}", e))?
+            .is_some();
+        if !exists {
+            return Err(format!("History item not found: {}", id));
+        }
+
+        tx.execute(
+            "DELETE FROM history_tags WHERE history_id = ?1",
+            params![id],
+        )
+        .and_then(|_| insert_tags(&tx, id, tags))
+        .and_then(|_| tx.commit())
+        .map_err(|e| format!("Failed to save tags: {}", e))
+    }
+
+    fn update_column(&self, id: &str, column: &str, value: Option<&str>) -> Result<(), String> {
+        let conn = self.conn()?;
+        let updated = conn
+            .execute(
+                &format!("UPDATE history SET {} = ?1 WHERE id = ?2", column),
+                params![value, id],
+            )
+            .map_err(|e| format!("Failed to save history: {}", e))?;
+
+        if updated == 0 {
+            return Err(format!("History item not found: {}", id));
+        }
+        Ok(())
+    }
+
+    /// Imports items in a single transaction, skipping IDs that already exist
+    ///
+    /// Returns the number of newly inserted items.
+    pub fn import(&self, items: &[HistoryItem]) -> Result<usize, String> {
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start history import: {}", e))?;
+
+        let mut imported = 0;
+        for item in items {
+            imported += insert_item(&tx, item, true)
+                .map_err(|e| format!("Failed to import history item {}: {}", item.id, e))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit history import: {}", e))?;
+        Ok(imported)
+    }
+
+    /// Adds a recording to the pending transcription queue
+    ///
+    /// Queuing a recording that is already pending only updates its last error
+    /// and, if given, the history item waiting for it.
+    pub fn enqueue_pending(&self, pending: &PendingTranscription) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO pending_transcriptions
+                (file_path, duration_seconds, created_at, attempts, last_error, last_attempt_at,
+                 history_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(file_path) DO UPDATE SET
+                last_error = excluded.last_error,
+                history_id = COALESCE(excluded.history_id, history_id)",
+            params![
+                pending.file_path,
+                pending.duration_seconds,
+                pending.created_at,
+                pending.attempts,
+                pending.last_error,
+                pending.last_attempt_at,
+                pending.history_id,
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to queue transcription: {}", e))
+    }
+
+    /// Returns all pending transcriptions, oldest first
+    pub fn list_pending(&self) -> Result<Vec<PendingTranscription>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT file_path, duration_seconds, created_at, attempts, last_error, last_attempt_at,
+                    history_id
+                 FROM pending_transcriptions ORDER BY created_at, rowid",
+            )
+            .map_err(|e| format!("Failed to read transcription queue: {}", e))?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(PendingTranscription {
+                    file_path: row.get(0)?,
+                    duration_seconds: row.get(1)?,
+                    created_at: row.get(2)?,
+                    attempts: row.get(3)?,
+                    last_error: row.get(4)?,
+                    last_attempt_at: row.get(5)?,
+                    history_id: row.get(6)?,
+                })
+            })
+            .map_err(|e| format!("Failed to read transcription queue: {}", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read transcription queue: {}", e))
+    }
+
+    /// Records a failed retry of a pending transcription
+    pub fn record_pending_attempt(&self, file_path: &str, error: &str) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE pending_transcriptions
+             SET attempts = attempts + 1, last_error = ?1, last_attempt_at = ?2
+             WHERE file_path = ?3",
+            params![error, Utc::now().to_rfc3339(), file_path],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to update transcription queue: {}", e))
+    }
+
+    /// Removes a recording from the pending queue, returning whether it was queued
+    pub fn remove_pending(&self, file_path: &str) -> Result<bool, String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "DELETE FROM pending_transcriptions WHERE file_path = ?1",
+            params![file_path],
+        )
+        .map(|removed| removed > 0)
+        .map_err(|e| format!("Failed to update transcription queue: {}", e))
+    }
+
+    /// Looks up an inbox file by its path
+    pub fn get_inbox_file(&self, source_path: &str) -> Result<Option<InboxFile>, String> {
+        let conn = self.conn()?;
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM inbox_files WHERE source_path = ?1",
+                INBOX_COLUMNS
+            ),
+            params![source_path],
+            inbox_file_from_row,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read inbox files: {}", e))
+    }
+
+    /// Looks up an inbox file by its content hash, preferring imported files
+    pub fn find_inbox_file_by_hash(&self, content_hash: &str) -> Result<Option<InboxFile>, String> {
+        let conn = self.conn()?;
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM inbox_files WHERE content_hash = ?1
+                 ORDER BY history_id IS NULL, imported_at LIMIT 1",
+                INBOX_COLUMNS
+            ),
+            params![content_hash],
+            inbox_file_from_row,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read inbox files: {}", e))
+    }
+
+    /// Records an inbox file as handled, replacing an earlier record for its path
+    pub fn record_inbox_file(&self, file: &InboxFile) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO inbox_files
+                (source_path, file_size, modified_at, content_hash, history_id, imported_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                file.source_path,
+                file.file_size as i64,
+                file.modified_at,
+                file.content_hash,
+                file.history_id,
+                Utc::now().to_rfc3339(),
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to record inbox file: {}", e))
+    }
+
+    /// Full-text search over transcriptions and summaries
+    ///
+    /// Results are ranked by BM25, newest first on ties. A query without any
+    /// searchable terms returns no results.
+    pub fn search(&self, query: &HistorySearchQuery) -> Result<Vec<HistorySearchResult>, String> {
+        let Some(match_expression) = build_match_expression(&query.query) else {
+            return Ok(Vec::new());
+        };
+        let from = query
+            .from
+            .as_deref()
+            .map(|value| parse_date_bound(value, false))
+            .transpose()?;
+        let to = query
+            .to
+            .as_deref()
+            .map(|value| parse_date_bound(value, true))
+            .transpose()?;
+        let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {},
+                        snippet(history_fts, 1, ?2, ?3, '…', ?4),
+                        snippet(history_fts, 2, ?2, ?3, '…', ?4)
+                 FROM history_fts
+                 JOIN history h ON h.id = history_fts.id
+                 WHERE history_fts MATCH ?1
+                   AND (?5 IS NULL OR julianday(h.created_at) >= julianday(?5))
+                   AND (?6 IS NULL OR julianday(h.created_at) <= julianday(?6))
+                 ORDER BY bm25(history_fts), h.created_at DESC
+                 LIMIT ?7",
+                ITEM_COLUMNS
+            ))
+            .map_err(|e| format!("Failed to search history: {}", e))?;
+
+        let results = stmt
+            .query_map(
+                params![
+                    match_expression,
+                    HIGHLIGHT_OPEN.to_string(),
+                    HIGHLIGHT_CLOSE.to_string(),
+                    SNIPPET_TOKENS,
+                    from,
+                    to,
+                    limit,
+                ],
+                |row| {
+                    let summary_snippet: String = row.get(ITEM_COLUMN_COUNT + 1)?;
+                    Ok(HistorySearchResult {
+                        item: row_to_item(row)?,
+                        transcription_snippet: render_snippet(
+                            &row.get::<_, String>(ITEM_COLUMN_COUNT)?,
+                        ),
+                        summary_snippet: summary_snippet
+                            .contains(HIGHLIGHT_OPEN)
+                            .then(|| render_snippet(&summary_snippet)),
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to search history: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read search results: {}", e))?;
+
+        Ok(results)
+    }
+}
+
+/// Convert user input into an FTS5 match expression
+///
+/// Every word and `"quoted phrase"` becomes a quoted FTS5 string so operators
+/// and punctuation in the input can't produce syntax errors. Terms are ANDed.
+fn build_match_expression(query: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut rest = query.trim();
+
+    while !rest.is_empty() {
+        let (term, is_phrase, remainder) = if let Some(stripped) = rest.strip_prefix('"') {
+            match stripped.find('"') {
+                Some(end) => (&stripped[..end], true, &stripped[end + 1..]),
+                None => (stripped, true, ""),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '"')
+                .unwrap_or(rest.len());
+            (&rest[..end], false, &rest[end..])
+        };
+        rest = remainder.trim_start();
+
+        let (term, is_prefix) = match term.strip_suffix('*') {
+            Some(stripped) if !is_phrase => (stripped, true),
+            _ => (term, false),
+        };
+        if !term.chars().any(char::is_alphanumeric) {
+            continue;
+        }
+
+        let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+        terms.push(if is_prefix {
+            format!("{}*", quoted)
+        } else {
+            quoted
+        });
+    }
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Parse an RFC 3339 timestamp or `YYYY-MM-DD` date into a UTC timestamp
+///
+/// Dates map to the start of the day, or its last millisecond when
+/// `end_of_day` is set so the range includes the whole day.
+fn parse_date_bound(value: &str, end_of_day: bool) -> Result<String, String> {
+    let value = value.trim();
+    let timestamp = if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        timestamp.with_timezone(&Utc)
+    } else {
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {}", value))?;
+        let time = if end_of_day {
+            date.and_hms_milli_opt(23, 59, 59, 999)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        time.ok_or_else(|| format!("Invalid date: {}", value))?
+            .and_utc()
+    };
+
+    Ok(timestamp.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+/// HTML-escape an FTS5 snippet and turn the highlight markers into `<mark>` tags
+fn render_snippet(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            HIGHLIGHT_OPEN => out.push_str("<mark>"),
+            HIGHLIGHT_CLOSE => out.push_str("</mark>"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Applies pending schema migrations
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read history schema version: {}", e))?;
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start history migration: {}", e))?;
+        tx.execute_batch(sql)
+            .and_then(|_| tx.pragma_update(None, "user_version", index + 1))
+            .map_err(|e| format!("Failed to migrate history schema to v{}: {}", index + 1, e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit history migration: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn inbox_file_from_row(row: &Row) -> rusqlite::Result<InboxFile> {
+    Ok(InboxFile {
+        source_path: row.get(0)?,
+        file_size: row.get::<_, i64>(1)? as u64,
+        modified_at: row.get(2)?,
+        content_hash: row.get(3)?,
+        history_id: row.get(4)?,
+    })
+}
+
+/// Inserts a history row and its tags, returning the number of inserted rows
+fn insert_item(
+    conn: &Connection,
+    item: &HistoryItem,
+    ignore_existing: bool,
+) -> rusqlite::Result<usize> {
+    let to_sql = |e| rusqlite::Error::ToSqlConversionFailure(Box::new(e));
+    let segments = json_column(item.segments.as_deref()).map_err(to_sql)?;
+    let pipeline = json_column(item.pipeline.as_ref()).map_err(to_sql)?;
+
+    let inserted = conn.execute(
+        &format!(
+            "INSERT {} INTO history ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            if ignore_existing { "OR IGNORE" } else { "" },
+            INSERT_COLUMNS
+        ),
+        params![
+            item.id,
+            item.file_path,
+            item.duration_seconds,
+            item.transcription,
+            item.created_at,
+            item.summary,
+            segments,
+            normalize_name(item.title.as_deref()),
+            normalize_name(item.folder.as_deref()),
+            pipeline,
+        ],
+    )?;
+
+    if inserted > 0 {
+        insert_tags(conn, &item.id, &item.tags)?;
+    }
+    Ok(inserted)
+}
+
+/// Serializes an optional value for a JSON text column
+fn json_column<T: Serialize + ?Sized>(value: Option<&T>) -> serde_json::Result<Option<String>> {
+    value.map(serde_json::to_string).transpose()
+}
+
+/// Like `json_column`, for repository methods
+fn to_json<T: Serialize + ?Sized>(value: Option<&T>) -> Result<Option<String>, String> {
+    json_column(value).map_err(|e| format!("Failed to save history: {}", e))
+}
+
+fn insert_tags(conn: &Connection, id: &str, tags: &[String]) -> rusqlite::Result<()> {
+    let mut stmt =
+        conn.prepare("INSERT OR IGNORE INTO history_tags (history_id, tag) VALUES (?1, ?2)")?;
+    for tag in tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    {
+        stmt.execute(params![id, tag])?;
+    }
+    Ok(())
+}
+
+/// Trim a title or folder name, treating blank names as unset
+fn normalize_name(name: Option<&str>) -> Option<&str> {
+    name.map(str::trim).filter(|name| !name.is_empty())
+}
+
+fn row_to_item(row: &Row<'_>) -> rusqlite::Result<HistoryItem> {
+    let segments: Option<String> = row.get(6)?;
+    let segments = segments
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+    let tags: String = row.get(9)?;
+    let tags = serde_json::from_str(&tags).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(9, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    let pipeline: Option<String> = row.get(10)?;
+    let pipeline = pipeline
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(10, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+    Ok(HistoryItem {
+        id: row.get(0)?,
+        file_path: row.get(1)?,
+        duration_seconds: row.get(2)?,
+        transcription: row.get(3)?,
+        created_at: row.get(4)?,
+        summary: row.get(5)?,
+        segments,
+        title: row.get(7)?,
+        tags,
+        folder: row.get(8)?,
+        pipeline,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PipelineStepStatus;
+    use crate::settings::{Pipeline, PipelineStep};
+
+    fn item(id: &str, created_at: &str) -> HistoryItem {
+        HistoryItem {
+            id: id.to_string(),
+            file_path: format!("/path/{}.webm", id),
+            duration_seconds: 60.0,
+            transcription: format!("Transcription {}", id),
+            created_at: created_at.to_string(),
+            summary: None,
+            segments: None,
+            title: None,
+            tags: Vec::new(),
+            folder: None,
+            pipeline: None,
+        }
+    }
+
+    #[test]
+    fn test_migrations_set_schema_version() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let version: usize = repo
+            .conn()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_insert_and_get_round_trips() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let mut new_item = HistoryItem::new("/path/a.webm".to_string(), 12.5, "Hello".to_string());
+        new_item.segments = Some(vec![TranscriptionSegment {
+            start: 0.0,
+            end: 1.0,
+            text: "Hello".to_string(),
+            words: None,
+        }]);
+
+        repo.insert(&new_item).unwrap();
+
+        assert_eq!(repo.get(&new_item.id).unwrap(), Some(new_item));
+        assert_eq!(repo.get("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_list_orders_by_created_at_descending() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+        repo.insert(&item("3", "2024-01-21T12:00:00Z")).unwrap();
+        repo.insert(&item("2", "2024-01-21T11:00:00Z")).unwrap();
+
+        let ids: Vec<String> = repo.list().unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(ids, vec!["3", "2", "1"]);
+    }
+
+    #[test]
+    fn test_history_is_not_capped() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        for i in 0..150 {
+            repo.insert(&item(
+                &format!("item-{}", i),
+                &format!("2024-01-21T10:{:02}:00Z", i % 60),
+            ))
+            .unwrap();
+        }
+        assert_eq!(repo.list().unwrap().len(), 150);
+    }
+
+    #[test]
+    fn test_delete_returns_removed_item() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+
+        let removed = repo.delete("1").unwrap();
+        assert_eq!(removed.map(|i| i.id), Some("1".to_string()));
+        assert!(repo.list().unwrap().is_empty());
+
+        // Deleting again is a no-op
+        assert_eq!(repo.delete("1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_update_summary() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+
+        repo.update_summary("1", "- Point").unwrap();
+        assert_eq!(
+            repo.get("1").unwrap().unwrap().summary,
+            Some("- Point".to_string())
+        );
+
+        assert!(repo.update_summary("missing", "x").is_err());
+    }
+
+    #[test]
+    fn test_update_transcription_clears_segments() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let mut original = item("1", "2024-01-21T10:00:00Z");
+        original.segments = Some(vec![TranscriptionSegment {
+            start: 0.0,
+            end: 1.0,
+            text: "Hello".to_string(),
+            words: None,
+        }]);
+        repo.insert(&original).unwrap();
+
+        repo.update_transcription("1", "Hello, edited", None)
+            .unwrap();
+        let stored = repo.get("1").unwrap().unwrap();
+        assert_eq!(stored.transcription, "Hello, edited");
+        assert!(stored.segments.is_none());
+
+        // The search index follows the new text
+        let results = repo
+            .search(&HistorySearchQuery {
+                query: "edited".to_string(),
+                ..HistorySearchQuery::default()
+            })
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        assert!(repo.update_transcription("missing", "x", None).is_err());
+    }
+
+    #[test]
+    fn test_update_transcription_with_segments() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+
+        let segments = vec![TranscriptionSegment {
+            start: 0.0,
+            end: 2.0,
+            text: "Hello again".to_string(),
+            words: None,
+        }];
+        repo.update_transcription("1", "Hello again", Some(&segments))
+            .unwrap();
+
+        let stored = repo.get("1").unwrap().unwrap();
+        assert_eq!(stored.transcription, "Hello again");
+        assert_eq!(stored.segments, Some(segments));
+    }
+
+    #[test]
+    fn test_pipeline_round_trip() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let pipeline = Pipeline {
+            id: "notes".to_string(),
+            name: "Notes".to_string(),
+            steps: vec![
+                PipelineStep::Transcribe,
+                PipelineStep::CustomAction {
+                    action_id: "notion".to_string(),
+                },
+            ],
+        };
+        let mut run = PipelineRun::new(&pipeline);
+        repo.insert(&HistoryItem {
+            pipeline: Some(run.clone()),
+            ..item("1", "2024-01-21T10:00:00Z")
+        })
+        .unwrap();
+        assert_eq!(repo.get("1").unwrap().unwrap().pipeline, Some(run.clone()));
+
+        run.steps[0].status = PipelineStepStatus::Completed;
+        run.steps[1].status = PipelineStepStatus::Failed;
+        run.steps[1].error_message = Some("Service unavailable".to_string());
+        repo.update_pipeline("1", &run).unwrap();
+        assert_eq!(repo.get("1").unwrap().unwrap().pipeline, Some(run.clone()));
+
+        assert!(repo.update_pipeline("missing", &run).is_err());
+    }
+
+    #[test]
+    fn test_import_skips_existing_items() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+
+        let imported = repo
+            .import(&[
+                item("1", "2024-01-21T10:00:00Z"),
+                item("2", "2024-01-21T11:00:00Z"),
+            ])
+            .unwrap();
+
+        assert_eq!(imported, 1);
+        assert_eq!(repo.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_open_persists_to_file() {
+        let path =
+            std::env::temp_dir().join(format!("evervoice-history-{}.db", uuid::Uuid::new_v4()));
+
+        {
+            let repo = HistoryRepository::open(&path).unwrap();
+            repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+        }
+
+        let reopened = HistoryRepository::open(&path).unwrap();
+        assert_eq!(reopened.list().unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// History used by the search tests
+    fn search_fixture() -> HistoryRepository {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let items = [
+            (
+                "standup",
+                "2024-01-10T09:00:00Z",
+                "Daily standup. The budget review is on Friday.",
+                None,
+            ),
+            (
+                "budget",
+                "2024-02-15T14:30:00+01:00",
+                "Budget planning: the budget for Q2 and the budget for marketing.",
+                Some("- Budget approved"),
+            ),
+            (
+                "review",
+                "2024-03-01T18:00:00.123456789+00:00",
+                "Review of the budget spreadsheet with the finance team.",
+                None,
+            ),
+            (
+                "groceries",
+                "2024-03-02T08:00:00Z",
+                "Buy milk, eggs & <bread> for the café.",
+                Some("Shopping list"),
+            ),
+        ];
+        for (id, created_at, transcription, summary) in items {
+            let mut fixture = item(id, created_at);
+            fixture.transcription = transcription.to_string();
+            fixture.summary = summary.map(str::to_string);
+            repo.insert(&fixture).unwrap();
+        }
+        repo
+    }
+
+    fn search_ids(repo: &HistoryRepository, query: HistorySearchQuery) -> Vec<String> {
+        repo.search(&query)
+            .unwrap()
+            .into_iter()
+            .map(|result| result.item.id)
+            .collect()
+    }
+
+    fn text_query(query: &str) -> HistorySearchQuery {
+        HistorySearchQuery {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_best_match_first() {
+        let repo = search_fixture();
+        let ids = search_ids(&repo, text_query("budget"));
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], "budget");
+    }
+
+    #[test]
+    fn test_search_phrase_query() {
+        let repo = search_fixture();
+        assert_eq!(
+            search_ids(&repo, text_query("\"budget review\"")),
+            vec!["standup"]
+        );
+        // Without quotes both words just have to appear
+        let mut ids = search_ids(&repo, text_query("budget review"));
+        ids.sort();
+        assert_eq!(ids, vec!["review", "standup"]);
+    }
+
+    #[test]
+    fn test_search_prefix_and_diacritics() {
+        let repo = search_fixture();
+        assert_eq!(search_ids(&repo, text_query("spread*")), vec!["review"]);
+        assert_eq!(search_ids(&repo, text_query("cafe")), vec!["groceries"]);
+    }
+
+    #[test]
+    fn test_search_matches_summary() {
+        let repo = search_fixture();
+        let results = repo.search(&text_query("shopping")).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.id, "groceries");
+        assert_eq!(
+            results[0].summary_snippet.as_deref(),
+            Some("<mark>Shopping</mark> list")
+        );
+    }
+
+    #[test]
+    fn test_search_snippets_are_highlighted_and_escaped() {
+        let repo = search_fixture();
+        let results = repo.search(&text_query("bread")).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].transcription_snippet,
+            "Buy milk, eggs &amp; &lt;<mark>bread</mark>&gt; for the café."
+        );
+        // Summary didn't match, so no summary snippet
+        assert_eq!(results[0].summary_snippet, None);
+    }
+
+    #[test]
+    fn test_search_date_range() {
+        let repo = search_fixture();
+        let query = |from: Option<&str>, to: Option<&str>| HistorySearchQuery {
+            query: "budget".to_string(),
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            limit: None,
+        };
+
+        let mut ids = search_ids(&repo, query(Some("2024-02-01"), None));
+        ids.sort();
+        assert_eq!(ids, vec!["budget", "review"]);
+
+        // A date as upper bound includes the whole day
+        assert_eq!(
+            search_ids(&repo, query(Some("2024-03-01"), Some("2024-03-01"))),
+            vec!["review"]
+        );
+
+        // Timestamps with offsets are compared as instants
+        assert_eq!(
+            search_ids(
+                &repo,
+                query(Some("2024-02-15T13:30:00Z"), Some("2024-02-15T13:30:00Z"))
+            ),
+            vec!["budget"]
+        );
+
+        assert!(repo.search(&query(Some("last week"), None)).is_err());
+    }
+
+    #[test]
+    fn test_search_limit() {
+        let repo = search_fixture();
+        let query = HistorySearchQuery {
+            limit: Some(1),
+            ..text_query("budget")
+        };
+        assert_eq!(search_ids(&repo, query), vec!["budget"]);
+    }
+
+    #[test]
+    fn test_search_handles_operators_and_empty_queries() {
+        let repo = search_fixture();
+        assert!(search_ids(&repo, text_query("")).is_empty());
+        assert!(search_ids(&repo, text_query(" - * \"\" ")).is_empty());
+        assert_eq!(
+            search_ids(&repo, text_query("milk AND")),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            search_ids(&repo, text_query("(eggs) -milk:")),
+            vec!["groceries"]
+        );
+    }
+
+    #[test]
+    fn test_search_index_follows_updates_and_deletes() {
+        let repo = search_fixture();
+
+        repo.update_summary("standup", "Deadline moved").unwrap();
+        assert_eq!(search_ids(&repo, text_query("deadline")), vec!["standup"]);
+
+        repo.delete("standup").unwrap();
+        assert!(search_ids(&repo, text_query("deadline")).is_empty());
+    }
+
+    #[test]
+    fn test_search_index_backfills_existing_history() {
+        // Database created before the search index existed
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO history (id, file_path, duration_seconds, transcription, created_at)
+             VALUES ('old', '/path/old.webm', 60.0, 'Transcription old', '2024-01-21T10:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        let repo = HistoryRepository::with_connection(conn).unwrap();
+        assert_eq!(search_ids(&repo, text_query("transcription")), vec!["old"]);
+    }
+
+    #[test]
+    fn test_build_match_expression() {
+        assert_eq!(
+            build_match_expression("hello \"big world\" pre*"),
+            Some("\"hello\" \"big world\" \"pre\"*".to_string())
+        );
+        assert_eq!(build_match_expression("   "), None);
+    }
+
+    #[test]
+    fn test_rename_and_move_to_folder() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+
+        repo.rename("1", Some("  Weekly sync  ")).unwrap();
+        repo.move_to_folder("1", Some("Work")).unwrap();
+        let stored = repo.get("1").unwrap().unwrap();
+        assert_eq!(stored.title.as_deref(), Some("Weekly sync"));
+        assert_eq!(stored.folder.as_deref(), Some("Work"));
+
+        // Blank values clear the field
+        repo.rename("1", Some("   ")).unwrap();
+        repo.move_to_folder("1", None).unwrap();
+        let stored = repo.get("1").unwrap().unwrap();
+        assert_eq!(stored.title, None);
+        assert_eq!(stored.folder, None);
+
+        assert!(repo.rename("missing", Some("x")).is_err());
+        assert!(repo.move_to_folder("missing", Some("x")).is_err());
+    }
+
+    #[test]
+    fn test_set_tags_normalizes_and_replaces() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&item("1", "2024-01-21T10:00:00Z")).unwrap();
+
+        let tags = ["work", " Ideas ", "", "WORK", "budget"].map(str::to_string);
+        repo.set_tags("1", &tags).unwrap();
+        assert_eq!(
+            repo.get("1").unwrap().unwrap().tags,
+            vec!["budget", "Ideas", "work"]
+        );
+
+        repo.set_tags("1", &["personal".to_string()]).unwrap();
+        assert_eq!(repo.get("1").unwrap().unwrap().tags, vec!["personal"]);
+
+        assert!(repo.set_tags("missing", &tags).is_err());
+    }
+
+    #[test]
+    fn test_list_by_tag_and_folder() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&HistoryItem {
+            tags: vec!["work".to_string()],
+            folder: Some("Projects".to_string()),
+            ..item("1", "2024-01-21T10:00:00Z")
+        })
+        .unwrap();
+        repo.insert(&HistoryItem {
+            tags: vec!["work".to_string(), "ideas".to_string()],
+            ..item("2", "2024-01-21T11:00:00Z")
+        })
+        .unwrap();
+        repo.insert(&item("3", "2024-01-21T12:00:00Z")).unwrap();
+
+        let ids = |items: Vec<HistoryItem>| items.into_iter().map(|i| i.id).collect::<Vec<_>>();
+        assert_eq!(ids(repo.list_by_tag("WORK").unwrap()), vec!["2", "1"]);
+        assert_eq!(ids(repo.list_by_tag("ideas").unwrap()), vec!["2"]);
+        assert_eq!(
+            ids(repo.list_by_folder(Some("Projects")).unwrap()),
+            vec!["1"]
+        );
+        assert_eq!(ids(repo.list_by_folder(None).unwrap()), vec!["3", "2"]);
+
+        assert_eq!(repo.list_tags().unwrap(), vec!["ideas", "work"]);
+        assert_eq!(repo.list_folders().unwrap(), vec!["Projects"]);
+    }
+
+    #[test]
+    fn test_delete_removes_tags() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        repo.insert(&HistoryItem {
+            tags: vec!["work".to_string()],
+            ..item("1", "2024-01-21T10:00:00Z")
+        })
+        .unwrap();
+
+        repo.delete("1").unwrap();
+        assert!(repo.list_tags().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pending_queue_round_trip() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let first = PendingTranscription::new("/path/a.webm".to_string(), 30.0, "offline");
+        let second = PendingTranscription::new("/path/b.webm".to_string(), 45.0, "offline");
+        repo.enqueue_pending(&first).unwrap();
+        repo.enqueue_pending(&second).unwrap();
+
+        // Re-queuing keeps the original entry and attempt count
+        repo.record_pending_attempt("/path/a.webm", "timeout")
+            .unwrap();
+        repo.enqueue_pending(&PendingTranscription {
+            history_id: Some("1".to_string()),
+            ..PendingTranscription::new("/path/a.webm".to_string(), 30.0, "still offline")
+        })
+        .unwrap();
+
+        let pending = repo.list_pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].file_path, "/path/a.webm");
+        assert_eq!(pending[0].attempts, 1);
+        assert_eq!(pending[0].last_error.as_deref(), Some("still offline"));
+        assert!(pending[0].last_attempt_at.is_some());
+        assert_eq!(pending[0].history_id.as_deref(), Some("1"));
+        assert_eq!(pending[1], second);
+
+        assert!(repo.remove_pending("/path/a.webm").unwrap());
+        assert!(!repo.remove_pending("/path/a.webm").unwrap());
+        assert_eq!(repo.list_pending().unwrap(), vec![second]);
+    }
+
+    #[test]
+    fn test_inbox_files_round_trip() {
+        let repo = HistoryRepository::open_in_memory().unwrap();
+        let file = |path: &str, hash: &str, history_id: Option<&str>| InboxFile {
+            source_path: path.to_string(),
+            file_size: 1024,
+            modified_at: "2024-01-21T10:30:00Z".to_string(),
+            content_hash: hash.to_string(),
+            history_id: history_id.map(str::to_string),
+        };
+        assert_eq!(repo.get_inbox_file("/inbox/a.mp3").unwrap(), None);
+
+        repo.record_inbox_file(&file("/inbox/a.mp3", "aaa", None))
+            .unwrap();
+        repo.record_inbox_file(&file("/inbox/b.mp3", "aaa", Some("1")))
+            .unwrap();
+        assert_eq!(
+            repo.get_inbox_file("/inbox/a.mp3").unwrap(),
+            Some(file("/inbox/a.mp3", "aaa", None))
+        );
+
+        // Imported files are preferred among files with the same content
+        let found = repo.find_inbox_file_by_hash("aaa").unwrap().unwrap();
+        assert_eq!(found.history_id.as_deref(), Some("1"));
+        assert_eq!(repo.find_inbox_file_by_hash("bbb").unwrap(), None);
+
+        // Recording a path again replaces its entry
+        repo.record_inbox_file(&file("/inbox/a.mp3", "ccc", Some("2")))
+            .unwrap();
+        let replaced = repo.get_inbox_file("/inbox/a.mp3").unwrap().unwrap();
+        assert_eq!(replaced.content_hash, "ccc");
+        assert_eq!(replaced.history_id.as_deref(), Some("2"));
+    }
+}
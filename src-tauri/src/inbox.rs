@@ -0,0 +1,402 @@
+//! Watched inbox folder for bulk imports.
+//!
+//! Audio files dropped into the inbox folder from settings (e.g. synced from
+//! a phone) are imported into history and transcribed. The folder is polled
+//! rather than watched through file system events, which are unreliable on
+//! network and cloud-synced folders, and a file is only imported once its
+//! size and modification time are the same on two scans in a row, so files
+//! that are still being written are left alone.
+//!
+//! Every handled file is recorded in the history database with a hash of its
+//! content. Restarting the app, renaming a file or syncing it again doesn't
+//! import it a second time; changing its content does.
+
+use crate::audio_format::AudioFormat;
+use crate::audio_import::{import_audio_data, ImportMode};
+use crate::commands::{self, ImportResponse};
+use crate::file_storage;
+use crate::history_repository::HistoryRepository;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event emitted with an `ImportResponse` for every file taken from the inbox
+pub const INBOX_FILE_IMPORTED_EVENT: &str = "inbox-file-imported";
+
+/// Delay between two scans of the inbox folder
+const SCAN_INTERVAL_SECS: u64 = 10;
+
+/// An inbox file that has been handled
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboxFile {
+    /// Full path of the file in the inbox folder
+    pub source_path: String,
+    /// File size in bytes when it was handled
+    pub file_size: u64,
+    /// Modification time when it was handled (ISO 8601)
+    pub modified_at: String,
+    /// Hex-encoded SHA-256 of the file content
+    pub content_hash: String,
+    /// History item created for the file (none if it isn't a supported audio file)
+    pub history_id: Option<String>,
+}
+
+/// Size and modification time of an inbox file at the previous scan
+struct FileState {
+    size: u64,
+    modified: SystemTime,
+    /// Whether the file was returned as ready in this state
+    handled: bool,
+}
+
+/// Tracks inbox files between scans to tell when they're complete
+#[derive(Default)]
+pub struct InboxScanner {
+    files: HashMap<PathBuf, FileState>,
+}
+
+impl InboxScanner {
+    /// Audio files in `dir` that are unchanged since the previous scan
+    ///
+    /// A file is returned once for every state it settles in.
+    fn ready_files(&mut self, dir: &Path) -> Result<Vec<(PathBuf, fs::Metadata)>, String> {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read inbox folder: {}", e))?;
+
+        let mut files = HashMap::new();
+        let mut ready = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            if !metadata.is_file() || !is_audio_file_name(&path) {
+                continue;
+            }
+
+            let size = metadata.len();
+            let unchanged = self
+                .files
+                .get(&path)
+                .filter(|previous| previous.size == size && previous.modified == modified);
+            let handled = match unchanged {
+                Some(previous) if previous.handled => true,
+                Some(_) => {
+                    ready.push((path.clone(), metadata));
+                    true
+                }
+                None => false,
+            };
+            files.insert(
+                path,
+                FileState {
+                    size,
+                    modified,
+                    handled,
+                },
+            );
+        }
+
+        self.files = files;
+        Ok(ready)
+    }
+}
+
+/// Whether a file name looks like a complete audio file
+///
+/// Hidden files and partial downloads (e.g. `memo.m4a.part`) are skipped.
+fn is_audio_file_name(path: &Path) -> bool {
+    let visible = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| !name.starts_with('.'));
+    let audio = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(AudioFormat::from_extension)
+        .is_some();
+    visible && audio
+}
+
+/// Start the background worker that scans the inbox folder
+///
+/// Settings are read on every scan, so changing the folder takes effect
+/// without a restart.
+pub fn start(app: AppHandle) {
+    let worker = std::thread::Builder::new()
+        .name("inbox".to_string())
+        .spawn(move || {
+            let mut scanner = InboxScanner::default();
+            loop {
+                if let Err(e) = scan_app_inbox(&app, &mut scanner) {
+                    log::warn!("Inbox scan failed: {}", e);
+                }
+                std::thread::sleep(Duration::from_secs(SCAN_INTERVAL_SECS));
+            }
+        });
+    if let Err(e) = worker {
+        log::error!("Failed to start inbox worker: {}", e);
+    }
+}
+
+fn scan_app_inbox(app: &AppHandle, scanner: &mut InboxScanner) -> Result<(), String> {
+    let settings = commands::get_settings_internal(app)?;
+    let Some(folder) = settings.inbox_folder else {
+        return Ok(());
+    };
+    let dir = Path::new(&folder);
+    if !dir.is_dir() {
+        log::debug!("Inbox folder not found: {}", folder);
+        return Ok(());
+    }
+
+    let recordings_dir = file_storage::ensure_recordings_dir_exists()?;
+    let pipeline = commands::auto_pipeline(app)?;
+    let history = app.state::<HistoryRepository>();
+
+    let responses = import_ready_files(&history, scanner, dir, |path, data, metadata| {
+        let imported = import_audio_data(path, data, metadata, &recordings_dir, ImportMode::Copy)?;
+        commands::add_imported_to_history(app, &imported, true, pipeline.as_ref())
+    })?;
+
+    for response in responses {
+        if let Err(e) = app.emit(INBOX_FILE_IMPORTED_EVENT, &response) {
+            log::warn!("Failed to emit inbox import: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Import the complete audio files in `dir` that weren't handled before
+///
+/// `import` adds a file (its path, content and metadata) to history and
+/// returns the IDs of the history item and the queued job. Files without
+/// supported audio content are recorded as well, so they're only reported
+/// once; files whose import fails otherwise are tried again after a restart.
+pub fn import_ready_files(
+    history: &HistoryRepository,
+    scanner: &mut InboxScanner,
+    dir: &Path,
+    mut import: impl FnMut(&Path, &[u8], &fs::Metadata) -> Result<(String, Option<String>), String>,
+) -> Result<Vec<ImportResponse>, String> {
+    let mut responses = Vec::new();
+
+    for (path, metadata) in scanner.ready_files(dir)? {
+        let source_path = path.to_string_lossy().into_owned();
+        let modified_at = metadata
+            .modified()
+            .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339())
+            .unwrap_or_default();
+
+        // Unchanged since it was handled, e.g. before a restart
+        if let Some(known) = history.get_inbox_file(&source_path)? {
+            if known.file_size == metadata.len() && known.modified_at == modified_at {
+                continue;
+            }
+        }
+
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to read inbox file {}: {}", source_path, e);
+                continue;
+            }
+        };
+        let mut file = InboxFile {
+            source_path: source_path.clone(),
+            file_size: metadata.len(),
+            modified_at,
+            content_hash: content_hash(&data),
+            history_id: None,
+        };
+
+        // Same content under another name, e.g. a renamed or re-synced file
+        if let Some(existing) = history.find_inbox_file_by_hash(&file.content_hash)? {
+            log::info!(
+                "Skipping {}, already imported from {}",
+                source_path,
+                existing.source_path
+            );
+            file.history_id = existing.history_id;
+            history.record_inbox_file(&file)?;
+            continue;
+        }
+
+        let result = import(&path, &data, &metadata);
+        match &result {
+            Ok((history_id, _)) => {
+                file.history_id = Some(history_id.clone());
+                history.record_inbox_file(&file)?;
+            }
+            Err(_) if AudioFormat::detect(&data).is_none() => history.record_inbox_file(&file)?,
+            Err(_) => {}
+        }
+        responses.push(ImportResponse::from_result(
+            source_path,
+            result.map(|(history_id, job_id)| (history_id, job_id, false)),
+        ));
+    }
+
+    Ok(responses)
+}
+
+/// Hex-encoded SHA-256 of file content
+fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::cell::RefCell;
+
+    /// Imports by recording the file name, failing for unsupported content
+    struct Importer {
+        imported: RefCell<Vec<String>>,
+    }
+
+    impl Importer {
+        fn new() -> Self {
+            Self {
+                imported: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn scan(
+            &self,
+            history: &HistoryRepository,
+            scanner: &mut InboxScanner,
+            dir: &Path,
+        ) -> Vec<ImportResponse> {
+            import_ready_files(history, scanner, dir, |path, data, _| {
+                AudioFormat::detect(data).ok_or("Unsupported file type")?;
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                self.imported.borrow_mut().push(name.clone());
+                Ok((format!("history-{}", name), Some(format!("job-{}", name))))
+            })
+            .unwrap()
+        }
+
+        fn take(&self) -> Vec<String> {
+            let mut imported = self.imported.take();
+            imported.sort();
+            imported
+        }
+    }
+
+    #[test]
+    fn test_imports_complete_audio_files_once() {
+        let dir = fixtures::temp_dir("inbox");
+        fs::write(dir.join("memo.wav"), fixtures::audio("silence.wav")).unwrap();
+        fs::write(dir.join("call.mp3"), fixtures::audio("silence.mp3")).unwrap();
+        fs::write(dir.join("notes.txt"), "not audio").unwrap();
+        fs::write(dir.join(".memo.m4a"), fixtures::audio("silence.m4a")).unwrap();
+        fs::write(dir.join("upload.ogg.part"), fixtures::audio("silence.ogg")).unwrap();
+
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let importer = Importer::new();
+        let mut scanner = InboxScanner::default();
+
+        // New files are only imported once they're unchanged on the next scan
+        assert!(importer.scan(&history, &mut scanner, &dir).is_empty());
+        let responses = importer.scan(&history, &mut scanner, &dir);
+        assert_eq!(importer.take(), vec!["call.mp3", "memo.wav"]);
+        assert!(responses.iter().all(|response| response.success));
+        assert!(responses
+            .iter()
+            .any(|response| response.job_id.as_deref() == Some("job-memo.wav")));
+
+        let source = dir.join("memo.wav").to_string_lossy().into_owned();
+        let recorded = history.get_inbox_file(&source).unwrap().unwrap();
+        assert_eq!(recorded.history_id.as_deref(), Some("history-memo.wav"));
+        assert_eq!(
+            recorded.content_hash,
+            content_hash(&fixtures::audio("silence.wav"))
+        );
+
+        assert!(importer.scan(&history, &mut scanner, &dir).is_empty());
+
+        // A restart doesn't import the files again
+        let mut scanner = InboxScanner::default();
+        importer.scan(&history, &mut scanner, &dir);
+        assert!(importer.scan(&history, &mut scanner, &dir).is_empty());
+        assert!(importer.take().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_skips_files_with_imported_content() {
+        let dir = fixtures::temp_dir("inbox");
+        fs::write(dir.join("memo.wav"), fixtures::audio("silence.wav")).unwrap();
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let importer = Importer::new();
+        let mut scanner = InboxScanner::default();
+        importer.scan(&history, &mut scanner, &dir);
+        importer.scan(&history, &mut scanner, &dir);
+        assert_eq!(importer.take(), vec!["memo.wav"]);
+
+        // Renamed (or synced again under another name)
+        fs::rename(dir.join("memo.wav"), dir.join("memo (1).wav")).unwrap();
+        importer.scan(&history, &mut scanner, &dir);
+        assert!(importer.scan(&history, &mut scanner, &dir).is_empty());
+        assert!(importer.take().is_empty());
+
+        let source = dir.join("memo (1).wav").to_string_lossy().into_owned();
+        let recorded = history.get_inbox_file(&source).unwrap().unwrap();
+        assert_eq!(recorded.history_id.as_deref(), Some("history-memo.wav"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_waits_for_files_being_written() {
+        let dir = fixtures::temp_dir("inbox");
+        let path = dir.join("long.mp3");
+        let data = fixtures::audio("silence.mp3");
+        fs::write(&path, &data[..100]).unwrap();
+
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let importer = Importer::new();
+        let mut scanner = InboxScanner::default();
+        importer.scan(&history, &mut scanner, &dir);
+
+        fs::write(&path, &data).unwrap();
+        assert!(importer.scan(&history, &mut scanner, &dir).is_empty());
+        assert_eq!(importer.scan(&history, &mut scanner, &dir).len(), 1);
+        assert_eq!(importer.take(), vec!["long.mp3"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reports_unsupported_content_once() {
+        let dir = fixtures::temp_dir("inbox");
+        fs::write(dir.join("fake.mp3"), "not really audio").unwrap();
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let importer = Importer::new();
+
+        let mut scanner = InboxScanner::default();
+        importer.scan(&history, &mut scanner, &dir);
+        let responses = importer.scan(&history, &mut scanner, &dir);
+        assert_eq!(responses.len(), 1);
+        assert!(!responses[0].success);
+        assert!(responses[0].error_message.is_some());
+
+        let mut scanner = InboxScanner::default();
+        importer.scan(&history, &mut scanner, &dir);
+        assert!(importer.scan(&history, &mut scanner, &dir).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
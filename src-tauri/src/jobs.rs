@@ -0,0 +1,549 @@
+//! Background job manager for transcription, summarization and pipelines.
+//!
+//! Jobs run on the async runtime independently of the command that started
+//! them, so results are persisted even if the window stops listening. Every
+//! state change is reported to a listener (the app emits it as a
+//! `job-updated` event) and jobs can be cancelled while queued or running.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::async_runtime::{self, JoinHandle};
+use tokio::sync::Semaphore;
+
+/// Number of jobs that may run at the same time; the rest wait in the queue
+const MAX_RUNNING_JOBS: usize = 2;
+
+/// Number of finished jobs kept for `list`/`get` before the oldest are dropped
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    Transcription,
+    Summarization,
+    /// A post-transcription pipeline run on a history item
+    Pipeline,
+}
+
+/// Lifecycle state of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Whether the job has reached a final state
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// Snapshot of a job as reported to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    /// Unique identifier (UUID)
+    pub id: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Fraction of the work done, from 0.0 to 1.0
+    pub progress: f64,
+    /// History item the job reads from or writes to
+    pub history_id: Option<String>,
+    /// Transcription or summary text (if completed)
+    pub result: Option<String>,
+    /// Error type (if failed)
+    pub error_type: Option<String>,
+    /// User-friendly error message (if failed)
+    pub error_message: Option<String>,
+    /// ISO 8601 timestamp when the job was submitted
+    pub created_at: String,
+}
+
+/// Successful job result
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobOutput {
+    /// Transcription or summary text
+    pub text: String,
+    /// History item the result was saved to
+    pub history_id: Option<String>,
+}
+
+/// Failed job result, in the same shape as command error responses
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobError {
+    pub error_type: String,
+    pub message: String,
+}
+
+impl JobError {
+    pub fn new(error_type: &str, message: impl Into<String>) -> Self {
+        Self {
+            error_type: error_type.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Handle passed to a running job for reporting progress
+#[derive(Clone)]
+pub struct JobProgress {
+    manager: JobManager,
+    id: String,
+}
+
+impl JobProgress {
+    /// Report the fraction of work done (clamped to 0.0..=1.0)
+    pub fn set(&self, fraction: f64) {
+        self.manager.update(&self.id, |job| {
+            if job.status == JobStatus::Running {
+                job.progress = fraction.clamp(0.0, 1.0);
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+type Listener = dyn Fn(&Job) + Send + Sync;
+
+struct JobEntry {
+    job: Job,
+    handle: Option<JoinHandle<()>>,
+    /// Submission order, used for listing and pruning
+    sequence: u64,
+}
+
+struct Inner {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    slots: Arc<Semaphore>,
+    listener: Box<Listener>,
+    next_sequence: Mutex<u64>,
+}
+
+/// Queue of background jobs, shared as Tauri managed state
+#[derive(Clone)]
+pub struct JobManager {
+    inner: Arc<Inner>,
+}
+
+impl JobManager {
+    /// Create a manager that reports every job change to `listener`
+    pub fn new(listener: impl Fn(&Job) + Send + Sync + 'static) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                jobs: Mutex::new(HashMap::new()),
+                slots: Arc::new(Semaphore::new(MAX_RUNNING_JOBS)),
+                listener: Box::new(listener),
+                next_sequence: Mutex::new(0),
+            }),
+        }
+    }
+
+    /// Queue a job and return its ID
+    ///
+    /// `work` is started once a slot is free and receives a progress handle.
+    /// Cancelling the job drops its future at the next await point.
+    pub fn submit<F, Fut>(&self, kind: JobKind, history_id: Option<String>, work: F) -> String
+    where
+        F: FnOnce(JobProgress) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<JobOutput, JobError>> + Send + 'static,
+    {
+        let id = uuid::Uuid::new_v4().to_string();
+        let job = Job {
+            id: id.clone(),
+            kind,
+            status: JobStatus::Queued,
+            progress: 0.0,
+            history_id,
+            result: None,
+            error_type: None,
+            error_message: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let sequence = {
+            let mut next = lock(&self.inner.next_sequence);
+            *next += 1;
+            *next
+        };
+
+        // Hold the lock until the handle is stored so a cancel can't miss it,
+        // and report the job as queued before its task can report progress
+        let mut jobs = lock(&self.inner.jobs);
+        jobs.insert(
+            id.clone(),
+            JobEntry {
+                job: job.clone(),
+                handle: None,
+                sequence,
+            },
+        );
+        (self.inner.listener)(&job);
+
+        let manager = self.clone();
+        let job_id = id.clone();
+        let handle = async_runtime::spawn(async move {
+            let Ok(_permit) = manager.inner.slots.clone().acquire_owned().await else {
+                return;
+            };
+
+            let started = manager.update(&job_id, |job| {
+                if job.status == JobStatus::Queued {
+                    job.status = JobStatus::Running;
+                    true
+                } else {
+                    false
+                }
+            });
+            if !started {
+                return;
+            }
+
+            let progress = JobProgress {
+                manager: manager.clone(),
+                id: job_id.clone(),
+            };
+            let result = work(progress).await;
+            manager.finish(&job_id, result);
+        });
+
+        if let Some(entry) = jobs.get_mut(&id) {
+            entry.handle = Some(handle);
+        }
+        id
+    }
+
+    /// Returns a job by ID
+    pub fn get(&self, id: &str) -> Option<Job> {
+        lock(&self.inner.jobs)
+            .get(id)
+            .map(|entry| entry.job.clone())
+    }
+
+    /// Returns all known jobs, newest first
+    pub fn list(&self) -> Vec<Job> {
+        let jobs = lock(&self.inner.jobs);
+        let mut entries: Vec<&JobEntry> = jobs.values().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.sequence));
+        entries.into_iter().map(|entry| entry.job.clone()).collect()
+    }
+
+    /// Cancel a queued or running job
+    pub fn cancel(&self, id: &str) -> Result<(), String> {
+        let job = {
+            let mut jobs = lock(&self.inner.jobs);
+            let entry = jobs
+                .get_mut(id)
+                .ok_or_else(|| format!("Job not found: {}", id))?;
+            if entry.job.status.is_finished() {
+                return Err(format!("Job already finished: {}", id));
+            }
+
+            if let Some(handle) = entry.handle.take() {
+                handle.abort();
+            }
+            entry.job.status = JobStatus::Cancelled;
+            entry.job.clone()
+        };
+
+        (self.inner.listener)(&job);
+        Ok(())
+    }
+
+    /// Forget all finished jobs
+    pub fn clear_finished(&self) {
+        lock(&self.inner.jobs).retain(|_, entry| !entry.job.status.is_finished());
+    }
+
+    /// Record the result of a job unless it was cancelled meanwhile
+    fn finish(&self, id: &str, result: Result<JobOutput, JobError>) {
+        let finished = self.update(id, |job| {
+            if job.status != JobStatus::Running {
+                return false;
+            }
+            match &result {
+                Ok(output) => {
+                    job.status = JobStatus::Completed;
+                    job.progress = 1.0;
+                    job.result = Some(output.text.clone());
+                    if output.history_id.is_some() {
+                        job.history_id = output.history_id.clone();
+                    }
+                }
+                Err(error) => {
+                    job.status = JobStatus::Failed;
+                    job.error_type = Some(error.error_type.clone());
+                    job.error_message = Some(error.message.clone());
+                }
+            }
+            true
+        });
+
+        if finished {
+            let mut jobs = lock(&self.inner.jobs);
+            if let Some(entry) = jobs.get_mut(id) {
+                entry.handle = None;
+            }
+            prune_finished(&mut jobs);
+        }
+    }
+
+    /// Apply `change` to a job; the listener is notified if it returns true
+    fn update(&self, id: &str, change: impl FnOnce(&mut Job) -> bool) -> bool {
+        let job = {
+            let mut jobs = lock(&self.inner.jobs);
+            let Some(entry) = jobs.get_mut(id) else {
+                return false;
+            };
+            if !change(&mut entry.job) {
+                return false;
+            }
+            entry.job.clone()
+        };
+
+        (self.inner.listener)(&job);
+        true
+    }
+}
+
+/// Drop the oldest finished jobs beyond `MAX_FINISHED_JOBS`
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(u64, String)> = jobs
+        .iter()
+        .filter(|(_, entry)| entry.job.status.is_finished())
+        .map(|(id, entry)| (entry.sequence, id.clone()))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+
+    finished.sort();
+    let excess = finished.len() - MAX_FINISHED_JOBS;
+    for (_, id) in finished.into_iter().take(excess) {
+        jobs.remove(&id);
+    }
+}
+
+/// Lock a mutex, recovering the data if another thread panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    fn recording_manager() -> (JobManager, Arc<Mutex<Vec<Job>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let manager = JobManager::new(move |job| recorded.lock().unwrap().push(job.clone()));
+        (manager, events)
+    }
+
+    /// Wait until the job reaches `status` (jobs run on other tasks)
+    async fn wait_for(manager: &JobManager, id: &str, status: JobStatus) -> Job {
+        for _ in 0..200 {
+            if let Some(job) = manager.get(id).filter(|job| job.status == status) {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!(
+            "job {} never reached {:?}: {:?}",
+            id,
+            status,
+            manager.get(id)
+        );
+    }
+
+    fn output(text: &str) -> JobOutput {
+        JobOutput {
+            text: text.to_string(),
+            history_id: Some("history-1".to_string()),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_job_completes_with_progress_and_result() {
+        let (manager, events) = recording_manager();
+
+        let id = manager.submit(JobKind::Transcription, None, |progress| async move {
+            progress.set(0.5);
+            Ok(output("Hello world"))
+        });
+
+        let job = wait_for(&manager, &id, JobStatus::Completed).await;
+        assert_eq!(job.result.as_deref(), Some("Hello world"));
+        assert_eq!(job.history_id.as_deref(), Some("history-1"));
+        assert_eq!(job.progress, 1.0);
+
+        let statuses: Vec<(JobStatus, f64)> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|job| (job.status, job.progress))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (JobStatus::Queued, 0.0),
+                (JobStatus::Running, 0.0),
+                (JobStatus::Running, 0.5),
+                (JobStatus::Completed, 1.0),
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_queued_is_reported_before_instant_jobs_finish() {
+        let (manager, events) = recording_manager();
+
+        let ids: Vec<String> = (0..50)
+            .map(|_| {
+                manager.submit(JobKind::Transcription, None, |_| async {
+                    Ok(output("done"))
+                })
+            })
+            .collect();
+        for id in &ids {
+            wait_for(&manager, id, JobStatus::Completed).await;
+        }
+
+        let events = events.lock().unwrap();
+        for id in &ids {
+            let first = events.iter().find(|job| &job.id == id).unwrap();
+            assert_eq!(first.status, JobStatus::Queued);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_job_failure_is_recorded() {
+        let (manager, _) = recording_manager();
+
+        let id = manager.submit(JobKind::Summarization, None, |_| async {
+            Err(JobError::new("rate_limit_exceeded", "Please wait"))
+        });
+
+        let job = wait_for(&manager, &id, JobStatus::Failed).await;
+        assert_eq!(job.error_type.as_deref(), Some("rate_limit_exceeded"));
+        assert_eq!(job.error_message.as_deref(), Some("Please wait"));
+        assert_eq!(job.result, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_running_job_stops_work() {
+        let (manager, _) = recording_manager();
+        let (dropped_tx, dropped_rx) = oneshot::channel::<()>();
+
+        let id = manager.submit(JobKind::Transcription, None, |_| async move {
+            // Sender is dropped when the future is aborted
+            let _guard = dropped_tx;
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(output("never"))
+        });
+        wait_for(&manager, &id, JobStatus::Running).await;
+
+        manager.cancel(&id).unwrap();
+        assert_eq!(manager.get(&id).unwrap().status, JobStatus::Cancelled);
+        tokio::time::timeout(Duration::from_secs(1), dropped_rx)
+            .await
+            .expect("job future was not dropped")
+            .unwrap_err();
+
+        // Finished jobs can't be cancelled again
+        assert!(manager.cancel(&id).is_err());
+        assert!(manager.cancel("missing").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_jobs_beyond_limit_wait_in_queue() {
+        let (manager, _) = recording_manager();
+        let mut releases = Vec::new();
+        let mut ids = Vec::new();
+
+        for _ in 0..MAX_RUNNING_JOBS + 1 {
+            let (tx, rx) = oneshot::channel::<()>();
+            releases.push(tx);
+            ids.push(
+                manager.submit(JobKind::Transcription, None, |_| async move {
+                    let _ = rx.await;
+                    Ok(output("done"))
+                }),
+            );
+        }
+
+        for id in &ids[..MAX_RUNNING_JOBS] {
+            wait_for(&manager, id, JobStatus::Running).await;
+        }
+        let queued = &ids[MAX_RUNNING_JOBS];
+        assert_eq!(manager.get(queued).unwrap().status, JobStatus::Queued);
+
+        // Cancelling a queued job means it never starts
+        manager.cancel(queued).unwrap();
+        for release in releases {
+            let _ = release.send(());
+        }
+        for id in &ids[..MAX_RUNNING_JOBS] {
+            wait_for(&manager, id, JobStatus::Completed).await;
+        }
+        assert_eq!(manager.get(queued).unwrap().status, JobStatus::Cancelled);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_is_newest_first_and_clear_finished() {
+        let (manager, _) = recording_manager();
+        let first = manager.submit(JobKind::Transcription, None, |_| async { Ok(output("a")) });
+        wait_for(&manager, &first, JobStatus::Completed).await;
+        let second = manager.submit(JobKind::Summarization, None, |_| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(output("b"))
+        });
+
+        let ids: Vec<String> = manager.list().into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, vec![second.clone(), first.clone()]);
+
+        manager.clear_finished();
+        let ids: Vec<String> = manager.list().into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, vec![second.clone()]);
+
+        manager.cancel(&second).unwrap();
+    }
+
+    #[test]
+    fn test_job_serializes_camel_case() {
+        let job = Job {
+            id: "job-1".to_string(),
+            kind: JobKind::Transcription,
+            status: JobStatus::Running,
+            progress: 0.25,
+            history_id: None,
+            result: None,
+            error_type: None,
+            error_message: None,
+            created_at: "2024-01-21T10:30:00Z".to_string(),
+        };
+
+        let json = serde_json::to_value(&job).unwrap();
+        assert_eq!(json["kind"], "transcription");
+        assert_eq!(json["status"], "running");
+        assert_eq!(json["historyId"], serde_json::Value::Null);
+        assert_eq!(json["createdAt"], "2024-01-21T10:30:00Z");
+    }
+}
@@ -1,16 +1,42 @@
+mod audio_chunking;
+mod audio_decode;
+mod audio_duration;
+mod audio_format;
+mod audio_import;
+#[cfg(feature = "transcode")]
+mod audio_transcode;
 mod commands;
+mod export;
 mod external_service;
 mod file_storage;
+#[cfg(test)]
+#[path = "../tests/fixtures/mod.rs"]
+mod fixtures;
 mod global_hotkey;
 mod history;
+mod history_repository;
+mod inbox;
+mod jobs;
+mod local_models;
+#[cfg(feature = "local-whisper")]
+mod local_whisper;
+mod pending_queue;
+mod pipeline;
+mod retry;
+mod secret_store;
 mod settings;
 mod summarization;
 mod transcription;
+mod util;
+mod vad;
 
 pub use history::HistoryItem;
 pub use settings::Settings;
 
-use tauri::Manager;
+use history_repository::HistoryRepository;
+use jobs::JobManager;
+use secret_store::SecretStore;
+use tauri::{Emitter, Manager};
 use global_hotkey::{
     initialize_global_hotkey, unregister_all_hotkeys, update_global_hotkey, HotkeyResult,
 };
@@ -43,6 +69,38 @@ pub fn run() {
                 )?;
             }
 
+            // Keep the API keys in the OS keyring (or an encrypted file) instead of settings.json
+            let secrets = SecretStore::open(&file_storage::get_app_data_dir()?);
+            match commands::migrate_api_keys_from_store(app.handle(), &secrets) {
+                Ok(true) => log::info!("Moved the API keys to secure storage"),
+                Ok(false) => {}
+                Err(e) => log::error!("Failed to migrate API keys: {}", e),
+            }
+            app.manage(secrets);
+
+            // Open the history database and import history from the legacy store
+            let history = HistoryRepository::open(&file_storage::get_history_db_path()?)?;
+            match commands::migrate_history_from_store(app.handle(), &history) {
+                Ok(0) => {}
+                Ok(count) => log::info!("Migrated {} history items to the database", count),
+                Err(e) => log::error!("Failed to migrate history: {}", e),
+            }
+            app.manage(history);
+
+            // Background jobs report every state change to the frontend
+            let handle = app.handle().clone();
+            app.manage(JobManager::new(move |job| {
+                if let Err(e) = handle.emit(commands::JOB_UPDATED_EVENT, job.clone()) {
+                    log::warn!("Failed to emit job update: {}", e);
+                }
+            }));
+
+            // Retry recordings that could not be transcribed while offline
+            app.manage(pending_queue::start(app.handle().clone()));
+
+            // Import audio files dropped into the inbox folder
+            inbox::start(app.handle().clone());
+
             // Initialize global hotkey on app startup
             initialize_global_hotkey(app.handle());
 
@@ -61,16 +119,44 @@ pub fn run() {
             commands::get_settings,
             commands::save_settings,
             commands::get_history,
+            commands::search_history,
             commands::save_recording_history,
             commands::delete_recording_history,
             commands::update_history_summary,
+            commands::rename_history_item,
+            commands::set_history_tags,
+            commands::move_history_item,
+            commands::list_history_by_tag,
+            commands::list_history_by_folder,
+            commands::list_history_tags,
+            commands::list_history_folders,
+            commands::export_history_item,
             commands::get_recordings_directory,
             commands::ensure_directory_exists,
             commands::save_recording,
             commands::delete_recording,
+            commands::import_audio_files,
             commands::transcribe_audio,
+            commands::is_local_transcription_available,
+            commands::list_local_models,
+            commands::download_local_model,
+            commands::delete_local_model,
             commands::summarize_transcription,
+            commands::summarize_transcription_stream,
+            commands::generate_history_title,
+            commands::start_transcription_job,
+            commands::start_summarization_job,
+            commands::start_pipeline_job,
+            commands::get_job,
+            commands::list_jobs,
+            commands::cancel_job,
+            commands::clear_finished_jobs,
+            commands::list_pending_transcriptions,
+            commands::retry_pending_transcriptions,
+            commands::remove_pending_transcription,
             external_service::call_external_service,
+            commands::run_custom_action,
+            commands::open_url,
             update_global_hotkey_cmd,
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,250 @@
+//! Whisper model files for on-device transcription.
+//!
+//! Models are ggml files from the whisper.cpp project, downloaded on demand
+//! into `EverVoice/models/` next to the recordings folder. Downloads are
+//! written to a `.part` file and renamed once complete, so an interrupted
+//! download never looks like a usable model.
+
+use futures::StreamExt;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Base URL of the whisper.cpp ggml model repository
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// A model that can be downloaded for local transcription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalModelSpec {
+    /// Identifier stored in settings (e.g., "base.en")
+    pub id: &'static str,
+    /// Display name
+    pub name: &'static str,
+    /// Approximate download size in megabytes
+    pub size_mb: u32,
+}
+
+impl LocalModelSpec {
+    /// File name of the model inside the models directory
+    pub fn file_name(&self) -> String {
+        format!("ggml-{}.bin", self.id)
+    }
+
+    fn url(&self) -> String {
+        format!("{}/{}", MODEL_BASE_URL, self.file_name())
+    }
+}
+
+/// Models offered for download, smallest first
+pub const LOCAL_MODELS: &[LocalModelSpec] = &[
+    LocalModelSpec {
+        id: "tiny",
+        name: "Tiny (multilingual)",
+        size_mb: 75,
+    },
+    LocalModelSpec {
+        id: "tiny.en",
+        name: "Tiny (English)",
+        size_mb: 75,
+    },
+    LocalModelSpec {
+        id: "base",
+        name: "Base (multilingual)",
+        size_mb: 142,
+    },
+    LocalModelSpec {
+        id: "base.en",
+        name: "Base (English)",
+        size_mb: 142,
+    },
+    LocalModelSpec {
+        id: "small",
+        name: "Small (multilingual)",
+        size_mb: 466,
+    },
+    LocalModelSpec {
+        id: "small.en",
+        name: "Small (English)",
+        size_mb: 466,
+    },
+    LocalModelSpec {
+        id: "medium",
+        name: "Medium (multilingual)",
+        size_mb: 1_500,
+    },
+    LocalModelSpec {
+        id: "large-v3-turbo",
+        name: "Large v3 Turbo (multilingual)",
+        size_mb: 1_620,
+    },
+];
+
+/// A catalog model and its download state, returned to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModelInfo {
+    pub id: String,
+    pub name: String,
+    /// Approximate download size in megabytes
+    pub size_mb: u32,
+    /// Whether the model file is present in the models directory
+    pub downloaded: bool,
+    /// Full path to the model file (if downloaded)
+    pub path: Option<String>,
+}
+
+/// Looks up a catalog model by ID
+pub fn model_spec(id: &str) -> Option<&'static LocalModelSpec> {
+    LOCAL_MODELS.iter().find(|model| model.id == id)
+}
+
+/// Path of a catalog model inside `dir`, whether or not it is downloaded
+pub fn model_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    model_spec(id)
+        .map(|model| dir.join(model.file_name()))
+        .ok_or_else(|| format!("Unknown transcription model: {}", id))
+}
+
+/// Returns every catalog model with its download state
+pub fn list_models(dir: &Path) -> Vec<LocalModelInfo> {
+    LOCAL_MODELS
+        .iter()
+        .map(|model| {
+            let path = dir.join(model.file_name());
+            let downloaded = path.is_file();
+            LocalModelInfo {
+                id: model.id.to_string(),
+                name: model.name.to_string(),
+                size_mb: model.size_mb,
+                downloaded,
+                path: downloaded.then(|| path.to_string_lossy().into_owned()),
+            }
+        })
+        .collect()
+}
+
+/// Deletes a downloaded model, returning whether a file was removed
+pub fn delete_model(dir: &Path, id: &str) -> Result<bool, String> {
+    let path = model_path(dir, id)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete model: {}", e))?;
+    Ok(true)
+}
+
+/// Downloads a catalog model into `dir`
+///
+/// `on_progress` receives the downloaded and (if known) total byte count
+/// after every received piece.
+pub async fn download_model(
+    dir: &Path,
+    id: &str,
+    mut on_progress: impl FnMut(u64, Option<u64>) + Send,
+) -> Result<PathBuf, String> {
+    let model = model_spec(id).ok_or_else(|| format!("Unknown transcription model: {}", id))?;
+    let path = dir.join(model.file_name());
+    let partial = dir.join(format!("{}.part", model.file_name()));
+
+    let response = reqwest::get(model.url())
+        .await
+        .map_err(|e| format!("Failed to download model: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download model: server returned {}",
+            response.status()
+        ));
+    }
+
+    let total = response.content_length();
+    let mut file =
+        fs::File::create(&partial).map_err(|e| format!("Failed to create model file: {}", e))?;
+    let mut downloaded = 0u64;
+    let mut body = response.bytes_stream();
+
+    while let Some(bytes) = body.next().await {
+        let written = bytes
+            .map_err(|e| format!("Failed to download model: {}", e))
+            .and_then(|bytes| {
+                file.write_all(&bytes)
+                    .map(|_| bytes.len() as u64)
+                    .map_err(|e| format!("Failed to write model file: {}", e))
+            });
+        match written {
+            Ok(len) => {
+                downloaded += len;
+                on_progress(downloaded, total);
+            }
+            Err(e) => {
+                drop(file);
+                let _ = fs::remove_file(&partial);
+                return Err(e);
+            }
+        }
+    }
+
+    file.flush()
+        .map_err(|e| format!("Failed to write model file: {}", e))?;
+    drop(file);
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to save model file: {}", e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_catalog_ids_are_unique() {
+        for (i, model) in LOCAL_MODELS.iter().enumerate() {
+            assert!(LOCAL_MODELS[i + 1..].iter().all(|m| m.id != model.id));
+            assert_eq!(model_spec(model.id), Some(model));
+        }
+        assert_eq!(model_spec("huge"), None);
+    }
+
+    #[test]
+    fn test_model_path_uses_ggml_file_name() {
+        let dir = Path::new("/models");
+        assert_eq!(
+            model_path(dir, "base.en").unwrap(),
+            dir.join("ggml-base.en.bin")
+        );
+        assert!(model_path(dir, "../../etc/passwd").is_err());
+        assert_eq!(
+            model_spec("tiny").unwrap().url(),
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin"
+        );
+    }
+
+    #[test]
+    fn test_list_and_delete_models() {
+        let dir = fixtures::temp_dir("models");
+        fs::write(dir.join("ggml-tiny.bin"), b"model").unwrap();
+        // Partial downloads don't count
+        fs::write(dir.join("ggml-base.bin.part"), b"mod").unwrap();
+
+        let models = list_models(&dir);
+        assert_eq!(models.len(), LOCAL_MODELS.len());
+        let downloaded: Vec<&str> = models
+            .iter()
+            .filter(|m| m.downloaded)
+            .map(|m| m.id.as_str())
+            .collect();
+        assert_eq!(downloaded, vec!["tiny"]);
+        assert!(models[0]
+            .path
+            .as_deref()
+            .unwrap()
+            .ends_with("ggml-tiny.bin"));
+
+        assert!(delete_model(&dir, "tiny").unwrap());
+        assert!(!delete_model(&dir, "tiny").unwrap());
+        assert!(list_models(&dir).iter().all(|m| !m.downloaded));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,187 @@
+//! On-device transcription with whisper.cpp (`local-whisper` feature).
+//!
+//! Recordings are decoded to mono (see `audio_decode`) and resampled to
+//! the 16 kHz whisper expects. Nothing leaves the machine. The loaded model
+//! is kept in memory between recordings because loading it takes longer
+//! than a short clip.
+
+use crate::audio_decode::{decode_to_mono, resample};
+use crate::transcription::{
+    TranscriptionError, TranscriptionProvider, TranscriptionResult, TranscriptionSegment,
+};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+/// Sample rate whisper models are trained on
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Upper bound for decoder threads; more rarely helps on CPU
+const MAX_THREADS: usize = 8;
+
+/// Most recently loaded model, reused while the settings keep selecting it
+static LOADED_MODEL: Mutex<Option<(PathBuf, Arc<WhisperContext>)>> = Mutex::new(None);
+
+/// Transcription provider running a whisper.cpp model on the CPU
+pub struct LocalWhisperProvider {
+    model_path: PathBuf,
+    trim_silence: bool,
+}
+
+impl LocalWhisperProvider {
+    pub fn new(model_path: PathBuf) -> Self {
+        Self {
+            model_path,
+            trim_silence: false,
+        }
+    }
+
+    /// Cut long silences out of recordings before running the model
+    pub fn with_silence_trimming(mut self, enabled: bool) -> Self {
+        self.trim_silence = enabled;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptionProvider for LocalWhisperProvider {
+    async fn transcribe(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        language: &str,
+    ) -> Result<TranscriptionResult, TranscriptionError> {
+        let data = file_data.to_vec();
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_string);
+        let model_path = self.model_path.clone();
+        let language = language.to_string();
+
+        // Decoding and inference are CPU-bound; keep them off the async runtime
+        tauri::async_runtime::spawn_blocking(move || {
+            let samples = decode_to_whisper_pcm(&data, extension.as_deref())
+                .map_err(TranscriptionError::InvalidAudioFormat)?;
+            run_model(&model_path, &samples, &language)
+        })
+        .await
+        .map_err(|e| TranscriptionError::LocalEngine(e.to_string()))?
+    }
+
+    /// Audio is decoded in place, so compressing it first would only cost time
+    fn uploads_audio(&self) -> bool {
+        false
+    }
+
+    fn trims_silence(&self) -> bool {
+        self.trim_silence
+    }
+}
+
+/// Load the model, reusing the cached context for the same file
+fn load_model(model_path: &Path) -> Result<Arc<WhisperContext>, TranscriptionError> {
+    let mut loaded = LOADED_MODEL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((path, context)) = loaded.as_ref() {
+        if path == model_path {
+            return Ok(context.clone());
+        }
+    }
+
+    let path = model_path
+        .to_str()
+        .ok_or_else(|| TranscriptionError::LocalEngine("Invalid model path".to_string()))?;
+    let context = WhisperContext::new_with_params(path, WhisperContextParameters::default())
+        .map(Arc::new)
+        .map_err(|e| TranscriptionError::LocalEngine(format!("Failed to load model: {}", e)))?;
+
+    *loaded = Some((model_path.to_path_buf(), context.clone()));
+    Ok(context)
+}
+
+fn run_model(
+    model_path: &Path,
+    samples: &[f32],
+    language: &str,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    if samples.is_empty() {
+        return Err(TranscriptionError::InvalidAudioFormat(
+            "Recording contains no audio".to_string(),
+        ));
+    }
+
+    let context = load_model(model_path)?;
+    let engine_error = |e: whisper_rs::WhisperError| TranscriptionError::LocalEngine(e.to_string());
+    let mut state = context.create_state().map_err(engine_error)?;
+
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get().min(MAX_THREADS))
+        .unwrap_or(4);
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language));
+    params.set_n_threads(threads as i32);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+
+    state.full(params, samples).map_err(engine_error)?;
+
+    let mut segments = Vec::new();
+    for i in 0..state.full_n_segments().map_err(engine_error)? {
+        let text = state.full_get_segment_text_lossy(i).map_err(engine_error)?;
+        // Segment timestamps are in centiseconds
+        let start = state.full_get_segment_t0(i).map_err(engine_error)? as f64 / 100.0;
+        let end = state.full_get_segment_t1(i).map_err(engine_error)? as f64 / 100.0;
+        segments.push(TranscriptionSegment {
+            start,
+            end,
+            text: text.trim().to_string(),
+            words: None,
+        });
+    }
+
+    let text = segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(TranscriptionResult {
+        text,
+        segments: Some(segments),
+        removed_silence: Vec::new(),
+    })
+}
+
+/// Decode a recording to 16 kHz mono samples
+///
+/// `extension` (e.g. "webm") helps pick the container format.
+pub fn decode_to_whisper_pcm(data: &[u8], extension: Option<&str>) -> Result<Vec<f32>, String> {
+    let (samples, sample_rate) = decode_to_mono(data, extension)?;
+    Ok(resample(&samples, sample_rate, WHISPER_SAMPLE_RATE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_decode::wav_file;
+
+    #[test]
+    fn test_decode_wav_to_whisper_pcm() {
+        // One second of 8 kHz audio is resampled to 16 kHz
+        let data = wav_file(&[0.5; 8_000], 8_000);
+
+        let pcm = decode_to_whisper_pcm(&data, Some("wav")).unwrap();
+        assert_eq!(pcm.len(), 16_000);
+        assert!(pcm.iter().all(|sample| (sample - 0.5).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert!(decode_to_whisper_pcm(b"not audio at all", None).is_err());
+    }
+}
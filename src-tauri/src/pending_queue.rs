@@ -0,0 +1,465 @@
+//! Persistent queue of recordings waiting for the network to come back.
+//!
+//! When a transcription still fails with a network error after its retries,
+//! the recording is added to the queue in the history database. A background
+//! worker retries the queue oldest first, backing off while the network stays
+//! down, saves each transcription to history and reports it with a
+//! `pending-transcription-completed` event. The queue survives app restarts.
+//!
+//! Recordings queued by a pipeline or an import already have a history item;
+//! their transcription is filled in there and the pipeline resumes.
+
+use crate::commands;
+use crate::history::{HistoryItem, PipelineStepStatus};
+use crate::history_repository::HistoryRepository;
+use crate::settings::PipelineStep;
+use crate::transcription::{
+    provider_from_settings, transcribe_audio_file, TranscriptionError, TranscriptionProvider,
+    TranscriptionResult,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+/// Event emitted when a queued recording leaves the queue
+pub const PENDING_TRANSCRIPTION_COMPLETED_EVENT: &str = "pending-transcription-completed";
+
+/// Delay before the first retry, doubled after each failed round
+const RETRY_BASE_DELAY_SECS: u64 = 30;
+
+/// Upper bound for the delay between retry rounds
+const RETRY_MAX_DELAY_SECS: u64 = 30 * 60;
+
+/// A recording whose transcription is waiting to be retried
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTranscription {
+    /// Full path to the recording file
+    pub file_path: String,
+    /// Duration of the recording in seconds
+    pub duration_seconds: f64,
+    /// ISO 8601 timestamp when the recording was queued
+    pub created_at: String,
+    /// Number of failed retries from the queue
+    pub attempts: u32,
+    /// Error of the most recent failure
+    pub last_error: Option<String>,
+    /// ISO 8601 timestamp of the most recent retry
+    pub last_attempt_at: Option<String>,
+    /// History item waiting for the transcription (`None` creates a new one)
+    pub history_id: Option<String>,
+}
+
+impl PendingTranscription {
+    /// Creates a queue entry for a recording that just failed with `error`
+    pub fn new(file_path: String, duration_seconds: f64, error: &str) -> Self {
+        Self {
+            file_path,
+            duration_seconds,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            attempts: 0,
+            last_error: Some(error.to_string()),
+            last_attempt_at: None,
+            history_id: None,
+        }
+    }
+}
+
+/// Payload of the `pending-transcription-completed` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PendingTranscriptionEvent {
+    /// Recording that left the queue
+    pub file_path: String,
+    /// Whether the recording was transcribed and saved to history
+    pub success: bool,
+    /// History item the transcription was saved to (if successful)
+    pub history_id: Option<String>,
+    /// The transcribed text (if successful)
+    pub text: Option<String>,
+    /// User-friendly error message (if the recording can't be transcribed)
+    pub error_message: Option<String>,
+}
+
+/// Handle to the background worker, shared as Tauri managed state
+#[derive(Clone)]
+pub struct PendingQueue {
+    wake: Arc<Notify>,
+}
+
+impl PendingQueue {
+    /// Retry the queue now instead of waiting for the next round
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+}
+
+/// Start the background worker that drains the queue
+pub fn start(app: AppHandle) -> PendingQueue {
+    let queue = PendingQueue {
+        wake: Arc::new(Notify::new()),
+    };
+
+    let wake = queue.wake.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut failed_rounds = 0;
+        loop {
+            match process_app_queue(&app).await {
+                Ok(_) => failed_rounds = 0,
+                Err(e) => {
+                    failed_rounds += 1;
+                    log::info!("Transcription queue paused: {}", e);
+                }
+            }
+
+            // Either a wake-up or the timeout starts the next round
+            let _ = tokio::time::timeout(retry_delay(failed_rounds), wake.notified()).await;
+        }
+    });
+
+    queue
+}
+
+/// Delay before the next round after `failed_rounds` consecutive failures
+fn retry_delay(failed_rounds: u32) -> Duration {
+    let factor = 2u64.saturating_pow(failed_rounds.saturating_sub(1));
+    Duration::from_secs(
+        RETRY_BASE_DELAY_SECS
+            .saturating_mul(factor)
+            .min(RETRY_MAX_DELAY_SECS),
+    )
+}
+
+async fn process_app_queue(app: &AppHandle) -> Result<usize, TranscriptionError> {
+    let history = app.state::<HistoryRepository>();
+    if history
+        .list_pending()
+        .map_err(TranscriptionError::Unknown)?
+        .is_empty()
+    {
+        return Ok(0);
+    }
+
+    let settings = commands::get_settings_internal(app).map_err(TranscriptionError::Unknown)?;
+    let provider = provider_from_settings(&settings)?;
+
+    process_pending(&history, provider.as_ref(), &settings.language, |event| {
+        if let Some(history_id) = event.history_id.as_deref() {
+            commands::resume_pipeline(app, history_id);
+        }
+        if let Err(e) = app.emit(PENDING_TRANSCRIPTION_COMPLETED_EVENT, event) {
+            log::warn!("Failed to emit pending transcription update: {}", e);
+        }
+    })
+    .await
+}
+
+/// Retry every queued recording, oldest first
+///
+/// Successful transcriptions are saved to history and removed from the queue,
+/// filling in the waiting history item if there is one,
+/// as are recordings that can never be transcribed (e.g. a deleted file).
+/// Stops at the first error that affects the whole queue (network, rate limit
+/// or API key) and returns it; the remaining recordings stay queued.
+///
+/// Returns the number of recordings saved to history.
+pub async fn process_pending(
+    history: &HistoryRepository,
+    provider: &dyn TranscriptionProvider,
+    language: &str,
+    mut on_finished: impl FnMut(PendingTranscriptionEvent),
+) -> Result<usize, TranscriptionError> {
+    let mut saved = 0;
+
+    for pending in history
+        .list_pending()
+        .map_err(TranscriptionError::Unknown)?
+    {
+        match transcribe_audio_file(&pending.file_path, language, provider).await {
+            Ok(result) => {
+                let history_id = save_transcription(history, &pending, &result)
+                    .map_err(TranscriptionError::Unknown)?;
+                history
+                    .remove_pending(&pending.file_path)
+                    .map_err(TranscriptionError::Unknown)?;
+                saved += 1;
+
+                on_finished(PendingTranscriptionEvent {
+                    file_path: pending.file_path,
+                    success: true,
+                    history_id: Some(history_id),
+                    text: Some(result.text),
+                    error_message: None,
+                });
+            }
+            Err(e) if blocks_queue(&e) => {
+                history
+                    .record_pending_attempt(&pending.file_path, &e.to_string())
+                    .map_err(TranscriptionError::Unknown)?;
+                return Err(e);
+            }
+            Err(e) => {
+                log::warn!(
+                    "Dropping {} from transcription queue: {}",
+                    pending.file_path,
+                    e
+                );
+                history
+                    .remove_pending(&pending.file_path)
+                    .map_err(TranscriptionError::Unknown)?;
+
+                on_finished(PendingTranscriptionEvent {
+                    file_path: pending.file_path,
+                    success: false,
+                    history_id: None,
+                    text: None,
+                    error_message: Some(e.user_message()),
+                });
+            }
+        }
+    }
+
+    Ok(saved)
+}
+
+/// Saves a queued transcription to history, returning the item's ID
+///
+/// The history item waiting for it gets the transcription, and the transcribe
+/// step of its pipeline is marked as done so the rest of the pipeline can
+/// resume. Without one (or if it was deleted meanwhile) a new item is created.
+fn save_transcription(
+    history: &HistoryRepository,
+    pending: &PendingTranscription,
+    result: &TranscriptionResult,
+) -> Result<String, String> {
+    let waiting = match pending.history_id.as_deref() {
+        Some(id) => history.get(id)?,
+        None => None,
+    };
+    let Some(item) = waiting else {
+        let mut item = HistoryItem::new(
+            pending.file_path.clone(),
+            pending.duration_seconds,
+            result.text.clone(),
+        );
+        item.segments = result.segments.clone();
+        history.insert(&item)?;
+        return Ok(item.id);
+    };
+
+    history.update_transcription(&item.id, &result.text, result.segments.as_deref())?;
+    if let Some(mut run) = item.pipeline {
+        for state in &mut run.steps {
+            if state.step == PipelineStep::Transcribe {
+                state.status = PipelineStepStatus::Completed;
+                state.error_type = None;
+                state.error_message = None;
+            }
+        }
+        history.update_pipeline(&item.id, &run)?;
+    }
+    Ok(item.id)
+}
+
+/// Whether an error would also fail every other queued recording
+fn blocks_queue(error: &TranscriptionError) -> bool {
+    error.is_transient()
+        || matches!(
+            error,
+            TranscriptionError::ApiKeyNotConfigured | TranscriptionError::InvalidApiKey
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PipelineRun;
+    use crate::settings::Pipeline;
+    use std::fs;
+    use std::sync::Mutex;
+
+    /// Provider answering by file name; unknown files fail with a network error
+    struct FileNameProvider {
+        responses: Vec<(
+            &'static str,
+            Result<TranscriptionResult, TranscriptionError>,
+        )>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TranscriptionProvider for FileNameProvider {
+        async fn transcribe(
+            &self,
+            _file_data: &[u8],
+            file_name: &str,
+            _language: &str,
+        ) -> Result<TranscriptionResult, TranscriptionError> {
+            self.calls.lock().unwrap().push(file_name.to_string());
+            self.responses
+                .iter()
+                .find(|(name, _)| file_name.starts_with(name))
+                .map(|(_, response)| response.clone())
+                .unwrap_or_else(|| Err(TranscriptionError::NetworkError("offline".to_string())))
+        }
+    }
+
+    fn text(text: &str) -> Result<TranscriptionResult, TranscriptionError> {
+        Ok(TranscriptionResult {
+            text: text.to_string(),
+            segments: None,
+            removed_silence: Vec::new(),
+        })
+    }
+
+    fn write_temp_audio(prefix: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "{}-evervoice-pending-{}.webm",
+            prefix,
+            uuid::Uuid::new_v4()
+        ));
+        // EBML magic bytes, so the file passes format detection
+        fs::write(&path, b"\x1A\x45\xDF\xA3fake audio").unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn queue(history: &HistoryRepository, file_path: &str) {
+        history
+            .enqueue_pending(&PendingTranscription::new(
+                file_path.to_string(),
+                12.0,
+                "offline",
+            ))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_and_caps() {
+        assert_eq!(retry_delay(0), Duration::from_secs(30));
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(4), Duration::from_secs(240));
+        assert_eq!(retry_delay(20), Duration::from_secs(RETRY_MAX_DELAY_SECS));
+        assert_eq!(
+            retry_delay(u32::MAX),
+            Duration::from_secs(RETRY_MAX_DELAY_SECS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_pending_saves_history_and_drops_unreadable_files() {
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let good = write_temp_audio("good");
+        queue(&history, &good);
+        queue(&history, "/nonexistent/evervoice/missing.webm");
+
+        let provider = FileNameProvider {
+            responses: vec![("good", text("Back online"))],
+            calls: Mutex::new(Vec::new()),
+        };
+        let mut events = Vec::new();
+        let saved = process_pending(&history, &provider, "en", |event| events.push(event))
+            .await
+            .unwrap();
+
+        assert_eq!(saved, 1);
+        assert!(history.list_pending().unwrap().is_empty());
+
+        let items = history.list().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].file_path, good);
+        assert_eq!(items[0].transcription, "Back online");
+        assert_eq!(items[0].duration_seconds, 12.0);
+
+        assert_eq!(events.len(), 2);
+        assert!(events[0].success);
+        assert_eq!(events[0].history_id.as_deref(), Some(items[0].id.as_str()));
+        assert!(!events[1].success);
+        assert!(events[1].error_message.is_some());
+
+        let _ = fs::remove_file(&good);
+    }
+
+    #[tokio::test]
+    async fn test_process_pending_fills_in_waiting_pipeline_item() {
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let audio = write_temp_audio("pipeline");
+        let pipeline = Pipeline {
+            id: "notes".to_string(),
+            name: "Notes".to_string(),
+            steps: vec![
+                PipelineStep::Transcribe,
+                PipelineStep::Summarize { template_id: None },
+            ],
+        };
+        let mut item = HistoryItem::new(audio.clone(), 12.0, String::new());
+        let mut run = PipelineRun::new(&pipeline);
+        run.steps[0].status = PipelineStepStatus::Failed;
+        run.steps[0].error_type = Some("network_error".to_string());
+        run.steps[1].status = PipelineStepStatus::Skipped;
+        item.pipeline = Some(run);
+        history.insert(&item).unwrap();
+        history
+            .enqueue_pending(&PendingTranscription {
+                history_id: Some(item.id.clone()),
+                ..PendingTranscription::new(audio.clone(), 12.0, "offline")
+            })
+            .unwrap();
+
+        let provider = FileNameProvider {
+            responses: vec![("pipeline", text("Back online"))],
+            calls: Mutex::new(Vec::new()),
+        };
+        let mut events = Vec::new();
+        process_pending(&history, &provider, "en", |event| events.push(event))
+            .await
+            .unwrap();
+
+        let items = history.list().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].transcription, "Back online");
+        let run = items[0].pipeline.as_ref().unwrap();
+        assert_eq!(run.steps[0].status, PipelineStepStatus::Completed);
+        assert!(run.steps[0].error_type.is_none());
+        assert_eq!(run.next_step(), Some(1));
+        assert_eq!(events[0].history_id.as_deref(), Some(item.id.as_str()));
+
+        let _ = fs::remove_file(&audio);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_process_pending_stops_on_network_error() {
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let offline = write_temp_audio("offline");
+        let later = write_temp_audio("later");
+        queue(&history, &offline);
+        queue(&history, &later);
+
+        let provider = FileNameProvider {
+            responses: vec![("later", text("Not reached"))],
+            calls: Mutex::new(Vec::new()),
+        };
+        let mut events = Vec::new();
+        let result = process_pending(&history, &provider, "en", |event| events.push(event)).await;
+
+        assert!(matches!(result, Err(TranscriptionError::NetworkError(_))));
+        assert!(events.is_empty());
+        assert!(history.list().unwrap().is_empty());
+        assert!(provider
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|name| name.starts_with("offline")));
+
+        let pending = history.list_pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].file_path, offline);
+        assert_eq!(pending[0].attempts, 1);
+        assert_eq!(pending[1].attempts, 0);
+
+        let _ = fs::remove_file(&offline);
+        let _ = fs::remove_file(&later);
+    }
+}
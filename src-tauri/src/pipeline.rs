@@ -0,0 +1,265 @@
+//! Automatic post-transcription pipelines.
+//!
+//! A pipeline is a list of steps from settings (transcribe, summarize, run a
+//! custom action) that the backend runs one after another on a new recording.
+//! The status of every step is stored on the history item as a `PipelineRun`,
+//! so it survives restarts and a failed run can be resumed from the step that
+//! failed.
+
+use crate::history::{PipelineRun, PipelineStepStatus};
+use crate::history_repository::HistoryRepository;
+use crate::jobs::{JobError, JobOutput};
+use crate::settings::PipelineStep;
+use std::future::Future;
+
+/// Runs single pipeline steps; implemented by the app and by test mocks
+pub trait StepRunner: Send + Sync {
+    /// Run `step` on a history item
+    ///
+    /// Steps store their own output on the item. The returned text is
+    /// recorded as the step's result (used for custom action responses).
+    fn run(
+        &self,
+        step: &PipelineStep,
+        history_id: &str,
+    ) -> impl Future<Output = Result<Option<String>, JobError>> + Send;
+}
+
+/// Run the pipeline of a history item, starting at its first unfinished step
+///
+/// Every status change is saved before the next step starts. When a step
+/// fails, the remaining steps are marked as skipped and its error is
+/// returned. `progress` receives the fraction of steps done.
+pub async fn run_pipeline(
+    history: &HistoryRepository,
+    runner: &impl StepRunner,
+    history_id: &str,
+    progress: impl Fn(f64),
+) -> Result<JobOutput, JobError> {
+    let item = history
+        .get(history_id)
+        .map_err(|e| JobError::new("history_error", e))?
+        .ok_or_else(|| {
+            JobError::new(
+                "history_not_found",
+                format!("History item not found: {}", history_id),
+            )
+        })?;
+    let mut run = item.pipeline.ok_or_else(|| {
+        JobError::new(
+            "no_pipeline",
+            format!("History item {} has no pipeline", history_id),
+        )
+    })?;
+
+    let total = run.steps.len();
+    let start = run.next_step().unwrap_or(total);
+    for state in &mut run.steps[start..] {
+        state.status = PipelineStepStatus::Pending;
+        state.result = None;
+        state.error_type = None;
+        state.error_message = None;
+    }
+
+    for index in start..total {
+        run.steps[index].status = PipelineStepStatus::Running;
+        save(history, history_id, &run)?;
+
+        let step = run.steps[index].step.clone();
+        match runner.run(&step, history_id).await {
+            Ok(result) => {
+                let state = &mut run.steps[index];
+                state.status = PipelineStepStatus::Completed;
+                state.result = result;
+                progress((index + 1) as f64 / total as f64);
+            }
+            Err(error) => {
+                let state = &mut run.steps[index];
+                state.status = PipelineStepStatus::Failed;
+                state.error_type = Some(error.error_type.clone());
+                state.error_message = Some(error.message.clone());
+                for state in &mut run.steps[index + 1..] {
+                    state.status = PipelineStepStatus::Skipped;
+                }
+                save(history, history_id, &run)?;
+                return Err(error);
+            }
+        }
+    }
+    save(history, history_id, &run)?;
+
+    let item = history
+        .get(history_id)
+        .map_err(|e| JobError::new("history_error", e))?;
+    Ok(JobOutput {
+        text: item.map(|item| item.transcription).unwrap_or_default(),
+        history_id: Some(history_id.to_string()),
+    })
+}
+
+fn save(history: &HistoryRepository, history_id: &str, run: &PipelineRun) -> Result<(), JobError> {
+    history
+        .update_pipeline(history_id, run)
+        .map_err(|e| JobError::new("history_error", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryItem;
+    use crate::settings::Pipeline;
+    use std::sync::Mutex;
+
+    /// Records the steps it runs and fails the first `failures` custom actions
+    struct MockRunner<'a> {
+        history: &'a HistoryRepository,
+        calls: Mutex<Vec<PipelineStep>>,
+        failures: Mutex<u32>,
+    }
+
+    impl<'a> MockRunner<'a> {
+        fn new(history: &'a HistoryRepository, failures: u32) -> Self {
+            Self {
+                history,
+                calls: Mutex::new(Vec::new()),
+                failures: Mutex::new(failures),
+            }
+        }
+    }
+
+    impl StepRunner for MockRunner<'_> {
+        async fn run(
+            &self,
+            step: &PipelineStep,
+            history_id: &str,
+        ) -> Result<Option<String>, JobError> {
+            self.calls.lock().unwrap().push(step.clone());
+            match step {
+                PipelineStep::Transcribe => {
+                    self.history
+                        .update_transcription(history_id, "Hello world", None)
+                        .map_err(|e| JobError::new("history_error", e))?;
+                    Ok(None)
+                }
+                PipelineStep::Summarize { .. } => {
+                    self.history
+                        .update_summary(history_id, "- Hello")
+                        .map_err(|e| JobError::new("history_error", e))?;
+                    Ok(None)
+                }
+                PipelineStep::CustomAction { .. } => {
+                    let mut failures = self.failures.lock().unwrap();
+                    if *failures > 0 {
+                        *failures -= 1;
+                        return Err(JobError::new("server_error", "Service unavailable"));
+                    }
+                    Ok(Some("https://notes.example.com/1".to_string()))
+                }
+            }
+        }
+    }
+
+    fn pipeline() -> Pipeline {
+        Pipeline {
+            id: "notes".to_string(),
+            name: "Notes".to_string(),
+            steps: vec![
+                PipelineStep::Transcribe,
+                PipelineStep::CustomAction {
+                    action_id: "notion".to_string(),
+                },
+                PipelineStep::Summarize { template_id: None },
+            ],
+        }
+    }
+
+    fn repository_with_item() -> HistoryRepository {
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let mut item = HistoryItem::new("/path/a.webm".to_string(), 30.0, String::new());
+        item.id = "1".to_string();
+        item.pipeline = Some(PipelineRun::new(&pipeline()));
+        history.insert(&item).unwrap();
+        history
+    }
+
+    fn statuses(history: &HistoryRepository) -> Vec<PipelineStepStatus> {
+        let item = history.get("1").unwrap().unwrap();
+        let run = item.pipeline.unwrap();
+        run.steps.iter().map(|state| state.status).collect()
+    }
+
+    #[tokio::test]
+    async fn test_runs_all_steps_in_order() {
+        let history = repository_with_item();
+        let runner = MockRunner::new(&history, 0);
+        let reported = Mutex::new(Vec::new());
+
+        let output = run_pipeline(&history, &runner, "1", |p| reported.lock().unwrap().push(p))
+            .await
+            .unwrap();
+
+        assert_eq!(output.text, "Hello world");
+        assert_eq!(output.history_id.as_deref(), Some("1"));
+        assert_eq!(*runner.calls.lock().unwrap(), pipeline().steps);
+        assert_eq!(reported.into_inner().unwrap().last(), Some(&1.0));
+
+        let item = history.get("1").unwrap().unwrap();
+        assert_eq!(item.summary.as_deref(), Some("- Hello"));
+        let run = item.pipeline.unwrap();
+        assert!(run.next_step().is_none());
+        assert_eq!(
+            run.steps[1].result.as_deref(),
+            Some("https://notes.example.com/1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_skips_the_rest_and_resumes() {
+        let history = repository_with_item();
+        let runner = MockRunner::new(&history, 1);
+
+        let error = run_pipeline(&history, &runner, "1", |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_type, "server_error");
+        assert_eq!(
+            statuses(&history),
+            vec![
+                PipelineStepStatus::Completed,
+                PipelineStepStatus::Failed,
+                PipelineStepStatus::Skipped,
+            ]
+        );
+        let run = history.get("1").unwrap().unwrap().pipeline.unwrap();
+        assert_eq!(
+            run.steps[1].error_message.as_deref(),
+            Some("Service unavailable")
+        );
+
+        // Running again starts at the failed step
+        runner.calls.lock().unwrap().clear();
+        run_pipeline(&history, &runner, "1", |_| {}).await.unwrap();
+        assert_eq!(*runner.calls.lock().unwrap(), pipeline().steps[1..]);
+        assert_eq!(statuses(&history), vec![PipelineStepStatus::Completed; 3]);
+        let run = history.get("1").unwrap().unwrap().pipeline.unwrap();
+        assert!(run.steps[1].error_message.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_item_without_pipeline_fails() {
+        let history = HistoryRepository::open_in_memory().unwrap();
+        let item = HistoryItem::new("/path/a.webm".to_string(), 30.0, String::new());
+        history.insert(&item).unwrap();
+        let runner = MockRunner::new(&history, 0);
+
+        let error = run_pipeline(&history, &runner, &item.id, |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_type, "no_pipeline");
+
+        let error = run_pipeline(&history, &runner, "missing", |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_type, "history_not_found");
+    }
+}
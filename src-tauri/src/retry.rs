@@ -0,0 +1,202 @@
+//! Retry with exponential backoff for transient failures.
+//!
+//! Shared by transcription requests and external service calls. Errors decide
+//! whether they are worth retrying and may carry a delay requested by the
+//! server (e.g. a `Retry-After` header), which replaces the backoff delay.
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// An error that may go away when the operation is tried again
+pub trait Retryable {
+    /// Whether the operation should be tried again after this error
+    fn is_retryable(&self) -> bool;
+
+    /// Delay requested by the server before the next attempt
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// How often and how long to wait between attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay after the first failure, doubled after each further failure
+    pub base_delay: Duration,
+    /// Longest delay to wait; a server asking for more ends the retries
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Backoff delay after the failed attempt with index `attempt` (0-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+
+    /// Delay before retrying after `error`, or `None` to give up
+    fn delay_after(&self, attempt: u32, error: &impl Retryable) -> Option<Duration> {
+        if !error.is_retryable() || attempt + 1 >= self.max_attempts {
+            return None;
+        }
+
+        match error.retry_after() {
+            Some(delay) if delay > self.max_delay => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+/// Run `operation` until it succeeds, fails permanently or runs out of attempts
+///
+/// `what` names the operation in log messages. The last error is returned.
+pub async fn with_retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    what: &str,
+    mut operation: F,
+) -> Result<T, E>
+where
+    E: Retryable + Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+    loop {
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        let Some(delay) = policy.delay_after(attempt, &error) else {
+            return Err(error);
+        };
+        log::info!(
+            "{} attempt {} failed, retrying in {:?}: {}",
+            what,
+            attempt + 1,
+            delay,
+            error
+        );
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug, Clone)]
+    struct TestError {
+        retryable: bool,
+        retry_after: Option<Duration>,
+    }
+
+    impl Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "test error")
+        }
+    }
+
+    impl Retryable for TestError {
+        fn is_retryable(&self) -> bool {
+            self.retryable
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            self.retry_after
+        }
+    }
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(10),
+    };
+
+    /// Runs `with_retry` over errors from `errors`, then succeeds
+    async fn run(errors: Vec<TestError>) -> (Result<u32, TestError>, u32) {
+        let calls = AtomicU32::new(0);
+        let mut errors = errors.into_iter();
+        let result = with_retry(&POLICY, "Test", || {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let next = errors.next();
+            async move {
+                match next {
+                    Some(e) => Err(e),
+                    None => Ok(call),
+                }
+            }
+        })
+        .await;
+        (result, calls.load(Ordering::SeqCst))
+    }
+
+    fn retryable(retry_after: Option<u64>) -> TestError {
+        TestError {
+            retryable: true,
+            retry_after: retry_after.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(POLICY.backoff(0), Duration::from_secs(1));
+        assert_eq!(POLICY.backoff(1), Duration::from_secs(2));
+        assert_eq!(POLICY.backoff(3), Duration::from_secs(8));
+        assert_eq!(POLICY.backoff(4), Duration::from_secs(10));
+        assert_eq!(POLICY.backoff(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_until_success() {
+        let start = tokio::time::Instant::now();
+        let (result, calls) = run(vec![retryable(None), retryable(None)]).await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+        // 1s + 2s of backoff
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_max_attempts() {
+        let (result, calls) = run(vec![retryable(None); 3]).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_permanent_errors_are_not_retried() {
+        let permanent = TestError {
+            retryable: false,
+            retry_after: None,
+        };
+        let (result, calls) = run(vec![permanent]).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_after_replaces_backoff() {
+        let start = tokio::time::Instant::now();
+        let (result, calls) = run(vec![retryable(Some(7))]).await;
+        assert!(result.is_ok());
+        assert_eq!(calls, 2);
+        assert_eq!(start.elapsed(), Duration::from_secs(7));
+
+        // Waiting longer than the policy allows fails right away
+        let start = tokio::time::Instant::now();
+        let (result, calls) = run(vec![retryable(Some(60))]).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,701 @@
+//! Encrypted storage for credentials such as the OpenAI API key.
+//!
+//! Secrets are kept out of `settings.json`. The OS keyring (macOS Keychain,
+//! Windows Credential Manager, Secret Service on Linux) is used when it is
+//! available; otherwise secrets go to `secrets.json` in the application data
+//! directory, encrypted with ChaCha20-Poly1305 under a key derived from a
+//! random salt and the machine ID. The file fallback keeps headless Linux
+//! setups without a Secret Service daemon working.
+//!
+//! The machine ID isn't secret, so the file only keeps secrets out of copies
+//! taken to other machines (backups, synced folders). Anyone who can run code
+//! as the user on this machine can derive the same key. When no machine ID
+//! can be read the file fallback is refused rather than keyed with nothing.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Name of the OpenAI API key secret
+pub const OPENAI_API_KEY: &str = "openai-api-key";
+
+/// Name of the API key secret of an OpenAI-compatible transcription server
+pub const TRANSCRIPTION_API_KEY: &str = "transcription-api-key";
+
+/// Prefix of the secret holding a custom action's token or password
+const CUSTOM_ACTION_SECRET_PREFIX: &str = "custom-action-";
+
+/// Service name the secrets are filed under in the OS keyring
+const KEYRING_SERVICE: &str = "EverVoice";
+
+/// Encrypted fallback file inside the application data directory
+const SECRETS_FILE: &str = "secrets.json";
+
+/// Version of the encrypted file format
+const SECRETS_FILE_VERSION: u32 = 1;
+
+/// Domain separator mixed into the file encryption key
+const KEY_CONTEXT: &[u8] = b"EverVoice secret store v1";
+
+/// Files that hold a stable per-machine ID on Linux and the BSDs
+#[cfg(not(any(target_os = "macos", windows)))]
+const MACHINE_ID_FILES: &[&str] = &["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+/// Length of the ChaCha20-Poly1305 nonce stored before each ciphertext
+const NONCE_LEN: usize = 12;
+
+/// Characters of a secret shown at the end of its masked form
+const MASK_VISIBLE_CHARS: usize = 4;
+
+/// Prefix of a masked secret; never part of a real API key
+const MASK_PREFIX: &str = "••••••••";
+
+/// A place secrets can be stored
+pub trait SecretBackend: Send + Sync {
+    /// Name used in log messages
+    fn name(&self) -> &'static str;
+
+    /// Reads a secret, returning `None` if it isn't stored
+    fn get(&self, name: &str) -> Result<Option<String>, String>;
+
+    /// Stores or replaces a secret
+    fn set(&self, name: &str, value: &str) -> Result<(), String>;
+
+    /// Removes a secret; removing a missing secret is not an error
+    fn delete(&self, name: &str) -> Result<(), String>;
+}
+
+/// Secrets in the OS keyring
+pub struct KeyringBackend;
+
+impl KeyringBackend {
+    fn entry(name: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYRING_SERVICE, name)
+            .map_err(|e| format!("Failed to open keyring entry: {}", e))
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        match Self::entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read from keyring: {}", e)),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        Self::entry(name)?
+            .set_password(value)
+            .map_err(|e| format!("Failed to write to keyring: {}", e))
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        match Self::entry(name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete from keyring: {}", e)),
+        }
+    }
+}
+
+/// On-disk format of the encrypted secrets file
+#[derive(Debug, Serialize, Deserialize)]
+struct SecretsFile {
+    version: u32,
+    /// Base64 salt for the encryption key
+    salt: String,
+    /// Base64 nonce followed by ciphertext, by secret name
+    secrets: BTreeMap<String, String>,
+}
+
+/// Secrets in an encrypted JSON file
+///
+/// Each secret is sealed with its name as associated data, so entries can't
+/// be swapped inside the file. The key is bound to this machine, which
+/// protects copies of the file (backups, synced folders) rather than the file
+/// on a machine the attacker can already read as the user.
+pub struct EncryptedFileBackend {
+    path: PathBuf,
+    machine_id: Option<Vec<u8>>,
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileBackend {
+    /// Uses the secrets file at `path`, created on first write
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_machine_id(path, machine_id())
+    }
+
+    fn with_machine_id(path: PathBuf, machine_id: Option<Vec<u8>>) -> Self {
+        Self {
+            path,
+            machine_id,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn cipher(&self, salt: &[u8]) -> Result<ChaCha20Poly1305, String> {
+        let machine_id = self
+            .machine_id
+            .as_ref()
+            .ok_or_else(|| "No machine ID available to protect the secrets file".to_string())?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_CONTEXT);
+        hasher.update(salt);
+        hasher.update(machine_id);
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize())))
+    }
+
+    fn read(&self) -> Result<Option<SecretsFile>, String> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read secrets file: {}", e))?;
+        let file: SecretsFile = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse secrets file: {}", e))?;
+        if file.version != SECRETS_FILE_VERSION {
+            return Err(format!(
+                "Unsupported secrets file version: {}",
+                file.version
+            ));
+        }
+        Ok(Some(file))
+    }
+
+    fn write(&self, file: &SecretsFile) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(file)
+            .map_err(|e| format!("Failed to serialize secrets file: {}", e))?;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create application data directory: {}", e))?;
+        }
+
+        // Write next to the file and rename, so a crash never leaves it half written
+        let partial = self.path.with_extension("json.tmp");
+        write_private(&partial, contents.as_bytes())
+            .map_err(|e| format!("Failed to write secrets file: {}", e))?;
+        fs::rename(&partial, &self.path).map_err(|e| format!("Failed to save secrets file: {}", e))
+    }
+
+    fn salt(file: &SecretsFile) -> Result<Vec<u8>, String> {
+        BASE64
+            .decode(&file.salt)
+            .map_err(|e| format!("Failed to decode secrets file: {}", e))
+    }
+}
+
+impl SecretBackend for EncryptedFileBackend {
+    fn name(&self) -> &'static str {
+        "encrypted file"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        let _guard = self.lock();
+        let Some(file) = self.read()? else {
+            return Ok(None);
+        };
+        let Some(sealed) = file.secrets.get(name) else {
+            return Ok(None);
+        };
+
+        let sealed = BASE64
+            .decode(sealed)
+            .map_err(|e| format!("Failed to decode secret: {}", e))?;
+        if sealed.len() < NONCE_LEN {
+            return Err("Failed to decrypt secret: value is truncated".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+        let plaintext = self
+            .cipher(&Self::salt(&file)?)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                "Failed to decrypt secret: the secrets file was modified or belongs to another machine"
+                    .to_string()
+            })?;
+
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|e| format!("Failed to decrypt secret: {}", e))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        let _guard = self.lock();
+        let mut file = match self.read()? {
+            Some(file) => file,
+            None => SecretsFile {
+                version: SECRETS_FILE_VERSION,
+                salt: BASE64.encode(ChaCha20Poly1305::generate_key(&mut OsRng)),
+                secrets: BTreeMap::new(),
+            },
+        };
+
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(&Self::salt(&file)?)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: value.as_bytes(),
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|e| format!("Failed to encrypt secret: {}", e))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        file.secrets.insert(name.to_string(), BASE64.encode(sealed));
+        self.write(&file)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        let _guard = self.lock();
+        let Some(mut file) = self.read()? else {
+            return Ok(());
+        };
+        if file.secrets.remove(name).is_none() {
+            return Ok(());
+        }
+
+        if file.secrets.is_empty() {
+            fs::remove_file(&self.path).map_err(|e| format!("Failed to delete secrets file: {}", e))
+        } else {
+            self.write(&file)
+        }
+    }
+}
+
+/// Creates `path` readable only by the current user
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    fs::write(path, contents)
+}
+
+/// Stable ID of this machine, if one is available
+#[cfg(not(any(target_os = "macos", windows)))]
+fn machine_id() -> Option<Vec<u8>> {
+    MACHINE_ID_FILES
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().as_bytes().to_vec())
+        .find(|id| !id.is_empty())
+}
+
+/// Stable ID of this machine, if one is available
+#[cfg(target_os = "macos")]
+fn machine_id() -> Option<Vec<u8>> {
+    let output = std::process::Command::new("/usr/sbin/ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    parse_platform_uuid(&String::from_utf8_lossy(&output.stdout)).map(|id| id.as_bytes().to_vec())
+}
+
+/// Stable ID of this machine, if one is available
+#[cfg(windows)]
+fn machine_id() -> Option<Vec<u8>> {
+    use std::os::windows::process::CommandExt;
+
+    /// Keeps a console window from flashing up while `reg` runs
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    parse_machine_guid(&String::from_utf8_lossy(&output.stdout)).map(|id| id.as_bytes().to_vec())
+}
+
+/// Reads `IOPlatformUUID` from `ioreg -rd1 -c IOPlatformExpertDevice` output
+#[cfg(any(target_os = "macos", test))]
+fn parse_platform_uuid(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find(|line| line.contains("\"IOPlatformUUID\""))
+        .and_then(|line| line.split('"').nth(3))
+        .filter(|id| !id.is_empty())
+}
+
+/// Reads `MachineGuid` from `reg query ... /v MachineGuid` output
+#[cfg(any(windows, test))]
+fn parse_machine_guid(output: &str) -> Option<&str> {
+    output.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some("MachineGuid"), Some("REG_SZ"), Some(id)) => Some(id),
+            _ => None,
+        }
+    })
+}
+
+/// Secret storage shared as Tauri managed state
+///
+/// Backends are tried in order: reads return the first stored value, writes
+/// go to the first backend that accepts them and clear stale copies from the
+/// others. Values are cached after the first read so the keyring isn't asked
+/// on every request.
+pub struct SecretStore {
+    backends: Vec<Box<dyn SecretBackend>>,
+    cache: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl SecretStore {
+    /// Opens the default store: OS keyring, then an encrypted file in `app_data_dir`
+    pub fn open(app_data_dir: &Path) -> Self {
+        Self::with_backends(vec![
+            Box::new(KeyringBackend),
+            Box::new(EncryptedFileBackend::new(app_data_dir.join(SECRETS_FILE))),
+        ])
+    }
+
+    /// Creates a store over `backends`, most preferred first
+    pub fn with_backends(backends: Vec<Box<dyn SecretBackend>>) -> Self {
+        Self {
+            backends,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, HashMap<String, Option<String>>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reads a secret, returning `None` if no backend has it
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        if let Some(value) = self.cache().get(name) {
+            return Ok(value.clone());
+        }
+
+        let mut errors = Vec::new();
+        for backend in &self.backends {
+            match backend.get(name) {
+                Ok(Some(value)) => {
+                    self.cache().insert(name.to_string(), Some(value.clone()));
+                    return Ok(Some(value));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("Secret storage ({}) unavailable: {}", backend.name(), e);
+                    errors.push(e);
+                }
+            }
+        }
+
+        if errors.len() == self.backends.len() {
+            return Err(errors
+                .pop()
+                .unwrap_or_else(|| "No secret storage available".to_string()));
+        }
+        // A backend that failed might still hold the secret, so only cache a clean miss
+        if errors.is_empty() {
+            self.cache().insert(name.to_string(), None);
+        }
+        Ok(None)
+    }
+
+    /// Stores or replaces a secret
+    pub fn set(&self, name: &str, value: &str) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        for (index, backend) in self.backends.iter().enumerate() {
+            match backend.set(name, value) {
+                Ok(()) => {
+                    for other in &self.backends[index + 1..] {
+                        if let Err(e) = other.delete(name) {
+                            log::warn!("Failed to clear secret from {}: {}", other.name(), e);
+                        }
+                    }
+                    self.cache()
+                        .insert(name.to_string(), Some(value.to_string()));
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::warn!("Secret storage ({}) unavailable: {}", backend.name(), e);
+                    errors.push(e);
+                }
+            }
+        }
+
+        Err(errors
+            .pop()
+            .unwrap_or_else(|| "No secret storage available".to_string()))
+    }
+
+    /// Removes a secret from every backend
+    ///
+    /// Succeeds if at least one backend could be cleared; backends that are
+    /// unavailable can't hold the secret either.
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        self.cache().remove(name);
+
+        let mut last_error = None;
+        let mut cleared = false;
+        for backend in &self.backends {
+            match backend.delete(name) {
+                Ok(()) => cleared = true,
+                Err(e) => {
+                    log::warn!("Failed to clear secret from {}: {}", backend.name(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if !cleared => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Name of the secret holding the token or password of a custom action
+pub fn custom_action_secret(action_id: &str) -> String {
+    format!("{}{}", CUSTOM_ACTION_SECRET_PREFIX, action_id)
+}
+
+/// Name of the secret a custom action signs its requests with
+pub fn custom_action_signing_secret(action_id: &str) -> String {
+    format!("{}{}-signing", CUSTOM_ACTION_SECRET_PREFIX, action_id)
+}
+
+/// Masks a secret for display, keeping only its last few characters
+///
+/// e.g., "sk-proj-abc123xyz" → "••••••••3xyz"
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    // Short secrets are masked completely
+    if chars.len() <= MASK_VISIBLE_CHARS * 2 {
+        return MASK_PREFIX.to_string();
+    }
+
+    let visible: String = chars[chars.len() - MASK_VISIBLE_CHARS..].iter().collect();
+    format!("{}{}", MASK_PREFIX, visible)
+}
+
+/// Whether `value` is a masked secret as returned by `mask_secret`
+pub fn is_masked(value: &str) -> bool {
+    value.starts_with(MASK_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_secrets_path() -> PathBuf {
+        std::env::temp_dir().join(format!("evervoice-secrets-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    /// Backend that fails every call, like a keyring without a running daemon
+    struct UnavailableBackend;
+
+    impl SecretBackend for UnavailableBackend {
+        fn name(&self) -> &'static str {
+            "unavailable"
+        }
+
+        fn get(&self, _name: &str) -> Result<Option<String>, String> {
+            Err("no keyring".to_string())
+        }
+
+        fn set(&self, _name: &str, _value: &str) -> Result<(), String> {
+            Err("no keyring".to_string())
+        }
+
+        fn delete(&self, _name: &str) -> Result<(), String> {
+            Err("no keyring".to_string())
+        }
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let path = temp_secrets_path();
+        let backend =
+            EncryptedFileBackend::with_machine_id(path.clone(), Some(b"machine-a".to_vec()));
+
+        assert_eq!(backend.get(OPENAI_API_KEY).unwrap(), None);
+        backend.set(OPENAI_API_KEY, "sk-test-1234567890").unwrap();
+        backend.set("other", "value").unwrap();
+        assert_eq!(
+            backend.get(OPENAI_API_KEY).unwrap().as_deref(),
+            Some("sk-test-1234567890")
+        );
+
+        // The key never appears in the file
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("sk-test"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        backend.delete(OPENAI_API_KEY).unwrap();
+        backend.delete(OPENAI_API_KEY).unwrap();
+        assert_eq!(backend.get(OPENAI_API_KEY).unwrap(), None);
+        assert_eq!(backend.get("other").unwrap().as_deref(), Some("value"));
+
+        // Removing the last secret removes the file
+        backend.delete("other").unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_encrypted_file_rejects_tampering_and_other_machines() {
+        let path = temp_secrets_path();
+        let backend =
+            EncryptedFileBackend::with_machine_id(path.clone(), Some(b"machine-a".to_vec()));
+        backend.set(OPENAI_API_KEY, "sk-test-1234567890").unwrap();
+        backend.set("other", "value").unwrap();
+
+        let other_machine =
+            EncryptedFileBackend::with_machine_id(path.clone(), Some(b"machine-b".to_vec()));
+        assert!(other_machine.get(OPENAI_API_KEY).is_err());
+
+        // Swapping entries fails because the name is authenticated
+        let mut file: SecretsFile =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let sealed = file.secrets.remove("other").unwrap();
+        file.secrets.insert(OPENAI_API_KEY.to_string(), sealed);
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+        assert!(backend.get(OPENAI_API_KEY).is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_encrypted_file_requires_machine_id() {
+        let path = temp_secrets_path();
+        let backend = EncryptedFileBackend::with_machine_id(path.clone(), None);
+
+        assert!(backend.set(OPENAI_API_KEY, "sk-test-1234567890").is_err());
+        assert!(!path.exists());
+        assert_eq!(backend.get(OPENAI_API_KEY).unwrap(), None);
+
+        // A file written while the ID was readable can't be opened without it
+        EncryptedFileBackend::with_machine_id(path.clone(), Some(b"machine-a".to_vec()))
+            .set(OPENAI_API_KEY, "sk-test-1234567890")
+            .unwrap();
+        assert!(backend.get(OPENAI_API_KEY).is_err());
+        backend.delete(OPENAI_API_KEY).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_parse_machine_ids() {
+        let ioreg = r#"+-o J314sAP  <class IOPlatformExpertDevice, id 0x100000223, registered>
+    {
+      "IOPlatformSerialNumber" = "C02XXXXXXXXX"
+      "IOPlatformUUID" = "4C4C4544-0042-3510-8051-B2C04F4C4B32"
+    }
+"#;
+        assert_eq!(
+            parse_platform_uuid(ioreg),
+            Some("4C4C4544-0042-3510-8051-B2C04F4C4B32")
+        );
+        assert_eq!(parse_platform_uuid("\"IOPlatformUUID\" = \"\""), None);
+        assert_eq!(parse_platform_uuid(""), None);
+
+        let reg = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Cryptography\r\n    MachineGuid    REG_SZ    6f2a3c1e-8b4d-4e5f-9a7b-0c1d2e3f4a5b\r\n\r\n";
+        assert_eq!(
+            parse_machine_guid(reg),
+            Some("6f2a3c1e-8b4d-4e5f-9a7b-0c1d2e3f4a5b")
+        );
+        assert_eq!(
+            parse_machine_guid(
+                "ERROR: The system was unable to find the specified registry key or value."
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_store_falls_back_when_keyring_is_unavailable() {
+        let path = temp_secrets_path();
+        let store = SecretStore::with_backends(vec![
+            Box::new(UnavailableBackend),
+            Box::new(EncryptedFileBackend::with_machine_id(
+                path.clone(),
+                Some(b"machine-a".to_vec()),
+            )),
+        ]);
+
+        assert_eq!(store.get(OPENAI_API_KEY).unwrap(), None);
+        store.set(OPENAI_API_KEY, "sk-fallback").unwrap();
+        assert_eq!(
+            store.get(OPENAI_API_KEY).unwrap().as_deref(),
+            Some("sk-fallback")
+        );
+
+        // A fresh store reads the value back from the file
+        let reopened = SecretStore::with_backends(vec![Box::new(
+            EncryptedFileBackend::with_machine_id(path.clone(), Some(b"machine-a".to_vec())),
+        )]);
+        assert_eq!(
+            reopened.get(OPENAI_API_KEY).unwrap().as_deref(),
+            Some("sk-fallback")
+        );
+
+        store.delete(OPENAI_API_KEY).unwrap();
+        assert_eq!(store.get(OPENAI_API_KEY).unwrap(), None);
+        assert!(!path.exists());
+
+        let unavailable = SecretStore::with_backends(vec![Box::new(UnavailableBackend)]);
+        assert!(unavailable.set(OPENAI_API_KEY, "sk-fallback").is_err());
+        assert!(unavailable.get(OPENAI_API_KEY).is_err());
+    }
+
+    #[test]
+    fn test_mask_secret() {
+        assert_eq!(mask_secret("sk-proj-abc123xyz"), "••••••••3xyz");
+        assert_eq!(mask_secret("short"), "••••••••");
+        assert!(is_masked(&mask_secret("sk-proj-abc123xyz")));
+        assert!(!is_masked("sk-proj-abc123xyz"));
+        assert!(!is_masked(""));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::local_models;
 use serde::{Deserialize, Serialize};
 
 /// Default maximum recording duration in minutes
@@ -9,16 +10,313 @@ pub const DEFAULT_LANGUAGE: &str = "de";
 /// Default global hotkey for recording toggle
 pub const DEFAULT_GLOBAL_HOTKEY: &str = "Ctrl+Shift+R";
 
+/// Default chat model for summarization and title generation
+pub const DEFAULT_SUMMARIZATION_MODEL: &str = "gpt-4o-mini";
+
+/// Longest request timeout a custom action may configure
+pub const MAX_CUSTOM_ACTION_TIMEOUT_SECS: u64 = 300;
+
+/// ID of the built-in bullet-point summary template
+pub const DEFAULT_PROMPT_TEMPLATE_ID: &str = "summary";
+
 /// Custom action configuration for external service integration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CustomAction {
     /// Unique identifier for the action
     pub id: String,
     /// Display name for the action button
     pub name: String,
-    /// API endpoint URL to POST transcription text to
+    /// API endpoint URL to send the transcription to
     pub url: String,
+    /// HTTP method of the request
+    #[serde(default)]
+    pub method: HttpMethod,
+    /// Extra request headers
+    #[serde(default)]
+    pub headers: Vec<HttpHeader>,
+    /// Authentication sent with the request
+    #[serde(default)]
+    pub auth: CustomActionAuth,
+    /// Shared secret for HMAC-SHA256 request signatures (kept in the secret store)
+    #[serde(default)]
+    pub signing_secret: Option<String>,
+    /// Request body with placeholders (defaults to `{"text": "{transcription}"}`)
+    ///
+    /// Supported placeholders: `{transcription}`, `{summary}`, `{title}`,
+    /// `{language}`, `{duration}` and `{created_at}`.
+    #[serde(default)]
+    pub body_template: Option<String>,
+    /// Fields of the JSON body sent when there is no body template
+    #[serde(default = "default_payload_fields")]
+    pub payload_fields: Vec<PayloadField>,
+    /// Send text verbatim instead of collapsing line breaks and whitespace
+    #[serde(default)]
+    pub preserve_formatting: bool,
+    /// Request timeout in seconds (defaults to 30)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// JSON pointer (RFC 6901) selecting the result in a JSON response,
+    /// e.g. "/data/url"; the whole response body is used when unset
+    #[serde(default)]
+    pub response_pointer: Option<String>,
+    /// What to do with the result
+    #[serde(default)]
+    pub response_action: ResponseAction,
+}
+
+/// What a custom action does with the result of its response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResponseAction {
+    /// Show the result to the user
+    #[default]
+    Show,
+    /// Store the result as the history item's transcription
+    ReplaceTranscription,
+    /// Store the result as the history item's summary
+    ReplaceSummary,
+    /// Open the result, which must be an http(s) URL
+    OpenUrl,
+}
+
+impl ResponseAction {
+    /// Whether the action stores the result on a history item
+    pub fn updates_history(self) -> bool {
+        matches!(
+            self,
+            ResponseAction::ReplaceTranscription | ResponseAction::ReplaceSummary
+        )
+    }
+}
+
+/// Field of the default custom action body, named by its JSON key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PayloadField {
+    /// The transcription, sent as `text`
+    Text,
+    Summary,
+    Title,
+    /// Language code
+    Language,
+    /// Duration in seconds
+    Duration,
+    /// RFC 3339 timestamp of the recording
+    CreatedAt,
+}
+
+fn default_payload_fields() -> Vec<PayloadField> {
+    vec![PayloadField::Text]
+}
+
+/// HTTP method used by a custom action
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    Get,
+    #[default]
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+/// Request header sent by a custom action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// Authentication for a custom action
+///
+/// Tokens and passwords are kept in the secret store; `settings.json` only
+/// records which kind of authentication an action uses.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum CustomActionAuth {
+    /// No authentication
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>`
+    Bearer {
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// HTTP basic authentication
+    Basic {
+        username: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+impl CustomActionAuth {
+    /// The token or password, if this kind of authentication has one
+    pub fn secret(&self) -> Option<&Option<String>> {
+        match self {
+            CustomActionAuth::None => None,
+            CustomActionAuth::Bearer { token } => Some(token),
+            CustomActionAuth::Basic { password, .. } => Some(password),
+        }
+    }
+
+    /// Mutable access to the token or password
+    pub fn secret_mut(&mut self) -> Option<&mut Option<String>> {
+        match self {
+            CustomActionAuth::None => None,
+            CustomActionAuth::Bearer { token } => Some(token),
+            CustomActionAuth::Basic { password, .. } => Some(password),
+        }
+    }
+}
+
+/// Named system prompt for summarization
+///
+/// The prompt may contain the placeholders `{language}`, `{date}` and
+/// `{duration}`, which are replaced before the request is sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    /// Unique identifier for the template
+    pub id: String,
+    /// Display name for the template picker
+    pub name: String,
+    /// System prompt sent with the transcription
+    pub prompt: String,
+    /// Chat model for this template (defaults to `Settings::summarization_model`)
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl PromptTemplate {
+    fn built_in(id: &str, name: &str, prompt: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+            model: None,
+        }
+    }
+}
+
+/// Templates available before the user customizes anything
+pub fn default_prompt_templates() -> Vec<PromptTemplate> {
+    vec![
+        PromptTemplate::built_in(
+            DEFAULT_PROMPT_TEMPLATE_ID,
+            "Summary",
+            "Summarize the following transcription into concise Markdown-formatted bullet points. Respond in {language}.",
+        ),
+        PromptTemplate::built_in(
+            "meeting-minutes",
+            "Meeting minutes",
+            "Write meeting minutes for the following transcription of a meeting on {date} (duration {duration}). Use Markdown with the sections Participants, Discussion, Decisions and Action Items. Respond in {language}.",
+        ),
+        PromptTemplate::built_in(
+            "action-items",
+            "Action items",
+            "List every action item in the following transcription as a Markdown checklist (\"- [ ] ...\"), including the owner and due date when they are mentioned. Respond in {language}.",
+        ),
+        PromptTemplate::built_in(
+            "email-draft",
+            "Email draft",
+            "Turn the following transcription into a clear, friendly email draft with a subject line. Respond in {language}.",
+        ),
+        PromptTemplate::built_in(
+            "tweet",
+            "Tweet",
+            "Condense the following transcription into a single engaging tweet of at most 280 characters. Respond in {language}.",
+        ),
+    ]
+}
+
+/// Steps run in order on a new recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pipeline {
+    /// Unique identifier for the pipeline
+    pub id: String,
+    /// Display name for the pipeline picker
+    pub name: String,
+    /// Steps, starting with `Transcribe`
+    pub steps: Vec<PipelineStep>,
+}
+
+/// A single step of a pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum PipelineStep {
+    /// Transcribe the recording with the configured provider
+    Transcribe,
+    /// Summarize the transcription and store the summary
+    Summarize {
+        /// Prompt template to use (defaults to `Settings::default_prompt_template`)
+        #[serde(default)]
+        template_id: Option<String>,
+    },
+    /// Run a custom action on the transcription
+    CustomAction { action_id: String },
+}
+
+/// Transcription backend selection
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TranscriptionProviderSettings {
+    /// Hosted OpenAI Whisper API using `Settings::api_key`
+    #[default]
+    OpenAi,
+    /// Any server implementing the OpenAI `/audio/transcriptions` endpoint
+    OpenAiCompatible {
+        /// Base URL including the API version (e.g., "http://localhost:8000/v1")
+        base_url: String,
+        /// API key for the server (optional for self-hosted servers, kept in the secret store)
+        #[serde(default)]
+        api_key: Option<String>,
+        /// Model name (defaults to "whisper-1")
+        #[serde(default)]
+        model: Option<String>,
+    },
+    /// On-device whisper.cpp model (needs a build with the `local-whisper` feature)
+    Local {
+        /// Model ID from the local model catalog (e.g., "base.en")
+        model: String,
+    },
+}
+
+impl TranscriptionProviderSettings {
+    /// The API key, if this provider has its own
+    pub fn api_key(&self) -> Option<&Option<String>> {
+        match self {
+            TranscriptionProviderSettings::OpenAiCompatible { api_key, .. } => Some(api_key),
+            TranscriptionProviderSettings::OpenAi | TranscriptionProviderSettings::Local { .. } => {
+                None
+            }
+        }
+    }
+
+    /// Mutable access to the API key
+    pub fn api_key_mut(&mut self) -> Option<&mut Option<String>> {
+        match self {
+            TranscriptionProviderSettings::OpenAiCompatible { api_key, .. } => Some(api_key),
+            TranscriptionProviderSettings::OpenAi | TranscriptionProviderSettings::Local { .. } => {
+                None
+            }
+        }
+    }
 }
 
 /// Application settings stored via tauri-plugin-store
@@ -38,12 +336,51 @@ pub struct Settings {
     /// Global hotkey for toggling recording (e.g., "Ctrl+Shift+R")
     #[serde(default)]
     pub global_hotkey: Option<String>,
+    /// Transcription backend to use
+    #[serde(default)]
+    pub transcription_provider: TranscriptionProviderSettings,
+    /// Request word-level timestamps in addition to segments
+    #[serde(default)]
+    pub word_timestamps: bool,
+    /// Cut long silences out of recordings before transcribing them
+    #[serde(default = "default_trim_silence")]
+    pub trim_silence: bool,
+    /// Chat model used when a prompt template doesn't choose one
+    #[serde(default = "default_summarization_model")]
+    pub summarization_model: String,
+    /// Named summarization prompts
+    #[serde(default = "default_prompt_templates")]
+    pub prompt_templates: Vec<PromptTemplate>,
+    /// Template used when a summarize call doesn't select one
+    #[serde(default = "default_prompt_template_id")]
+    pub default_prompt_template: String,
+    /// Named post-transcription pipelines
+    #[serde(default)]
+    pub pipelines: Vec<Pipeline>,
+    /// Pipeline run automatically on every saved recording (none by default)
+    #[serde(default)]
+    pub auto_pipeline: Option<String>,
+    /// Folder watched for audio files to import and transcribe (none by default)
+    #[serde(default)]
+    pub inbox_folder: Option<String>,
 }
 
 fn default_language() -> String {
     DEFAULT_LANGUAGE.to_string()
 }
 
+fn default_trim_silence() -> bool {
+    true
+}
+
+fn default_summarization_model() -> String {
+    DEFAULT_SUMMARIZATION_MODEL.to_string()
+}
+
+fn default_prompt_template_id() -> String {
+    DEFAULT_PROMPT_TEMPLATE_ID.to_string()
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -52,6 +389,15 @@ impl Default for Settings {
             language: DEFAULT_LANGUAGE.to_string(),
             custom_actions: Vec::new(),
             global_hotkey: None,
+            transcription_provider: TranscriptionProviderSettings::default(),
+            word_timestamps: false,
+            trim_silence: default_trim_silence(),
+            summarization_model: default_summarization_model(),
+            prompt_templates: default_prompt_templates(),
+            default_prompt_template: default_prompt_template_id(),
+            pipelines: Vec::new(),
+            auto_pipeline: None,
+            inbox_folder: None,
         }
     }
 }
@@ -69,9 +415,57 @@ impl Settings {
         if let Some(ref hotkey) = self.global_hotkey {
             validate_hotkey_format(hotkey)?;
         }
+        if let TranscriptionProviderSettings::OpenAiCompatible { ref base_url, .. } =
+            self.transcription_provider
+        {
+            let url = base_url.to_lowercase();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(
+                    "Transcription server URL must start with http:// or https://".to_string(),
+                );
+            }
+        }
+        if let TranscriptionProviderSettings::Local { ref model } = self.transcription_provider {
+            if local_models::model_spec(model).is_none() {
+                return Err(format!("Unknown local transcription model: {}", model));
+            }
+        }
+        validate_custom_actions(&self.custom_actions)?;
+        if self.summarization_model.trim().is_empty() {
+            return Err("Summarization model cannot be empty".to_string());
+        }
+        validate_prompt_templates(&self.prompt_templates)?;
+        let default_template = &self.default_prompt_template;
+        if self.prompt_template(default_template).is_none() {
+            return Err(format!(
+                "Default prompt template not found: {}",
+                default_template
+            ));
+        }
+        validate_pipelines(self)?;
+        if let Some(ref id) = self.auto_pipeline {
+            if self.pipeline(id).is_none() {
+                return Err(format!("Automatic pipeline not found: {}", id));
+            }
+        }
+        if let Some(ref folder) = self.inbox_folder {
+            if !std::path::Path::new(folder).is_absolute() {
+                return Err("Inbox folder must be an absolute path".to_string());
+            }
+        }
         Ok(())
     }
 
+    /// Looks up a prompt template by ID
+    pub fn prompt_template(&self, id: &str) -> Option<&PromptTemplate> {
+        self.prompt_templates.iter().find(|t| t.id == id)
+    }
+
+    /// Looks up a pipeline by ID
+    pub fn pipeline(&self, id: &str) -> Option<&Pipeline> {
+        self.pipelines.iter().find(|p| p.id == id)
+    }
+
     /// Returns the effective global hotkey (user setting or default)
     pub fn effective_global_hotkey(&self) -> &str {
         self.global_hotkey
@@ -80,6 +474,154 @@ impl Settings {
     }
 }
 
+/// Validates that prompt templates have unique IDs and non-empty names and prompts
+fn validate_prompt_templates(templates: &[PromptTemplate]) -> Result<(), String> {
+    for (i, template) in templates.iter().enumerate() {
+        if template.id.trim().is_empty() {
+            return Err("Prompt template ID cannot be empty".to_string());
+        }
+        if template.name.trim().is_empty() {
+            return Err(format!("Prompt template {} needs a name", template.id));
+        }
+        if template.prompt.trim().is_empty() {
+            return Err(format!("Prompt template {} needs a prompt", template.name));
+        }
+        let model = template.model.as_deref();
+        if model.is_some_and(|m| m.trim().is_empty()) {
+            return Err(format!(
+                "Prompt template {} has an empty model",
+                template.name
+            ));
+        }
+        if templates[..i].iter().any(|t| t.id == template.id) {
+            return Err(format!("Duplicate prompt template ID: {}", template.id));
+        }
+    }
+    Ok(())
+}
+
+/// Validates that pipelines have unique IDs, start with a single transcription
+/// and only refer to existing prompt templates and custom actions
+fn validate_pipelines(settings: &Settings) -> Result<(), String> {
+    let pipelines = &settings.pipelines;
+    for (i, pipeline) in pipelines.iter().enumerate() {
+        if pipeline.id.trim().is_empty() {
+            return Err("Pipeline ID cannot be empty".to_string());
+        }
+        if pipeline.name.trim().is_empty() {
+            return Err(format!("Pipeline {} needs a name", pipeline.id));
+        }
+        if pipelines[..i].iter().any(|p| p.id == pipeline.id) {
+            return Err(format!("Duplicate pipeline ID: {}", pipeline.id));
+        }
+        if pipeline.steps.first() != Some(&PipelineStep::Transcribe)
+            || pipeline.steps[1..].contains(&PipelineStep::Transcribe)
+        {
+            return Err(format!(
+                "Pipeline {} must start with its only transcription step",
+                pipeline.name
+            ));
+        }
+        for step in &pipeline.steps {
+            match step {
+                PipelineStep::Transcribe => {}
+                PipelineStep::Summarize { template_id } => {
+                    if let Some(id) = template_id {
+                        if settings.prompt_template(id).is_none() {
+                            return Err(format!(
+                                "Pipeline {} uses an unknown prompt template: {}",
+                                pipeline.name, id
+                            ));
+                        }
+                    }
+                }
+                PipelineStep::CustomAction { action_id } => {
+                    if !settings.custom_actions.iter().any(|a| &a.id == action_id) {
+                        return Err(format!(
+                            "Pipeline {} uses an unknown custom action: {}",
+                            pipeline.name, action_id
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validates custom action IDs, URLs, headers and body templates
+fn validate_custom_actions(actions: &[CustomAction]) -> Result<(), String> {
+    for (i, action) in actions.iter().enumerate() {
+        if action.id.trim().is_empty() {
+            return Err("Custom action ID cannot be empty".to_string());
+        }
+        if actions[..i].iter().any(|a| a.id == action.id) {
+            return Err(format!("Duplicate custom action ID: {}", action.id));
+        }
+        let url = action.url.to_lowercase();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!(
+                "URL of custom action {} must start with http:// or https://",
+                action.name
+            ));
+        }
+        for header in &action.headers {
+            if !is_valid_header_name(&header.name) {
+                return Err(format!(
+                    "Custom action {} has an invalid header name: {}",
+                    action.name, header.name
+                ));
+            }
+            if header.value.contains(['\r', '\n']) {
+                return Err(format!(
+                    "Header {} of custom action {} cannot contain line breaks",
+                    header.name, action.name
+                ));
+            }
+        }
+        if let CustomActionAuth::Basic { ref username, .. } = action.auth {
+            if username.trim().is_empty() || username.contains(':') {
+                return Err(format!(
+                    "Custom action {} needs a username without ':'",
+                    action.name
+                ));
+            }
+        }
+        if action
+            .timeout_secs
+            .is_some_and(|secs| !(1..=MAX_CUSTOM_ACTION_TIMEOUT_SECS).contains(&secs))
+        {
+            return Err(format!(
+                "Timeout of custom action {} must be between 1 and {} seconds",
+                action.name, MAX_CUSTOM_ACTION_TIMEOUT_SECS
+            ));
+        }
+        if let Some(ref pointer) = action.response_pointer {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(format!(
+                    "Response pointer of custom action {} must start with '/'",
+                    action.name
+                ));
+            }
+        }
+        if action.method == HttpMethod::Get && action.body_template.is_some() {
+            return Err(format!(
+                "Custom action {} sends GET requests, which have no body",
+                action.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` is a valid HTTP header name (an RFC 9110 token)
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
 /// Validates that a hotkey string is in the correct format
 /// Format: Modifier+Modifier+Key (e.g., "Ctrl+Shift+R")
 /// At least one modifier (Ctrl, Alt, Shift, Meta) is required
@@ -151,6 +693,113 @@ mod tests {
         assert_eq!(settings.language, "de");
         assert!(settings.custom_actions.is_empty());
         assert!(settings.global_hotkey.is_none());
+        assert_eq!(
+            settings.transcription_provider,
+            TranscriptionProviderSettings::OpenAi
+        );
+    }
+
+    #[test]
+    fn test_settings_without_provider_defaults_to_openai() {
+        // JSON without transcriptionProvider (simulating old settings)
+        let json = r#"{"maxDuration": 5, "apiKey": null, "language": "en"}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            settings.transcription_provider,
+            TranscriptionProviderSettings::OpenAi
+        );
+    }
+
+    #[test]
+    fn test_trim_silence_defaults_to_enabled() {
+        assert!(Settings::default().trim_silence);
+
+        let json = r#"{"maxDuration": 5, "apiKey": null}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert!(settings.trim_silence);
+
+        let json = r#"{"maxDuration": 5, "apiKey": null, "trimSilence": false}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert!(!settings.trim_silence);
+    }
+
+    #[test]
+    fn test_openai_compatible_provider_serialization() {
+        let json = r#"{
+            "maxDuration": 5,
+            "apiKey": null,
+            "transcriptionProvider": {
+                "type": "openAiCompatible",
+                "baseUrl": "http://localhost:8000/v1",
+                "model": "Systran/faster-whisper-small"
+            }
+        }"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            settings.transcription_provider,
+            TranscriptionProviderSettings::OpenAiCompatible {
+                base_url: "http://localhost:8000/v1".to_string(),
+                api_key: None,
+                model: Some("Systran/faster-whisper-small".to_string()),
+            }
+        );
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_provider_api_key_access() {
+        let mut provider = TranscriptionProviderSettings::OpenAiCompatible {
+            base_url: "http://localhost:8000/v1".to_string(),
+            api_key: Some("secret".to_string()),
+            model: None,
+        };
+        assert_eq!(provider.api_key(), Some(&Some("secret".to_string())));
+        *provider.api_key_mut().unwrap() = None;
+        assert_eq!(provider.api_key(), Some(&None));
+
+        assert!(TranscriptionProviderSettings::OpenAi.api_key().is_none());
+        let mut local = TranscriptionProviderSettings::Local {
+            model: "base.en".to_string(),
+        };
+        assert!(local.api_key_mut().is_none());
+    }
+
+    #[test]
+    fn test_validate_openai_compatible_requires_http_url() {
+        let settings = Settings {
+            transcription_provider: TranscriptionProviderSettings::OpenAiCompatible {
+                base_url: "localhost:8000".to_string(),
+                api_key: None,
+                model: None,
+            },
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_local_provider_serialization_and_validation() {
+        let json = r#"{
+            "maxDuration": 5,
+            "apiKey": null,
+            "transcriptionProvider": { "type": "local", "model": "base.en" }
+        }"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            settings.transcription_provider,
+            TranscriptionProviderSettings::Local {
+                model: "base.en".to_string(),
+            }
+        );
+        assert!(settings.validate().is_ok());
+
+        let settings = Settings {
+            transcription_provider: TranscriptionProviderSettings::Local {
+                model: "huge".to_string(),
+            },
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_err());
     }
 
     #[test]
@@ -161,6 +810,7 @@ mod tests {
             language: "en".to_string(),
             custom_actions: Vec::new(),
             global_hotkey: Some("Ctrl+Shift+R".to_string()),
+            ..Settings::default()
         };
         assert!(settings.validate().is_ok());
     }
@@ -173,6 +823,7 @@ mod tests {
             language: "de".to_string(),
             custom_actions: Vec::new(),
             global_hotkey: None,
+            ..Settings::default()
         };
         assert!(settings.validate().is_err());
     }
@@ -185,10 +836,294 @@ mod tests {
             language: "de".to_string(),
             custom_actions: Vec::new(),
             global_hotkey: None,
+            ..Settings::default()
         };
         assert!(settings.validate().is_err());
     }
 
+    #[test]
+    fn test_settings_without_templates_use_built_ins() {
+        let json = r#"{"maxDuration": 5, "apiKey": null}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+
+        assert_eq!(settings.summarization_model, DEFAULT_SUMMARIZATION_MODEL);
+        assert_eq!(settings.prompt_templates, default_prompt_templates());
+        assert_eq!(settings.default_prompt_template, DEFAULT_PROMPT_TEMPLATE_ID);
+        assert!(settings.prompt_template("meeting-minutes").is_some());
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_prompt_template_deserializes_with_model() {
+        let json = r#"{
+            "maxDuration": 5,
+            "apiKey": null,
+            "promptTemplates": [
+                {"id": "standup", "name": "Standup", "prompt": "Summarize {date}", "model": "gpt-4o"}
+            ],
+            "defaultPromptTemplate": "standup"
+        }"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+
+        let template = settings.prompt_template("standup").unwrap();
+        assert_eq!(template.model.as_deref(), Some("gpt-4o"));
+        assert!(settings.prompt_template("summary").is_none());
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_prompt_templates() {
+        let template = PromptTemplate::built_in("a", "A", "Prompt");
+
+        let duplicate = Settings {
+            prompt_templates: vec![template.clone(), template.clone()],
+            default_prompt_template: "a".to_string(),
+            ..Settings::default()
+        };
+        assert!(duplicate.validate().is_err());
+
+        let empty_prompt = Settings {
+            prompt_templates: vec![PromptTemplate {
+                prompt: " ".to_string(),
+                ..template.clone()
+            }],
+            default_prompt_template: "a".to_string(),
+            ..Settings::default()
+        };
+        assert!(empty_prompt.validate().is_err());
+
+        let missing_default = Settings {
+            prompt_templates: vec![template],
+            default_prompt_template: "summary".to_string(),
+            ..Settings::default()
+        };
+        assert!(missing_default.validate().is_err());
+    }
+
+    #[test]
+    fn test_custom_action_defaults_for_old_settings() {
+        let json = r#"{
+            "maxDuration": 5,
+            "apiKey": null,
+            "customActions": [{"id": "a", "name": "Webhook", "url": "https://example.com"}]
+        }"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        let action = &settings.custom_actions[0];
+        assert_eq!(action.method, HttpMethod::Post);
+        assert!(action.headers.is_empty());
+        assert_eq!(action.auth, CustomActionAuth::None);
+        assert!(action.body_template.is_none());
+        assert!(action.signing_secret.is_none());
+        assert_eq!(action.response_action, ResponseAction::Show);
+        assert_eq!(action.payload_fields, vec![PayloadField::Text]);
+        assert!(!action.preserve_formatting);
+    }
+
+    #[test]
+    fn test_custom_action_serialization() {
+        let json = r#"{
+            "id": "jira",
+            "name": "Jira",
+            "url": "https://jira.example.com/rest/api/2/issue",
+            "method": "PUT",
+            "headers": [{"name": "X-Team", "value": "voice"}],
+            "auth": {"type": "basic", "username": "bot", "password": "secret"},
+            "bodyTemplate": "{\"summary\": \"{title}\"}",
+            "payloadFields": ["text", "createdAt"],
+            "preserveFormatting": true,
+            "responsePointer": "/key",
+            "responseAction": "replaceSummary"
+        }"#;
+        let action: CustomAction = serde_json::from_str(json).unwrap();
+        assert_eq!(action.method, HttpMethod::Put);
+        assert_eq!(action.headers[0].name, "X-Team");
+        assert_eq!(action.auth.secret(), Some(&Some("secret".to_string())));
+        assert_eq!(
+            action.payload_fields,
+            vec![PayloadField::Text, PayloadField::CreatedAt]
+        );
+        assert!(action.preserve_formatting);
+        assert_eq!(action.response_pointer.as_deref(), Some("/key"));
+        assert_eq!(action.response_action, ResponseAction::ReplaceSummary);
+        assert!(action.response_action.updates_history());
+
+        let value = serde_json::to_value(&action).unwrap();
+        assert_eq!(value["method"], "PUT");
+        assert_eq!(value["auth"]["type"], "basic");
+        assert_eq!(value["bodyTemplate"], "{\"summary\": \"{title}\"}");
+    }
+
+    #[test]
+    fn test_validate_custom_actions() {
+        let action = CustomAction {
+            id: "a".to_string(),
+            name: "Webhook".to_string(),
+            url: "https://example.com/hook".to_string(),
+            method: HttpMethod::Post,
+            headers: vec![HttpHeader {
+                name: "X-Source".to_string(),
+                value: "EverVoice".to_string(),
+            }],
+            auth: CustomActionAuth::Bearer { token: None },
+            signing_secret: None,
+            body_template: Some(r#"{"text": "{transcription}"}"#.to_string()),
+            payload_fields: default_payload_fields(),
+            preserve_formatting: false,
+            timeout_secs: Some(10),
+            response_pointer: Some("/data/url".to_string()),
+            response_action: ResponseAction::OpenUrl,
+        };
+        let with_action = |action: CustomAction| Settings {
+            custom_actions: vec![action],
+            ..Settings::default()
+        };
+        assert!(with_action(action.clone()).validate().is_ok());
+
+        let invalid = [
+            CustomAction {
+                url: "ftp://example.com".to_string(),
+                ..action.clone()
+            },
+            CustomAction {
+                headers: vec![HttpHeader {
+                    name: "Bad Header".to_string(),
+                    value: "x".to_string(),
+                }],
+                ..action.clone()
+            },
+            CustomAction {
+                headers: vec![HttpHeader {
+                    name: "X-Injected".to_string(),
+                    value: "a\r\nHost: evil".to_string(),
+                }],
+                ..action.clone()
+            },
+            CustomAction {
+                auth: CustomActionAuth::Basic {
+                    username: "a:b".to_string(),
+                    password: None,
+                },
+                ..action.clone()
+            },
+            CustomAction {
+                method: HttpMethod::Get,
+                ..action.clone()
+            },
+            CustomAction {
+                timeout_secs: Some(0),
+                ..action.clone()
+            },
+            CustomAction {
+                response_pointer: Some("data/url".to_string()),
+                ..action.clone()
+            },
+            CustomAction {
+                timeout_secs: Some(MAX_CUSTOM_ACTION_TIMEOUT_SECS + 1),
+                ..action.clone()
+            },
+        ];
+        for action in invalid {
+            assert!(with_action(action).validate().is_err());
+        }
+
+        let duplicate = Settings {
+            custom_actions: vec![action.clone(), action],
+            ..Settings::default()
+        };
+        assert!(duplicate.validate().is_err());
+    }
+
+    #[test]
+    fn test_pipeline_serialization() {
+        let json = r#"{
+            "maxDuration": 5,
+            "apiKey": null,
+            "pipelines": [{
+                "id": "notes",
+                "name": "Meeting notes",
+                "steps": [
+                    {"type": "transcribe"},
+                    {"type": "summarize", "templateId": "meeting-minutes"},
+                    {"type": "summarize"}
+                ]
+            }],
+            "autoPipeline": "notes"
+        }"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        let pipeline = settings.pipeline("notes").unwrap();
+        assert_eq!(
+            pipeline.steps[1],
+            PipelineStep::Summarize {
+                template_id: Some("meeting-minutes".to_string())
+            }
+        );
+        assert_eq!(
+            pipeline.steps[2],
+            PipelineStep::Summarize { template_id: None }
+        );
+        assert!(settings.validate().is_ok());
+
+        let old: Settings = serde_json::from_str(r#"{"maxDuration": 5, "apiKey": null}"#).unwrap();
+        assert!(old.pipelines.is_empty());
+        assert!(old.auto_pipeline.is_none());
+    }
+
+    #[test]
+    fn test_validate_pipelines() {
+        let pipeline = |steps: Vec<PipelineStep>| Settings {
+            pipelines: vec![Pipeline {
+                id: "p".to_string(),
+                name: "Pipeline".to_string(),
+                steps,
+            }],
+            auto_pipeline: Some("p".to_string()),
+            ..Settings::default()
+        };
+        let summarize = |template: &str| PipelineStep::Summarize {
+            template_id: Some(template.to_string()),
+        };
+        let action = |id: &str| PipelineStep::CustomAction {
+            action_id: id.to_string(),
+        };
+
+        assert!(pipeline(vec![PipelineStep::Transcribe, summarize("tweet")])
+            .validate()
+            .is_ok());
+        assert!(pipeline(vec![]).validate().is_err());
+        assert!(pipeline(vec![summarize("tweet")]).validate().is_err());
+        assert!(
+            pipeline(vec![PipelineStep::Transcribe, PipelineStep::Transcribe])
+                .validate()
+                .is_err()
+        );
+        assert!(
+            pipeline(vec![PipelineStep::Transcribe, summarize("missing")])
+                .validate()
+                .is_err()
+        );
+        assert!(pipeline(vec![PipelineStep::Transcribe, action("missing")])
+            .validate()
+            .is_err());
+
+        let missing_auto = Settings {
+            auto_pipeline: Some("other".to_string()),
+            ..pipeline(vec![PipelineStep::Transcribe])
+        };
+        assert!(missing_auto.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_inbox_folder() {
+        let inbox = |folder: &str| Settings {
+            inbox_folder: Some(folder.to_string()),
+            ..Settings::default()
+        };
+        let absolute = std::env::temp_dir().join("inbox");
+        assert!(inbox(absolute.to_str().unwrap()).validate().is_ok());
+        assert!(inbox("inbox").validate().is_err());
+        assert!(inbox("").validate().is_err());
+    }
+
     #[test]
     fn test_effective_global_hotkey_with_custom() {
         let settings = Settings {
@@ -197,6 +1132,7 @@ mod tests {
             language: "de".to_string(),
             custom_actions: Vec::new(),
             global_hotkey: Some("Alt+R".to_string()),
+            ..Settings::default()
         };
         assert_eq!(settings.effective_global_hotkey(), "Alt+R");
     }
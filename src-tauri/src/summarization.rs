@@ -1,15 +1,32 @@
+use crate::settings::{PromptTemplate, Settings};
+use crate::util::format_duration;
+use chrono::{DateTime, Local};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// OpenAI Chat Completions API endpoint
 const CHAT_API_URL: &str = "https://api.openai.com/v1/chat/completions";
 
-/// The model to use for summarization
-const SUMMARIZATION_MODEL: &str = "gpt-4o-mini";
+/// Maximum length of a generated title in characters
+const MAX_TITLE_CHARS: usize = 80;
 
-/// System prompt template for summarization (language placeholder: {language})
-fn get_summarization_prompt(language: &str) -> String {
-    let language_name = match language {
+/// Estimated token budget for the text of a single request
+///
+/// Kept well below the context window of small models so the prompt and the
+/// response fit as well. Longer transcriptions are summarized map-reduce style.
+const MAX_INPUT_TOKENS: usize = 12_000;
+
+/// Maximum number of chunk summaries requested in parallel
+const MAX_CONCURRENT_CHUNKS: usize = 3;
+
+/// Maximum number of times partial summaries are summarized again before the
+/// final request is sent regardless of its size
+const MAX_REDUCE_ROUNDS: usize = 3;
+
+/// Human-readable name of a language code for use in prompts
+fn language_name(language: &str) -> &'static str {
+    match language {
         "de" => "German",
         "en" => "English",
         "es" => "Spanish",
@@ -23,10 +40,115 @@ fn get_summarization_prompt(language: &str) -> String {
         "zh" => "Chinese",
         "ko" => "Korean",
         _ => "the same language as the transcription",
-    };
+    }
+}
+
+/// Values substituted into prompt template placeholders
+#[derive(Debug, Clone)]
+pub struct PromptContext {
+    /// Language code; `{language}` is replaced by its English name
+    pub language: String,
+    /// Recording date for `{date}` (YYYY-MM-DD)
+    pub date: String,
+    /// Recording duration for `{duration}`, if known
+    pub duration_seconds: Option<f64>,
+}
+
+impl PromptContext {
+    /// Build a context from recording metadata
+    ///
+    /// `recorded_at` is an RFC 3339 timestamp; today's date is used when it is
+    /// missing or invalid.
+    pub fn new(language: &str, recorded_at: Option<&str>, duration_seconds: Option<f64>) -> Self {
+        let date = recorded_at
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|timestamp| timestamp.date_naive())
+            .unwrap_or_else(|| Local::now().date_naive());
+
+        Self {
+            language: language.to_string(),
+            date: date.format("%Y-%m-%d").to_string(),
+            duration_seconds,
+        }
+    }
+}
+
+/// Replace the `{language}`, `{date}` and `{duration}` placeholders in a prompt
+pub fn render_prompt(template: &str, context: &PromptContext) -> String {
+    let duration = context
+        .duration_seconds
+        .map(format_duration)
+        .unwrap_or_else(|| "unknown".to_string());
+
+    template
+        .replace("{language}", language_name(&context.language))
+        .replace("{date}", &context.date)
+        .replace("{duration}", &duration)
+}
+
+/// Model, language and rendered system prompt for a summarization request
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummarizationOptions {
+    pub model: String,
+    pub system_prompt: String,
+    /// Language code used for the prompts of chunk summaries
+    pub language: String,
+}
+
+impl SummarizationOptions {
+    /// Resolve a prompt template from settings
+    ///
+    /// Uses the settings' default template when `template_id` is `None`. The
+    /// template's own model wins over `Settings::summarization_model`.
+    pub fn from_settings(
+        settings: &Settings,
+        template_id: Option<&str>,
+        context: &PromptContext,
+    ) -> Result<Self, SummarizationError> {
+        let id = template_id.unwrap_or(&settings.default_prompt_template);
+        let template = settings
+            .prompt_template(id)
+            .ok_or_else(|| SummarizationError::TemplateNotFound(id.to_string()))?;
+
+        Ok(Self::from_template(
+            template,
+            &settings.summarization_model,
+            context,
+        ))
+    }
+
+    /// Render a template, falling back to `default_model` if it has no model
+    pub fn from_template(
+        template: &PromptTemplate,
+        default_model: &str,
+        context: &PromptContext,
+    ) -> Self {
+        Self {
+            model: template
+                .model
+                .clone()
+                .unwrap_or_else(|| default_model.to_string()),
+            system_prompt: render_prompt(&template.prompt, context),
+            language: context.language.clone(),
+        }
+    }
+}
+
+/// System prompt for summarizing one chunk of a long transcription
+fn get_chunk_prompt(language: &str, part: usize, total: usize) -> String {
+    format!(
+        "The following text is part {} of {} of a long transcription. Summarize this part in detail, keeping all facts, names, numbers, decisions and action items. Respond in {}.",
+        part,
+        total,
+        language_name(language)
+    )
+}
+
+/// System prompt for generating a short title for a transcription
+fn get_title_prompt(language: &str) -> String {
     format!(
-        "Summarize the following transcription into concise Markdown-formatted bullet points. Respond in {}.",
-        language_name
+        "Write a short, descriptive title (at most 8 words) for the following transcription. Respond in {} with only the title, without quotes or trailing punctuation.",
+        language_name(language)
     )
 }
 
@@ -51,6 +173,9 @@ pub enum SummarizationError {
 
     #[error("Empty transcription text")]
     EmptyText,
+
+    #[error("Prompt template not found: {0}")]
+    TemplateNotFound(String),
 }
 
 impl SummarizationError {
@@ -73,8 +198,12 @@ impl SummarizationError {
             SummarizationError::ApiError(msg) => {
                 format!("Summarization failed: {}", msg)
             }
-            SummarizationError::EmptyText => {
-                "Cannot summarize empty text.".to_string()
+            SummarizationError::EmptyText => "Cannot summarize empty text.".to_string(),
+            SummarizationError::TemplateNotFound(id) => {
+                format!(
+                    "Prompt template \"{}\" not found. Please check Settings.",
+                    id
+                )
             }
         }
     }
@@ -87,11 +216,21 @@ pub struct SummarizationResult {
     pub summary: String,
 }
 
+/// Successful title generation result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleResult {
+    /// The generated title (single line)
+    pub title: String,
+}
+
 /// OpenAI Chat Completions API request structure
 #[derive(Debug, Serialize)]
 struct ChatCompletionRequest {
     model: String,
     messages: Vec<ChatMessage>,
+    /// Request a server-sent event stream instead of a single response
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 /// Chat message structure for the API request
@@ -117,6 +256,23 @@ struct ChatResponseMessage {
     content: String,
 }
 
+/// One event of a streamed chat completion
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    choices: Vec<ChatChunkChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunkChoice {
+    delta: ChatChunkDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
 /// OpenAI API error response structure
 #[derive(Debug, Deserialize)]
 struct OpenAIErrorResponse {
@@ -139,6 +295,20 @@ pub trait HttpClient: Send + Sync {
     ) -> Result<(u16, String), String>;
 }
 
+/// Response body delivered in pieces as it arrives
+pub type ByteStream = BoxStream<'static, Result<Vec<u8>, String>>;
+
+/// HTTP client trait for streamed responses (server-sent events)
+#[async_trait::async_trait]
+pub trait StreamingHttpClient: Send + Sync {
+    async fn post_json_stream(
+        &self,
+        url: &str,
+        api_key: &str,
+        body: &str,
+    ) -> Result<(u16, ByteStream), String>;
+}
+
 /// Default HTTP client implementation using reqwest
 pub struct ReqwestHttpClient;
 
@@ -168,89 +338,552 @@ impl HttpClient for ReqwestHttpClient {
     }
 }
 
+#[async_trait::async_trait]
+impl StreamingHttpClient for ReqwestHttpClient {
+    async fn post_json_stream(
+        &self,
+        url: &str,
+        api_key: &str,
+        body: &str,
+    ) -> Result<(u16, ByteStream), String> {
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let status = response.status().as_u16();
+        let stream = response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|bytes| bytes.to_vec()).map_err(|e| e.to_string()));
+
+        Ok((status, Box::pin(stream)))
+    }
+}
+
 /// Summarize transcription text using OpenAI Chat Completions API
 ///
-/// This function sends the transcription to the OpenAI API and returns
-/// a markdown-formatted summary in the specified language.
+/// This function sends the transcription with the rendered prompt template
+/// to the OpenAI API and returns the model's Markdown response.
 pub async fn summarize_text(
     text: &str,
     api_key: &str,
-    language: &str,
+    options: &SummarizationOptions,
 ) -> Result<SummarizationResult, SummarizationError> {
     let client = ReqwestHttpClient;
-    summarize_text_with_client(text, api_key, language, &client).await
+    summarize_text_with_client(text, api_key, options, &client).await
 }
 
 /// Summarize transcription text with an injectable HTTP client (for testing)
 pub async fn summarize_text_with_client<C: HttpClient>(
     text: &str,
     api_key: &str,
-    language: &str,
+    options: &SummarizationOptions,
+    client: &C,
+) -> Result<SummarizationResult, SummarizationError> {
+    summarize_in_chunks(text, api_key, options, client, MAX_INPUT_TOKENS).await
+}
+
+/// Map-reduce summarization
+///
+/// Text within `max_tokens` is summarized with a single request. Longer text
+/// is split into chunks that are summarized individually (map); the joined
+/// partial summaries then go through the template prompt (reduce). If the
+/// partial summaries are still too long, they are chunked again.
+async fn summarize_in_chunks<C: HttpClient>(
+    text: &str,
+    api_key: &str,
+    options: &SummarizationOptions,
     client: &C,
+    max_tokens: usize,
 ) -> Result<SummarizationResult, SummarizationError> {
+    let text = reduce_to_budget(text, api_key, options, client, max_tokens).await?;
+
+    let summary = complete_chat_with_client(
+        &options.model,
+        &options.system_prompt,
+        &text,
+        api_key,
+        client,
+    )
+    .await?;
+
+    Ok(SummarizationResult { summary })
+}
+
+/// Map step of map-reduce summarization
+///
+/// Returns the text unchanged if it fits `max_tokens`, otherwise the joined
+/// summaries of its chunks (repeated up to `MAX_REDUCE_ROUNDS` times).
+async fn reduce_to_budget<C: HttpClient>(
+    text: &str,
+    api_key: &str,
+    options: &SummarizationOptions,
+    client: &C,
+    max_tokens: usize,
+) -> Result<String, SummarizationError> {
+    if text.trim().is_empty() {
+        return Err(SummarizationError::EmptyText);
+    }
+
+    let mut text = text.to_string();
+    for _ in 0..MAX_REDUCE_ROUNDS {
+        if estimate_tokens(&text) <= max_tokens {
+            break;
+        }
+
+        let chunks = split_into_chunks(&text, max_tokens);
+        let total = chunks.len();
+        // Collected first so no mapping closure is held across awaits (keeps
+        // the future `Send` for Tauri commands)
+        let requests: Vec<_> = chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let prompt = get_chunk_prompt(&options.language, index + 1, total);
+                async move {
+                    complete_chat_with_client(&options.model, &prompt, chunk, api_key, client).await
+                }
+            })
+            .collect();
+        let partial_summaries: Vec<String> = stream::iter(requests)
+            .buffered(MAX_CONCURRENT_CHUNKS)
+            .try_collect()
+            .await?;
+
+        text = partial_summaries
+            .iter()
+            .map(|summary| summary.trim())
+            .filter(|summary| !summary.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+    }
+
+    Ok(text)
+}
+
+/// Summarize transcription text, streaming the final summary as it is generated
+///
+/// `on_delta` is called with every piece of text received. Chunk summaries
+/// of long transcriptions are requested without streaming; only the final
+/// (reduce) request streams. Returns the complete summary.
+pub async fn summarize_text_streaming<F>(
+    text: &str,
+    api_key: &str,
+    options: &SummarizationOptions,
+    on_delta: F,
+) -> Result<SummarizationResult, SummarizationError>
+where
+    F: FnMut(&str) + Send,
+{
+    let client = ReqwestHttpClient;
+    summarize_text_streaming_with_client(text, api_key, options, &client, on_delta).await
+}
+
+/// Streaming summarization with an injectable HTTP client (for testing)
+pub async fn summarize_text_streaming_with_client<C, F>(
+    text: &str,
+    api_key: &str,
+    options: &SummarizationOptions,
+    client: &C,
+    on_delta: F,
+) -> Result<SummarizationResult, SummarizationError>
+where
+    C: HttpClient + StreamingHttpClient,
+    F: FnMut(&str) + Send,
+{
+    let text = reduce_to_budget(text, api_key, options, client, MAX_INPUT_TOKENS).await?;
+
+    let summary = stream_chat_with_client(
+        &options.model,
+        &options.system_prompt,
+        &text,
+        api_key,
+        client,
+        on_delta,
+    )
+    .await?;
+
+    Ok(SummarizationResult { summary })
+}
+
+/// Rough token count without a tokenizer
+///
+/// ASCII text averages about four characters per token; other scripts
+/// (accents, CJK, Cyrillic) are counted as one token per character to stay on
+/// the safe side.
+fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other): (usize, usize) = text.chars().fold((0, 0), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+/// Split text into chunks of at most `max_tokens` (estimated)
+///
+/// Chunks break after sentences where possible, otherwise between words. A
+/// single word longer than the budget becomes its own chunk.
+fn split_into_chunks(text: &str, max_tokens: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in split_sentences(text) {
+        if estimate_tokens(&current) + estimate_tokens(sentence) <= max_tokens {
+            current.push_str(sentence);
+            continue;
+        }
+        if !current.trim().is_empty() {
+            chunks.push(current.trim().to_string());
+        }
+        current.clear();
+
+        if estimate_tokens(sentence) <= max_tokens {
+            current.push_str(sentence);
+            continue;
+        }
+
+        // Sentence alone exceeds the budget: fall back to words
+        for word in sentence.split_inclusive(char::is_whitespace) {
+            if !current.is_empty() && estimate_tokens(&current) + estimate_tokens(word) > max_tokens
+            {
+                chunks.push(current.trim().to_string());
+                current.clear();
+            }
+            current.push_str(word);
+        }
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+    chunks
+}
+
+/// Split text after sentence terminators, keeping the trailing whitespace
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        let is_terminator = matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '\n');
+        if !is_terminator {
+            continue;
+        }
+        // Include following whitespace in the sentence
+        while let Some(&(_, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            chars.next();
+        }
+        let end = chars.peek().map(|&(i, _)| i).unwrap_or(text.len());
+        if end > start {
+            sentences.push(&text[start..end]);
+            start = end;
+        }
+    }
+
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+    sentences
+}
+
+/// Generate a short title for transcription text using OpenAI Chat Completions API
+pub async fn generate_title(
+    text: &str,
+    api_key: &str,
+    model: &str,
+    language: &str,
+) -> Result<TitleResult, SummarizationError> {
+    let client = ReqwestHttpClient;
+    generate_title_with_client(text, api_key, model, language, &client).await
+}
+
+/// Generate a title with an injectable HTTP client (for testing)
+pub async fn generate_title_with_client<C: HttpClient>(
+    text: &str,
+    api_key: &str,
+    model: &str,
+    language: &str,
+    client: &C,
+) -> Result<TitleResult, SummarizationError> {
+    // The beginning of a long transcription is enough for a title
+    let text = split_into_chunks(text, MAX_INPUT_TOKENS)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let response =
+        complete_chat_with_client(model, &get_title_prompt(language), &text, api_key, client)
+            .await?;
+
+    let title = clean_title(&response);
+    if title.is_empty() {
+        return Err(SummarizationError::ApiError(
+            "Empty title in response".to_string(),
+        ));
+    }
+
+    Ok(TitleResult { title })
+}
+
+/// Reduce a model response to a single-line title
+///
+/// Models occasionally wrap the title in quotes, prefix it with "Title:" or
+/// end it with a period despite the prompt.
+fn clean_title(response: &str) -> String {
+    let line = response
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let line = line.strip_prefix("Title:").unwrap_or(line);
+    let title = line
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '*' | '#' | '“' | '”'))
+        .trim_end_matches('.')
+        .trim();
+
+    match title.char_indices().nth(MAX_TITLE_CHARS) {
+        Some((end, _)) => title[..end].trim_end().to_string(),
+        None => title.to_string(),
+    }
+}
+
+/// Send a system prompt and the transcription to the Chat Completions API
+/// and return the first choice's content
+async fn complete_chat_with_client<C: HttpClient>(
+    model: &str,
+    system_prompt: &str,
+    text: &str,
+    api_key: &str,
+    client: &C,
+) -> Result<String, SummarizationError> {
     // Validate input
     if text.trim().is_empty() {
         return Err(SummarizationError::EmptyText);
     }
 
-    // Build the request with language-specific prompt
-    let system_prompt = get_summarization_prompt(language);
+    let body = chat_request_body(model, system_prompt, text, false)?;
+
+    // Make the API request
+    let (status, response_text) = client
+        .post_json(CHAT_API_URL, api_key, &body)
+        .await
+        .map_err(SummarizationError::NetworkError)?;
+
+    // Handle different HTTP status codes
+    if status != 200 {
+        return Err(error_from_response(status, response_text));
+    }
+
+    // Success - parse the response
+    let response: ChatCompletionResponse = serde_json::from_str(&response_text)
+        .map_err(|e| SummarizationError::ApiError(format!("Failed to parse response: {}", e)))?;
+
+    Ok(response
+        .choices
+        .first()
+        .map(|c| c.message.content.clone())
+        .unwrap_or_default())
+}
+
+/// Like `complete_chat_with_client`, but streams the response
+///
+/// Calls `on_delta` for every content delta and returns the full text.
+async fn stream_chat_with_client<C, F>(
+    model: &str,
+    system_prompt: &str,
+    text: &str,
+    api_key: &str,
+    client: &C,
+    mut on_delta: F,
+) -> Result<String, SummarizationError>
+where
+    C: StreamingHttpClient,
+    F: FnMut(&str) + Send,
+{
+    if text.trim().is_empty() {
+        return Err(SummarizationError::EmptyText);
+    }
+
+    let body = chat_request_body(model, system_prompt, text, true)?;
+
+    let (status, mut stream) = client
+        .post_json_stream(CHAT_API_URL, api_key, &body)
+        .await
+        .map_err(SummarizationError::NetworkError)?;
+
+    if status != 200 {
+        // Error responses are plain JSON; collect the body for the message
+        let mut body = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(bytes) => body.extend_from_slice(&bytes),
+                Err(_) => break,
+            }
+        }
+        return Err(error_from_response(
+            status,
+            String::from_utf8_lossy(&body).into_owned(),
+        ));
+    }
+
+    let mut parser = SseParser::default();
+    let mut summary = String::new();
+    let mut done = false;
+
+    while !done {
+        let events = match stream.next().await {
+            Some(chunk) => parser.push(&chunk.map_err(SummarizationError::NetworkError)?),
+            None => {
+                done = true;
+                parser.finish()
+            }
+        };
+
+        for data in events {
+            if data == "[DONE]" {
+                done = true;
+                break;
+            }
+            let delta = parse_stream_event(&data)?;
+            if !delta.is_empty() {
+                on_delta(&delta);
+                summary.push_str(&delta);
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Serialize a chat completion request with a system prompt and user text
+fn chat_request_body(
+    model: &str,
+    system_prompt: &str,
+    text: &str,
+    stream: bool,
+) -> Result<String, SummarizationError> {
     let request = ChatCompletionRequest {
-        model: SUMMARIZATION_MODEL.to_string(),
+        model: model.to_string(),
         messages: vec![
             ChatMessage {
                 role: "system".to_string(),
-                content: system_prompt,
+                content: system_prompt.to_string(),
             },
             ChatMessage {
                 role: "user".to_string(),
                 content: text.to_string(),
             },
         ],
+        stream,
     };
 
-    let body = serde_json::to_string(&request)
-        .map_err(|e| SummarizationError::ApiError(format!("Failed to serialize request: {}", e)))?;
-
-    // Make the API request
-    let (status, response_text) = client
-        .post_json(CHAT_API_URL, api_key, &body)
-        .await
-        .map_err(SummarizationError::NetworkError)?;
+    serde_json::to_string(&request)
+        .map_err(|e| SummarizationError::ApiError(format!("Failed to serialize request: {}", e)))
+}
 
-    // Handle different HTTP status codes
+/// Map a non-200 response to a summarization error
+fn error_from_response(status: u16, response_text: String) -> SummarizationError {
     match status {
-        200 => {
-            // Success - parse the response
-            let response: ChatCompletionResponse = serde_json::from_str(&response_text)
-                .map_err(|e| {
-                    SummarizationError::ApiError(format!("Failed to parse response: {}", e))
-                })?;
-
-            let summary = response
-                .choices
-                .first()
-                .map(|c| c.message.content.clone())
-                .unwrap_or_default();
-
-            Ok(SummarizationResult { summary })
-        }
-        401 => Err(SummarizationError::InvalidApiKey),
-        429 => Err(SummarizationError::RateLimitExceeded),
+        401 => SummarizationError::InvalidApiKey,
+        429 => SummarizationError::RateLimitExceeded,
         _ => {
             // Try to parse error response
             let error_msg = match serde_json::from_str::<OpenAIErrorResponse>(&response_text) {
                 Ok(err) => err.error.message,
                 Err(_) => response_text,
             };
-            Err(SummarizationError::ApiError(error_msg))
+            SummarizationError::ApiError(error_msg)
         }
     }
 }
 
+/// Extract the content delta from one streamed event
+fn parse_stream_event(data: &str) -> Result<String, SummarizationError> {
+    if let Ok(err) = serde_json::from_str::<OpenAIErrorResponse>(data) {
+        return Err(SummarizationError::ApiError(err.error.message));
+    }
+
+    let chunk: ChatCompletionChunk = serde_json::from_str(data).map_err(|e| {
+        SummarizationError::ApiError(format!("Failed to parse stream event: {}", e))
+    })?;
+
+    Ok(chunk
+        .choices
+        .into_iter()
+        .filter_map(|choice| choice.delta.content)
+        .collect())
+}
+
+/// Incremental parser for a server-sent event stream
+///
+/// Bytes may arrive split anywhere, including inside a UTF-8 character, so
+/// they are buffered until an event is complete (terminated by a blank line).
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Feed received bytes, returning the `data` of every completed event
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer
+            .extend(bytes.iter().copied().filter(|&byte| byte != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            events.extend(Self::event_data(&event));
+        }
+        events
+    }
+
+    /// Flush a final event that wasn't terminated by a blank line
+    fn finish(&mut self) -> Vec<String> {
+        let event = std::mem::take(&mut self.buffer);
+        Self::event_data(&event).into_iter().collect()
+    }
+
+    /// Join the `data:` lines of an event (comments and other fields are ignored)
+    fn event_data(event: &[u8]) -> Option<String> {
+        let event = String::from_utf8_lossy(event);
+        let data: Vec<&str> = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|value| value.strip_prefix(' ').unwrap_or(value))
+            .collect();
+
+        (!data.is_empty()).then(|| data.join("\n"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::settings::{default_prompt_templates, DEFAULT_SUMMARIZATION_MODEL};
+
+    /// Options for the built-in summary template
+    fn default_options(language: &str) -> SummarizationOptions {
+        SummarizationOptions::from_settings(
+            &Settings::default(),
+            None,
+            &PromptContext::new(language, None, None),
+        )
+        .unwrap()
+    }
 
     /// Mock HTTP client for testing
     struct MockHttpClient {
@@ -304,7 +937,9 @@ mod tests {
         let transcription = "This is a test transcription.";
         let api_key = "test-api-key";
 
-        let result = summarize_text_with_client(transcription, api_key, "en", &client).await;
+        let result =
+            summarize_text_with_client(transcription, api_key, &default_options("en"), &client)
+                .await;
 
         assert!(result.is_ok());
 
@@ -331,12 +966,19 @@ mod tests {
 
     #[tokio::test]
     async fn test_summarize_returns_markdown_summary() {
-        let mock_response = r#"{"choices":[{"message":{"content":"- Point 1\n- Point 2\n- Point 3"}}]}"#;
+        let mock_response =
+            r#"{"choices":[{"message":{"content":"- Point 1\n- Point 2\n- Point 3"}}]}"#;
         let client = MockHttpClient {
             response: Ok((200, mock_response.to_string())),
         };
 
-        let result = summarize_text_with_client("Test transcription", "api-key", "en", &client).await;
+        let result = summarize_text_with_client(
+            "Test transcription",
+            "api-key",
+            &default_options("en"),
+            &client,
+        )
+        .await;
 
         assert!(result.is_ok());
         let summary = result.unwrap().summary;
@@ -352,7 +994,8 @@ mod tests {
             response: Err("Connection refused".to_string()),
         };
 
-        let result = summarize_text_with_client("Test", "api-key", "en", &client).await;
+        let result =
+            summarize_text_with_client("Test", "api-key", &default_options("en"), &client).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(matches!(error, SummarizationError::NetworkError(_)));
@@ -360,10 +1003,14 @@ mod tests {
 
         // Test 401 Unauthorized
         let client = MockHttpClient {
-            response: Ok((401, r#"{"error":{"message":"Invalid API key"}}"#.to_string())),
+            response: Ok((
+                401,
+                r#"{"error":{"message":"Invalid API key"}}"#.to_string(),
+            )),
         };
 
-        let result = summarize_text_with_client("Test", "api-key", "en", &client).await;
+        let result =
+            summarize_text_with_client("Test", "api-key", &default_options("en"), &client).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(matches!(error, SummarizationError::InvalidApiKey));
@@ -371,10 +1018,14 @@ mod tests {
 
         // Test 429 Rate Limit
         let client = MockHttpClient {
-            response: Ok((429, r#"{"error":{"message":"Rate limit exceeded"}}"#.to_string())),
+            response: Ok((
+                429,
+                r#"{"error":{"message":"Rate limit exceeded"}}"#.to_string(),
+            )),
         };
 
-        let result = summarize_text_with_client("Test", "api-key", "en", &client).await;
+        let result =
+            summarize_text_with_client("Test", "api-key", &default_options("en"), &client).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(matches!(error, SummarizationError::RateLimitExceeded));
@@ -382,10 +1033,14 @@ mod tests {
 
         // Test 500 Server Error
         let client = MockHttpClient {
-            response: Ok((500, r#"{"error":{"message":"Internal server error"}}"#.to_string())),
+            response: Ok((
+                500,
+                r#"{"error":{"message":"Internal server error"}}"#.to_string(),
+            )),
         };
 
-        let result = summarize_text_with_client("Test", "api-key", "en", &client).await;
+        let result =
+            summarize_text_with_client("Test", "api-key", &default_options("en"), &client).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(matches!(error, SummarizationError::ApiError(_)));
@@ -416,12 +1071,475 @@ mod tests {
             response: Ok((200, "".to_string())),
         };
 
-        let result = summarize_text_with_client("", "api-key", "en", &client).await;
+        let result =
+            summarize_text_with_client("", "api-key", &default_options("en"), &client).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), SummarizationError::EmptyText));
 
-        let result = summarize_text_with_client("   ", "api-key", "en", &client).await;
+        let result =
+            summarize_text_with_client("   ", "api-key", &default_options("en"), &client).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), SummarizationError::EmptyText));
     }
+
+    #[tokio::test]
+    async fn test_generate_title_uses_title_prompt() {
+        struct CapturingMockClient {
+            captured_body: std::sync::Mutex<Option<String>>,
+        }
+
+        #[async_trait::async_trait]
+        impl HttpClient for CapturingMockClient {
+            async fn post_json(
+                &self,
+                _url: &str,
+                _api_key: &str,
+                body: &str,
+            ) -> Result<(u16, String), String> {
+                *self.captured_body.lock().unwrap() = Some(body.to_string());
+                Ok((
+                    200,
+                    r#"{"choices":[{"message":{"content":"Budget Review"}}]}"#.to_string(),
+                ))
+            }
+        }
+
+        let client = CapturingMockClient {
+            captured_body: std::sync::Mutex::new(None),
+        };
+
+        let result = generate_title_with_client(
+            "We reviewed the budget.",
+            "api-key",
+            "gpt-4o",
+            "de",
+            &client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.title, "Budget Review");
+
+        let captured_body = client.captured_body.lock().unwrap().clone().unwrap();
+        let request: serde_json::Value = serde_json::from_str(&captured_body).unwrap();
+        let prompt = request["messages"][0]["content"].as_str().unwrap();
+        assert!(prompt.contains("title"));
+        assert!(prompt.contains("German"));
+        assert_eq!(request["model"], "gpt-4o");
+        assert_eq!(request["messages"][1]["content"], "We reviewed the budget.");
+    }
+
+    #[tokio::test]
+    async fn test_generate_title_errors() {
+        let client = MockHttpClient {
+            response: Ok((
+                200,
+                r#"{"choices":[{"message":{"content":"  "}}]}"#.to_string(),
+            )),
+        };
+        let result = generate_title_with_client(
+            "Test",
+            "api-key",
+            DEFAULT_SUMMARIZATION_MODEL,
+            "en",
+            &client,
+        )
+        .await;
+        assert!(matches!(result, Err(SummarizationError::ApiError(_))));
+
+        let result =
+            generate_title_with_client("", "api-key", DEFAULT_SUMMARIZATION_MODEL, "en", &client)
+                .await;
+        assert!(matches!(result, Err(SummarizationError::EmptyText)));
+
+        let client = MockHttpClient {
+            response: Ok((401, "".to_string())),
+        };
+        let result = generate_title_with_client(
+            "Test",
+            "api-key",
+            DEFAULT_SUMMARIZATION_MODEL,
+            "en",
+            &client,
+        )
+        .await;
+        assert!(matches!(result, Err(SummarizationError::InvalidApiKey)));
+    }
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(clean_title("\"Budget Review.\""), "Budget Review");
+        assert_eq!(
+            clean_title("\nTitle: **Weekly Sync**\nMore text"),
+            "Weekly Sync"
+        );
+        assert_eq!(
+            clean_title(&"a".repeat(200)).chars().count(),
+            MAX_TITLE_CHARS
+        );
+    }
+
+    #[test]
+    fn test_render_prompt_replaces_placeholders() {
+        let context = PromptContext::new("fr", Some("2024-01-21T23:30:00-05:00"), Some(3725.0));
+        let prompt = render_prompt(
+            "Minutes for {date} ({duration}) in {language}. Keep {other}.",
+            &context,
+        );
+        assert_eq!(
+            prompt,
+            "Minutes for 2024-01-21 (1:02:05) in French. Keep {other}."
+        );
+
+        let context = PromptContext::new("xx", None, None);
+        let prompt = render_prompt("{duration} / {language}", &context);
+        assert_eq!(prompt, "unknown / the same language as the transcription");
+    }
+
+    #[test]
+    fn test_options_from_settings_select_template_and_model() {
+        let settings = Settings {
+            summarization_model: "gpt-4o".to_string(),
+            prompt_templates: vec![
+                PromptTemplate {
+                    id: "minutes".to_string(),
+                    name: "Minutes".to_string(),
+                    prompt: "Minutes of {date}".to_string(),
+                    model: None,
+                },
+                PromptTemplate {
+                    id: "tweet".to_string(),
+                    name: "Tweet".to_string(),
+                    prompt: "Tweet in {language}".to_string(),
+                    model: Some("gpt-4.1-nano".to_string()),
+                },
+            ],
+            default_prompt_template: "minutes".to_string(),
+            ..Settings::default()
+        };
+        let context = PromptContext::new("en", Some("2024-03-01T10:00:00Z"), None);
+
+        let options = SummarizationOptions::from_settings(&settings, None, &context).unwrap();
+        assert_eq!(options.model, "gpt-4o");
+        assert_eq!(options.system_prompt, "Minutes of 2024-03-01");
+
+        let options =
+            SummarizationOptions::from_settings(&settings, Some("tweet"), &context).unwrap();
+        assert_eq!(options.model, "gpt-4.1-nano");
+        assert_eq!(options.system_prompt, "Tweet in English");
+
+        let error =
+            SummarizationOptions::from_settings(&settings, Some("missing"), &context).unwrap_err();
+        assert!(matches!(error, SummarizationError::TemplateNotFound(_)));
+        assert!(error.user_message().contains("missing"));
+    }
+
+    #[test]
+    fn test_built_in_templates_render_without_placeholders() {
+        let context = PromptContext::new("de", None, Some(90.0));
+        for template in default_prompt_templates() {
+            let options = SummarizationOptions::from_template(&template, "gpt-4o-mini", &context);
+            assert!(!options.system_prompt.contains('{'), "{}", template.id);
+        }
+    }
+
+    /// Mock client that records every request and answers chunk prompts with
+    /// a numbered partial summary
+    struct MapReduceMockClient {
+        requests: std::sync::Mutex<Vec<serde_json::Value>>,
+        fail_on_request: Option<(usize, u16)>,
+    }
+
+    impl MapReduceMockClient {
+        fn new() -> Self {
+            Self {
+                requests: std::sync::Mutex::new(Vec::new()),
+                fail_on_request: None,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for MapReduceMockClient {
+        async fn post_json(
+            &self,
+            _url: &str,
+            _api_key: &str,
+            body: &str,
+        ) -> Result<(u16, String), String> {
+            let request: serde_json::Value = serde_json::from_str(body).unwrap();
+            let system_prompt = request["messages"][0]["content"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            let index = {
+                let mut requests = self.requests.lock().unwrap();
+                requests.push(request);
+                requests.len()
+            };
+
+            if let Some((fail_index, status)) = self.fail_on_request {
+                if index == fail_index {
+                    return Ok((status, "{}".to_string()));
+                }
+            }
+
+            let content = match system_prompt.strip_prefix("The following text is part ") {
+                Some(rest) => format!("Summary of part {}.", rest.split(' ').next().unwrap()),
+                None => "Final summary".to_string(),
+            };
+            let response = serde_json::json!({"choices": [{"message": {"content": content}}]});
+            Ok((200, response.to_string()))
+        }
+    }
+
+    fn long_transcript(sentences: usize) -> String {
+        (1..=sentences)
+            .map(|i| format!("This is sentence number {} of the recording.", i))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Non-ASCII characters count as one token each
+        assert_eq!(estimate_tokens("日本語"), 3);
+    }
+
+    #[test]
+    fn test_split_into_chunks_respects_budget_and_keeps_text() {
+        let text = long_transcript(50);
+        let chunks = split_into_chunks(&text, 40);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(estimate_tokens(chunk) <= 40, "{}", chunk);
+            // Chunks end at sentence boundaries
+            assert!(chunk.ends_with('.'));
+        }
+        assert_eq!(chunks.join(" "), text);
+    }
+
+    #[test]
+    fn test_split_into_chunks_splits_long_sentences_by_words() {
+        let text = "word ".repeat(100);
+        let chunks = split_into_chunks(text.trim(), 10);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| estimate_tokens(chunk) <= 10));
+        assert_eq!(chunks.join(" "), text.trim());
+    }
+
+    #[tokio::test]
+    async fn test_short_text_is_summarized_in_one_request() {
+        let client = MapReduceMockClient::new();
+        let options = default_options("en");
+
+        let result = summarize_in_chunks("Short text.", "api-key", &options, &client, 100)
+            .await
+            .unwrap();
+
+        assert_eq!(result.summary, "Final summary");
+        assert_eq!(client.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_long_text_is_summarized_map_reduce() {
+        let client = MapReduceMockClient::new();
+        let options = default_options("fr");
+        let text = long_transcript(60);
+        let expected_chunks = split_into_chunks(&text, 100).len();
+
+        let result = summarize_in_chunks(&text, "api-key", &options, &client, 100)
+            .await
+            .unwrap();
+        assert_eq!(result.summary, "Final summary");
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests.len(), expected_chunks + 1);
+
+        // Map requests carry the chunk prompt in the requested language
+        for (i, request) in requests[..expected_chunks].iter().enumerate() {
+            let prompt = request["messages"][0]["content"].as_str().unwrap();
+            assert!(prompt.contains(&format!("part {} of {}", i + 1, expected_chunks)));
+            assert!(prompt.contains("French"));
+            assert_eq!(request["model"], "gpt-4o-mini");
+        }
+
+        // The reduce request uses the template prompt over the partial summaries
+        let reduce = &requests[expected_chunks];
+        assert_eq!(reduce["messages"][0]["content"], options.system_prompt);
+        let combined = reduce["messages"][1]["content"].as_str().unwrap();
+        let expected: Vec<String> = (1..=expected_chunks)
+            .map(|i| format!("Summary of part {}.", i))
+            .collect();
+        assert_eq!(combined, expected.join("\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_map_reduce_propagates_chunk_errors() {
+        let client = MapReduceMockClient {
+            fail_on_request: Some((2, 429)),
+            ..MapReduceMockClient::new()
+        };
+        let options = default_options("en");
+
+        let result =
+            summarize_in_chunks(&long_transcript(60), "api-key", &options, &client, 100).await;
+        assert!(matches!(result, Err(SummarizationError::RateLimitExceeded)));
+    }
+
+    /// Mock client that streams a fixed response in the given byte chunks
+    struct StreamingMockClient {
+        status: u16,
+        chunks: Vec<Vec<u8>>,
+        streamed_body: std::sync::Mutex<Option<String>>,
+    }
+
+    impl StreamingMockClient {
+        fn new(status: u16, body: &str, chunk_size: usize) -> Self {
+            Self {
+                status,
+                chunks: body
+                    .as_bytes()
+                    .chunks(chunk_size)
+                    .map(<[u8]>::to_vec)
+                    .collect(),
+                streamed_body: std::sync::Mutex::new(None),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for StreamingMockClient {
+        async fn post_json(
+            &self,
+            _url: &str,
+            _api_key: &str,
+            _body: &str,
+        ) -> Result<(u16, String), String> {
+            let response = r#"{"choices":[{"message":{"content":"Partial"}}]}"#;
+            Ok((200, response.to_string()))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl StreamingHttpClient for StreamingMockClient {
+        async fn post_json_stream(
+            &self,
+            _url: &str,
+            _api_key: &str,
+            body: &str,
+        ) -> Result<(u16, ByteStream), String> {
+            *self.streamed_body.lock().unwrap() = Some(body.to_string());
+            let chunks: Vec<Result<Vec<u8>, String>> =
+                self.chunks.iter().cloned().map(Ok).collect();
+            Ok((self.status, Box::pin(stream::iter(chunks))))
+        }
+    }
+
+    fn sse_body(deltas: &[&str]) -> String {
+        let mut body = String::from(": keep-alive\n\n");
+        body.push_str(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#);
+        body.push_str("\n\n");
+        for delta in deltas {
+            let event = serde_json::json!({"choices": [{"delta": {"content": delta}}]});
+            body.push_str(&format!("data: {}\r\n\r\n", event));
+        }
+        body.push_str("data: [DONE]\n\n");
+        body
+    }
+
+    #[test]
+    fn test_sse_parser_handles_split_events() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"data: {\"a\"").is_empty());
+        assert_eq!(parser.push(b":1}\n\ndata: x\n"), vec!["{\"a\":1}"]);
+        assert_eq!(parser.push(b"\n: comment\n\n"), vec!["x"]);
+        assert_eq!(parser.push(b"data: tail"), Vec::<String>::new());
+        assert_eq!(parser.finish(), vec!["tail"]);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_summary_emits_deltas() {
+        // Chunks of 7 bytes split events and the multi-byte "ü"
+        let client = StreamingMockClient::new(200, &sse_body(&["- Pünkt", " 1\n", "- Point 2"]), 7);
+        let mut deltas = Vec::new();
+
+        let result = summarize_text_streaming_with_client(
+            "Test transcription",
+            "api-key",
+            &default_options("en"),
+            &client,
+            |delta| deltas.push(delta.to_string()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(deltas, vec!["- Pünkt", " 1\n", "- Point 2"]);
+        assert_eq!(result.summary, "- Pünkt 1\n- Point 2");
+
+        let body = client.streamed_body.lock().unwrap().clone().unwrap();
+        let request: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(request["stream"], true);
+        assert_eq!(request["messages"][1]["content"], "Test transcription");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_summary_maps_error_status() {
+        let client = StreamingMockClient::new(401, r#"{"error":{"message":"bad key"}}"#, 5);
+        let result = summarize_text_streaming_with_client(
+            "Test",
+            "api-key",
+            &default_options("en"),
+            &client,
+            |_| {},
+        )
+        .await;
+        assert!(matches!(result, Err(SummarizationError::InvalidApiKey)));
+
+        let client = StreamingMockClient::new(500, r#"{"error":{"message":"overloaded"}}"#, 5);
+        let result = summarize_text_streaming_with_client(
+            "Test",
+            "api-key",
+            &default_options("en"),
+            &client,
+            |_| {},
+        )
+        .await;
+        assert!(matches!(result, Err(SummarizationError::ApiError(msg)) if msg == "overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_summary_reports_error_events() {
+        let body = "data: {\"error\":{\"message\":\"context too long\"}}\n\n";
+        let client = StreamingMockClient::new(200, body, 64);
+        let result = summarize_text_streaming_with_client(
+            "Test",
+            "api-key",
+            &default_options("en"),
+            &client,
+            |_| {},
+        )
+        .await;
+        assert!(
+            matches!(result, Err(SummarizationError::ApiError(msg)) if msg == "context too long")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streaming_summary_rejects_empty_text() {
+        let client = StreamingMockClient::new(200, &sse_body(&["x"]), 64);
+        let result = summarize_text_streaming_with_client(
+            " ",
+            "api-key",
+            &default_options("en"),
+            &client,
+            |_| {},
+        )
+        .await;
+        assert!(matches!(result, Err(SummarizationError::EmptyText)));
+    }
 }
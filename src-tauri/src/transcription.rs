@@ -1,19 +1,55 @@
+use crate::audio_chunking::{is_webm, split_webm, AudioChunk};
+use crate::audio_decode::{self, TrimmedAudio};
+use crate::audio_format::{AudioFormat, SUPPORTED_FORMATS};
+use crate::file_storage;
+use crate::local_models;
+#[cfg(feature = "local-whisper")]
+use crate::local_whisper::LocalWhisperProvider;
+use crate::retry::{with_retry, RetryPolicy, Retryable};
+use crate::settings::{Settings, TranscriptionProviderSettings};
+use crate::vad::{self, SilenceRange};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use reqwest::multipart::{Form, Part};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use thiserror::Error;
-use tokio::time::sleep;
 
 /// OpenAI Whisper API endpoint
 const WHISPER_API_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
 
-/// Maximum retry attempts for transient failures
-const MAX_RETRY_ATTEMPTS: u32 = 3;
+/// Default transcription model for OpenAI and compatible servers
+const DEFAULT_WHISPER_MODEL: &str = "whisper-1";
 
-/// Base delay in milliseconds for exponential backoff
-const BASE_DELAY_MS: u64 = 1000;
+/// Exponential backoff for transient failures: 3 attempts, 1s then 2s apart
+const RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 3,
+    base_delay: Duration::from_millis(1000),
+    max_delay: Duration::from_secs(30),
+};
+
+/// Maximum upload size accepted by the Whisper API (25 MB)
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+/// Target size for chunks of long recordings (headroom for multipart overhead)
+const MAX_CHUNK_BYTES: usize = 24 * 1024 * 1024;
+
+/// Audio shared between consecutive chunks so no words are lost at the cut
+const CHUNK_OVERLAP_MS: u64 = 5_000;
+
+/// Maximum number of chunks transcribed concurrently
+const MAX_CONCURRENT_CHUNKS: usize = 3;
+
+/// Maximum number of words compared when de-duplicating chunk overlaps
+const MAX_OVERLAP_WORDS: usize = 60;
+
+/// Minimum run of matching words treated as a genuine overlap
+const MIN_OVERLAP_WORDS: usize = 3;
+
+/// Slack when comparing segment boundaries across chunk overlaps (seconds)
+const SEGMENT_OVERLAP_TOLERANCE_SECS: f64 = 0.25;
 
 /// Transcription error types for specific error handling
 #[derive(Error, Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +79,9 @@ pub enum TranscriptionError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    #[error("Local transcription error: {0}")]
+    LocalEngine(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -71,8 +110,8 @@ impl TranscriptionError {
             TranscriptionError::FileReadError(_) => {
                 "Failed to read recording file. Please try recording again.".to_string()
             }
-            TranscriptionError::InvalidAudioFormat(_) => {
-                "Invalid audio format. Please try recording again.".to_string()
+            TranscriptionError::InvalidAudioFormat(msg) => {
+                format!("Invalid audio format: {}", msg)
             }
             TranscriptionError::NetworkError(_) => {
                 "Transcription failed - please try again. Check your internet connection."
@@ -84,6 +123,9 @@ impl TranscriptionError {
             TranscriptionError::ApiError(msg) => {
                 format!("Transcription failed: {}", msg)
             }
+            TranscriptionError::LocalEngine(msg) => {
+                format!("Local transcription failed: {}", msg)
+            }
             TranscriptionError::Unknown(msg) => {
                 format!("An unexpected error occurred: {}", msg)
             }
@@ -91,17 +133,130 @@ impl TranscriptionError {
     }
 }
 
+impl Retryable for TranscriptionError {
+    fn is_retryable(&self) -> bool {
+        self.is_transient()
+    }
+}
+
 /// Successful transcription result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionResult {
     /// The transcribed text
     pub text: String,
+    /// Timestamped segments (if the provider returned them)
+    #[serde(default)]
+    pub segments: Option<Vec<TranscriptionSegment>>,
+    /// Silences cut out before transcription; segment times already account
+    /// for them and refer to the original recording
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_silence: Vec<SilenceRange>,
+}
+
+/// A timestamped segment of the transcription
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionSegment {
+    /// Start time in seconds from the beginning of the recording
+    pub start: f64,
+    /// End time in seconds from the beginning of the recording
+    pub end: f64,
+    /// Segment text
+    pub text: String,
+    /// Word-level timings (only when word timestamps are enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<TranscriptionWord>>,
+}
+
+impl TranscriptionSegment {
+    /// Return a copy with all timestamps moved by `offset` seconds
+    pub fn shifted(&self, offset: f64) -> Self {
+        self.retimed(|time| time + offset)
+    }
+
+    /// Return a copy with all timestamps passed through `map`
+    pub fn retimed(&self, map: impl Fn(f64) -> f64) -> Self {
+        Self {
+            start: map(self.start),
+            end: map(self.end),
+            text: self.text.clone(),
+            words: self.words.as_ref().map(|words| {
+                words
+                    .iter()
+                    .map(|w| TranscriptionWord {
+                        word: w.word.clone(),
+                        start: map(w.start),
+                        end: map(w.end),
+                    })
+                    .collect()
+            }),
+        }
+    }
 }
 
-/// OpenAI Whisper API response structure
+/// Timing of a single transcribed word
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionWord {
+    /// The word as transcribed
+    pub word: String,
+    /// Start time in seconds from the beginning of the recording
+    pub start: f64,
+    /// End time in seconds from the beginning of the recording
+    pub end: f64,
+}
+
+/// OpenAI Whisper API response structure (`json` or `verbose_json`)
 #[derive(Debug, Deserialize)]
 struct WhisperResponse {
     text: String,
+    #[serde(default)]
+    segments: Option<Vec<WhisperSegment>>,
+    #[serde(default)]
+    words: Option<Vec<TranscriptionWord>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+impl WhisperResponse {
+    /// Convert to a result, attaching each word to the segment it starts in
+    fn into_result(self) -> TranscriptionResult {
+        let mut words = self.words;
+        let segments = self.segments.map(|segments| {
+            let count = segments.len();
+            segments
+                .into_iter()
+                .enumerate()
+                .map(|(i, segment)| {
+                    let is_last = i + 1 == count;
+                    let segment_words = words.as_mut().map(|words| {
+                        let split = words
+                            .iter()
+                            .position(|w| !is_last && w.start >= segment.end)
+                            .unwrap_or(words.len());
+                        words.drain(..split).collect()
+                    });
+                    TranscriptionSegment {
+                        start: segment.start,
+                        end: segment.end,
+                        text: segment.text.trim().to_string(),
+                        words: segment_words,
+                    }
+                })
+                .collect()
+        });
+
+        TranscriptionResult {
+            text: self.text,
+            segments,
+            removed_silence: Vec::new(),
+        }
+    }
 }
 
 /// OpenAI API error response structure
@@ -119,20 +274,248 @@ struct OpenAIError {
     code: Option<String>,
 }
 
-/// Transcribe an audio file using OpenAI Whisper API
+/// Transcription backend abstraction for dependency injection and provider selection
+#[async_trait::async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    /// Transcribe a single audio payload
+    async fn transcribe(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        language: &str,
+    ) -> Result<TranscriptionResult, TranscriptionError>;
+
+    /// Whether audio is sent over the network, so it's worth compressing first
+    fn uploads_audio(&self) -> bool {
+        true
+    }
+
+    /// Whether long silences should be cut out before transcription
+    fn trims_silence(&self) -> bool {
+        false
+    }
+}
+
+/// Transcription provider for the hosted OpenAI Whisper API
+pub struct OpenAiProvider {
+    api_key: String,
+    word_timestamps: bool,
+    trim_silence: bool,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            word_timestamps: false,
+            trim_silence: false,
+        }
+    }
+
+    /// Request word-level timings in addition to segments
+    pub fn with_word_timestamps(mut self, enabled: bool) -> Self {
+        self.word_timestamps = enabled;
+        self
+    }
+
+    /// Cut long silences out of recordings before uploading them
+    pub fn with_silence_trimming(mut self, enabled: bool) -> Self {
+        self.trim_silence = enabled;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptionProvider for OpenAiProvider {
+    async fn transcribe(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        language: &str,
+    ) -> Result<TranscriptionResult, TranscriptionError> {
+        call_whisper_api(
+            WHISPER_API_URL,
+            Some(&self.api_key),
+            DEFAULT_WHISPER_MODEL,
+            self.word_timestamps,
+            file_data,
+            file_name,
+            language,
+        )
+        .await
+    }
+
+    fn trims_silence(&self) -> bool {
+        self.trim_silence
+    }
+}
+
+/// Transcription provider for any server exposing the OpenAI
+/// `/audio/transcriptions` endpoint (faster-whisper-server, LocalAI, Groq, ...)
+pub struct OpenAiCompatibleProvider {
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    word_timestamps: bool,
+    trim_silence: bool,
+}
+
+impl OpenAiCompatibleProvider {
+    /// Create a provider for the given base URL (e.g., "http://localhost:8000/v1")
+    pub fn new(base_url: &str, api_key: Option<String>, model: Option<String>) -> Self {
+        Self {
+            endpoint: transcription_endpoint(base_url),
+            api_key: api_key.filter(|k| !k.trim().is_empty()),
+            model: model
+                .filter(|m| !m.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_WHISPER_MODEL.to_string()),
+            word_timestamps: false,
+            trim_silence: false,
+        }
+    }
+
+    /// Request word-level timings in addition to segments
+    pub fn with_word_timestamps(mut self, enabled: bool) -> Self {
+        self.word_timestamps = enabled;
+        self
+    }
+
+    /// Cut long silences out of recordings before uploading them
+    pub fn with_silence_trimming(mut self, enabled: bool) -> Self {
+        self.trim_silence = enabled;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptionProvider for OpenAiCompatibleProvider {
+    async fn transcribe(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        language: &str,
+    ) -> Result<TranscriptionResult, TranscriptionError> {
+        call_whisper_api(
+            &self.endpoint,
+            self.api_key.as_deref(),
+            &self.model,
+            self.word_timestamps,
+            file_data,
+            file_name,
+            language,
+        )
+        .await
+    }
+
+    fn trims_silence(&self) -> bool {
+        self.trim_silence
+    }
+}
+
+/// Build the transcription endpoint URL from an OpenAI-compatible base URL
+fn transcription_endpoint(base_url: &str) -> String {
+    format!("{}/audio/transcriptions", base_url.trim_end_matches('/'))
+}
+
+/// Create the transcription provider selected in settings
 ///
-/// This function reads the audio file from disk, sends it to the Whisper API,
-/// and returns the transcription text. It implements exponential backoff retry
-/// for transient failures.
+/// Returns `ApiKeyNotConfigured` when the hosted OpenAI provider is selected
+/// without an API key. OpenAI-compatible servers may run without authentication.
+/// The local provider needs a build with the `local-whisper` feature and a
+/// downloaded model.
+pub fn provider_from_settings(
+    settings: &Settings,
+) -> Result<Box<dyn TranscriptionProvider>, TranscriptionError> {
+    match &settings.transcription_provider {
+        TranscriptionProviderSettings::OpenAi => match &settings.api_key {
+            Some(key) if !key.trim().is_empty() => Ok(Box::new(
+                OpenAiProvider::new(key.clone())
+                    .with_word_timestamps(settings.word_timestamps)
+                    .with_silence_trimming(settings.trim_silence),
+            )),
+            _ => Err(TranscriptionError::ApiKeyNotConfigured),
+        },
+        TranscriptionProviderSettings::OpenAiCompatible {
+            base_url,
+            api_key,
+            model,
+        } => Ok(Box::new(
+            OpenAiCompatibleProvider::new(base_url, api_key.clone(), model.clone())
+                .with_word_timestamps(settings.word_timestamps)
+                .with_silence_trimming(settings.trim_silence),
+        )),
+        TranscriptionProviderSettings::Local { model } => {
+            local_provider(model, settings.trim_silence)
+        }
+    }
+}
+
+/// Create the on-device provider for a downloaded model
+#[cfg(feature = "local-whisper")]
+fn local_provider(
+    model: &str,
+    trim_silence: bool,
+) -> Result<Box<dyn TranscriptionProvider>, TranscriptionError> {
+    let path = local_model_path(model)?;
+    Ok(Box::new(
+        LocalWhisperProvider::new(path).with_silence_trimming(trim_silence),
+    ))
+}
+
+/// Local transcription is compiled out of this build
+#[cfg(not(feature = "local-whisper"))]
+fn local_provider(
+    model: &str,
+    _trim_silence: bool,
+) -> Result<Box<dyn TranscriptionProvider>, TranscriptionError> {
+    local_model_path(model)?;
+    Err(TranscriptionError::LocalEngine(
+        "This build of EverVoice does not include local transcription".to_string(),
+    ))
+}
+
+/// Path of a downloaded local model
+fn local_model_path(model: &str) -> Result<std::path::PathBuf, TranscriptionError> {
+    let dir = file_storage::get_models_dir().map_err(TranscriptionError::LocalEngine)?;
+    let path = local_models::model_path(&dir, model).map_err(TranscriptionError::LocalEngine)?;
+    if !path.is_file() {
+        return Err(TranscriptionError::LocalEngine(format!(
+            "Model \"{}\" is not downloaded. Download it in Settings.",
+            model
+        )));
+    }
+    Ok(path)
+}
+
+/// Transcribe an audio file using the given transcription provider
+///
+/// This function reads the audio file from disk, sends it to the provider,
+/// and returns the transcription text. Recordings larger than the upload
+/// limit are split into overlapping chunks that are transcribed concurrently
+/// and stitched back together. Each request implements exponential backoff
+/// retry for transient failures.
 ///
 /// # Arguments
 /// * `file_path` - Path to the audio file
-/// * `api_key` - OpenAI API key
 /// * `language` - ISO 639-1 language code (e.g., "de", "en")
+/// * `provider` - The transcription backend to use
 pub async fn transcribe_audio_file(
     file_path: &str,
-    api_key: &str,
     language: &str,
+    provider: &dyn TranscriptionProvider,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    transcribe_audio_file_with_progress(file_path, language, provider, &|_, _| {}).await
+}
+
+/// Like `transcribe_audio_file`, reporting `(completed, total)` requests
+///
+/// `progress` is called after each finished request: once for a small file,
+/// once per chunk for split recordings.
+pub async fn transcribe_audio_file_with_progress(
+    file_path: &str,
+    language: &str,
+    provider: &dyn TranscriptionProvider,
+    progress: &(dyn Fn(usize, usize) + Send + Sync),
 ) -> Result<TranscriptionResult, TranscriptionError> {
     // Read the audio file
     let path = Path::new(file_path);
@@ -142,100 +525,409 @@ pub async fn transcribe_audio_file(
 
     let file_data = fs::read(path).map_err(|e| TranscriptionError::FileReadError(e.to_string()))?;
 
-    // Extract filename for the multipart request
-    let file_name = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("recording.webm")
-        .to_string();
-
-    // Attempt transcription with retry logic
-    let mut last_error = TranscriptionError::Unknown("No attempts made".to_string());
-
-    for attempt in 0..MAX_RETRY_ATTEMPTS {
-        match call_whisper_api(&file_data, &file_name, api_key, language).await {
-            Ok(result) => return Ok(result),
-            Err(e) => {
-                last_error = e.clone();
-
-                // Only retry on transient failures
-                if !e.is_transient() {
-                    return Err(e);
-                }
+    let format = AudioFormat::detect(&file_data).ok_or_else(|| {
+        TranscriptionError::InvalidAudioFormat(format!(
+            "unsupported file type, expected {}",
+            SUPPORTED_FORMATS
+        ))
+    })?;
+
+    // Silence costs transcription minutes and makes Whisper invent text
+    let (file_data, format, removed_silence) = if provider.trims_silence() {
+        trim_for_transcription(file_data, format, !provider.uploads_audio()).await
+    } else {
+        (file_data, format, Vec::new())
+    };
+
+    // Older recordings and imported files may not be compressed yet
+    let (file_data, format) = if provider.uploads_audio() && format != AudioFormat::Ogg {
+        compress_for_upload(file_data, format).await
+    } else {
+        (file_data, format)
+    };
+
+    // Providers guess the format from the extension, so it must match the content
+    let file_name = upload_file_name(path, format);
+
+    let result = if file_data.len() <= MAX_UPLOAD_BYTES {
+        let result = transcribe_with_retry(provider, &file_data, &file_name, language).await?;
+        progress(1, 1);
+        result
+    } else {
+        transcribe_in_chunks(
+            provider,
+            &file_data,
+            &file_name,
+            language,
+            MAX_CHUNK_BYTES,
+            progress,
+        )
+        .await?
+    };
+
+    Ok(restore_original_timing(result, removed_silence))
+}
+
+/// Cut long silences out of audio before transcription (see `vad`)
+///
+/// Returns the audio to transcribe, as Opus/OGG or (`lossless`, or builds
+/// without the `transcode` feature) WAV, with the removed ranges. Audio that
+/// can't be decoded or has nothing to remove is returned unchanged, as is
+/// audio that would only exceed the upload limit once trimmed (WAV can be
+/// larger than the original, and only WebM can be split into chunks).
+async fn trim_for_transcription(
+    file_data: Vec<u8>,
+    format: AudioFormat,
+    lossless: bool,
+) -> (Vec<u8>, AudioFormat, Vec<SilenceRange>) {
+    let (file_data, trimmed) =
+        process_audio(file_data, move |data| trim_silence(data, format, lossless)).await;
+
+    let fits = |len: usize| len <= MAX_UPLOAD_BYTES;
+    match trimmed {
+        Some((trimmed, trimmed_format, removed))
+            if fits(trimmed.len()) || (!fits(file_data.len()) && format != AudioFormat::WebM) =>
+        {
+            log::info!(
+                "Removed {} silences, {:.1} s in total, before transcription",
+                removed.len(),
+                removed.iter().map(|r| r.end - r.start).sum::<f64>()
+            );
+            (trimmed, trimmed_format, removed)
+        }
+        _ => (file_data, format, Vec::new()),
+    }
+}
+
+/// Decode audio, cut out silences and re-encode it
+fn trim_silence(data: &[u8], format: AudioFormat, lossless: bool) -> Option<TrimmedAudio> {
+    audio_decode::trim_silence(data, Some(format.extension()), lossless).unwrap_or_else(|e| {
+        log::warn!("Transcribing without trimming silence: {}", e);
+        None
+    })
+}
+
+/// Map segment and word times of audio with silences cut out back to the
+/// original recording
+fn restore_original_timing(
+    result: TranscriptionResult,
+    removed_silence: Vec<SilenceRange>,
+) -> TranscriptionResult {
+    if removed_silence.is_empty() {
+        return result;
+    }
+
+    let to_original = |time: f64| vad::to_original_time(&removed_silence, time);
+    let segments = result.segments.map(|segments| {
+        segments
+            .iter()
+            .map(|segment| segment.retimed(to_original))
+            .collect()
+    });
+    TranscriptionResult {
+        segments,
+        removed_silence,
+        ..result
+    }
+}
+
+/// Run CPU-heavy audio processing off the async runtime
+///
+/// The data is handed back along with the result, which is `None` if
+/// processing panicked.
+async fn process_audio<T: Send + 'static>(
+    file_data: Vec<u8>,
+    process: impl FnOnce(&[u8]) -> Option<T> + Send + 'static,
+) -> (Vec<u8>, Option<T>) {
+    let file_data = std::sync::Arc::new(file_data);
+    let data = file_data.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || process(&data))
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Audio processing failed: {}", e);
+            None
+        });
+    let file_data = std::sync::Arc::try_unwrap(file_data).unwrap_or_else(|data| (*data).clone());
+    (file_data, result)
+}
+
+/// Compress audio to mono Opus/OGG before uploading it, if that helps
+///
+/// Oversized WebM recordings are kept when compressing doesn't bring them
+/// under the upload limit, as only WebM can be split into chunks.
+async fn compress_for_upload(file_data: Vec<u8>, format: AudioFormat) -> (Vec<u8>, AudioFormat) {
+    let (file_data, compressed) = process_audio(file_data, file_storage::compress_audio).await;
+
+    match compressed {
+        Some(compressed) if compressed.len() <= MAX_UPLOAD_BYTES || format != AudioFormat::WebM => {
+            log::info!(
+                "Compressed audio for upload from {} to {} bytes",
+                file_data.len(),
+                compressed.len()
+            );
+            (compressed, AudioFormat::Ogg)
+        }
+        _ => (file_data, format),
+    }
+}
+
+/// Split an oversized recording, transcribe the chunks and stitch the results
+async fn transcribe_in_chunks(
+    provider: &dyn TranscriptionProvider,
+    file_data: &[u8],
+    file_name: &str,
+    language: &str,
+    max_chunk_bytes: usize,
+    progress: &(dyn Fn(usize, usize) + Send + Sync),
+) -> Result<TranscriptionResult, TranscriptionError> {
+    if !is_webm(file_data) {
+        return Err(TranscriptionError::InvalidAudioFormat(format!(
+            "Recording exceeds the {} MB upload limit and cannot be split",
+            MAX_UPLOAD_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let chunks = split_webm(file_data, max_chunk_bytes, CHUNK_OVERLAP_MS)
+        .map_err(TranscriptionError::InvalidAudioFormat)?;
+
+    log::info!(
+        "Transcribing {} ({} bytes) in {} chunks",
+        file_name,
+        file_data.len(),
+        chunks.len()
+    );
+
+    // Futures are collected before buffering: a mapping closure kept inside
+    // the stream would make the command's future not `Send`
+    let completed = AtomicUsize::new(0);
+    let total = chunks.len();
+    let requests: Vec<_> = chunks
+        .iter()
+        .map(|chunk: &AudioChunk| {
+            let chunk_name = chunk_file_name(file_name, chunk.index);
+            let completed = &completed;
+            async move {
+                let result =
+                    transcribe_with_retry(provider, &chunk.data, &chunk_name, language).await?;
+                progress(completed.fetch_add(1, Ordering::SeqCst) + 1, total);
+                Ok(result)
+            }
+        })
+        .collect();
+
+    // Results are yielded in chunk order; the first error aborts the rest
+    let results: Vec<TranscriptionResult> = stream::iter(requests)
+        .buffered(MAX_CONCURRENT_CHUNKS)
+        .try_collect()
+        .await?;
+
+    let texts: Vec<String> = results.iter().map(|r| r.text.clone()).collect();
+    let offsets: Vec<f64> = chunks.iter().map(|c| c.start_ms as f64 / 1000.0).collect();
+
+    Ok(TranscriptionResult {
+        text: stitch_transcripts(&texts),
+        segments: merge_chunk_segments(&results, &offsets),
+        removed_silence: Vec::new(),
+    })
+}
+
+/// Shift chunk segments to recording time and drop segments repeated in overlaps
+///
+/// A segment from a later chunk is kept only if it starts after the last kept
+/// segment ended. Returns `None` if any chunk came back without segments.
+fn merge_chunk_segments(
+    results: &[TranscriptionResult],
+    offsets: &[f64],
+) -> Option<Vec<TranscriptionSegment>> {
+    let mut merged: Vec<TranscriptionSegment> = Vec::new();
+
+    for (result, &offset) in results.iter().zip(offsets) {
+        for segment in result.segments.as_ref()? {
+            let shifted = segment.shifted(offset);
+            let covered_until = merged.last().map_or(f64::MIN, |s| s.end);
+            if shifted.start >= covered_until - SEGMENT_OVERLAP_TOLERANCE_SECS {
+                merged.push(shifted);
+            }
+        }
+    }
+
+    Some(merged)
+}
+
+/// Build the upload file name from the file's stem and its detected format
+/// (e.g., "interview.m4a" for an M4A file named "interview.mp4")
+fn upload_file_name(path: &Path, format: AudioFormat) -> String {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("recording");
+    format!("{}.{}", stem, format.extension())
+}
+
+/// Build the upload file name for a chunk (e.g., "recording-part2.webm")
+fn chunk_file_name(file_name: &str, index: usize) -> String {
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("recording");
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("webm");
+    format!("{}-part{}.{}", stem, index + 1, extension)
+}
+
+/// Send audio to the provider, retrying transient failures with exponential backoff
+async fn transcribe_with_retry(
+    provider: &dyn TranscriptionProvider,
+    file_data: &[u8],
+    file_name: &str,
+    language: &str,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    with_retry(&RETRY_POLICY, "Transcription", || {
+        provider.transcribe(file_data, file_name, language)
+    })
+    .await
+}
+
+/// Join chunk transcripts, removing text repeated in the overlapping audio
+///
+/// The tail of the text so far is compared with the head of the next chunk;
+/// the longest run of matching words (ignoring case and punctuation) is kept
+/// once. Chunks without a sufficiently long match are simply appended.
+pub fn stitch_transcripts(parts: &[String]) -> String {
+    let mut words: Vec<&str> = Vec::new();
+
+    for part in parts {
+        let next: Vec<&str> = part.split_whitespace().collect();
+        let tail_start = words.len().saturating_sub(MAX_OVERLAP_WORDS);
+        let head_len = next.len().min(MAX_OVERLAP_WORDS);
+
+        match longest_common_run(&words[tail_start..], &next[..head_len]) {
+            Some((tail_end, head_end, len)) if len >= MIN_OVERLAP_WORDS => {
+                words.truncate(tail_start + tail_end);
+                words.extend_from_slice(&next[head_end..]);
+            }
+            _ => words.extend_from_slice(&next),
+        }
+    }
 
-                // Don't sleep after the last attempt
-                if attempt < MAX_RETRY_ATTEMPTS - 1 {
-                    let delay = Duration::from_millis(BASE_DELAY_MS * 2u64.pow(attempt));
-                    log::info!(
-                        "Transcription attempt {} failed, retrying in {:?}: {}",
-                        attempt + 1,
-                        delay,
-                        e
-                    );
-                    sleep(delay).await;
+    words.join(" ")
+}
+
+/// Find the longest run of equal words in `a` and `b`
+///
+/// Returns the exclusive end index in `a`, the exclusive end index in `b`
+/// and the run length.
+fn longest_common_run(a: &[&str], b: &[&str]) -> Option<(usize, usize, usize)> {
+    let a: Vec<String> = a.iter().map(|w| normalize_word(w)).collect();
+    let b: Vec<String> = b.iter().map(|w| normalize_word(w)).collect();
+
+    let mut best: Option<(usize, usize, usize)> = None;
+    let mut previous = vec![0usize; b.len() + 1];
+
+    for i in 1..=a.len() {
+        let mut current = vec![0usize; b.len() + 1];
+        for j in 1..=b.len() {
+            if !a[i - 1].is_empty() && a[i - 1] == b[j - 1] {
+                current[j] = previous[j - 1] + 1;
+                // Prefer longer runs, then runs closest to the end of `a`
+                let better = match best {
+                    None => true,
+                    Some((best_i, _, len)) => current[j] > len || (current[j] == len && i > best_i),
+                };
+                if better {
+                    best = Some((i, j, current[j]));
                 }
             }
         }
+        previous = current;
     }
 
-    Err(last_error)
+    best
 }
 
-/// Make a single call to the Whisper API
+/// Lowercase a word and strip punctuation for overlap comparison
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Make a single call to a Whisper-compatible transcription endpoint
+///
+/// Requests `verbose_json` so segment timestamps (and optionally word
+/// timestamps) are returned alongside the text.
 async fn call_whisper_api(
+    endpoint: &str,
+    api_key: Option<&str>,
+    model: &str,
+    word_timestamps: bool,
     file_data: &[u8],
     file_name: &str,
-    api_key: &str,
     language: &str,
 ) -> Result<TranscriptionResult, TranscriptionError> {
     let client = reqwest::Client::new();
 
     // Build multipart form
+    let mime_type =
+        AudioFormat::detect(file_data).map_or("application/octet-stream", |f| f.mime_type());
     let file_part = Part::bytes(file_data.to_vec())
         .file_name(file_name.to_string())
-        .mime_str("audio/webm")
+        .mime_str(mime_type)
         .map_err(|e| TranscriptionError::Unknown(e.to_string()))?;
 
-    let form = Form::new()
+    let mut form = Form::new()
         .part("file", file_part)
-        .text("model", "whisper-1")
-        .text("language", language.to_string());
+        .text("model", model.to_string())
+        .text("language", language.to_string())
+        .text("response_format", "verbose_json")
+        .text("timestamp_granularities[]", "segment");
+    if word_timestamps {
+        form = form.text("timestamp_granularities[]", "word");
+    }
 
     // Make the API request
-    let response = client
-        .post(WHISPER_API_URL)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .multipart(form)
-        .send()
+    let mut request = client.post(endpoint).multipart(form);
+    if let Some(key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", key));
+    }
+
+    let response = request.send().await.map_err(|e| {
+        if e.is_connect() || e.is_timeout() {
+            TranscriptionError::NetworkError(e.to_string())
+        } else {
+            TranscriptionError::Unknown(e.to_string())
+        }
+    })?;
+
+    let status = response.status().as_u16();
+    let body = response
+        .text()
         .await
-        .map_err(|e| {
-            if e.is_connect() || e.is_timeout() {
-                TranscriptionError::NetworkError(e.to_string())
-            } else {
-                TranscriptionError::Unknown(e.to_string())
-            }
-        })?;
+        .unwrap_or_else(|_| "Unknown error".to_string());
 
-    let status = response.status();
+    parse_whisper_response(status, &body)
+}
 
-    // Handle different HTTP status codes
-    match status.as_u16() {
+/// Map a Whisper API HTTP response to a transcription result or error
+fn parse_whisper_response(
+    status: u16,
+    body: &str,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    match status {
         200 => {
             // Success - parse the response
-            let whisper_response: WhisperResponse = response.json().await.map_err(|e| {
+            let whisper_response: WhisperResponse = serde_json::from_str(body).map_err(|e| {
                 TranscriptionError::ApiError(format!("Failed to parse response: {}", e))
             })?;
 
-            Ok(TranscriptionResult {
-                text: whisper_response.text,
-            })
+            Ok(whisper_response.into_result())
         }
         401 => Err(TranscriptionError::InvalidApiKey),
         429 => Err(TranscriptionError::RateLimitExceeded),
         400 => {
             // Bad request - likely invalid audio
-            let error_response: Result<OpenAIErrorResponse, _> = response.json().await;
-            match error_response {
+            match serde_json::from_str::<OpenAIErrorResponse>(body) {
                 Ok(err) => {
                     let message = err.error.message;
                     if message.contains("audio") || message.contains("format") {
@@ -249,26 +941,16 @@ async fn call_whisper_api(
                 )),
             }
         }
-        _ => {
-            // Other errors
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            // Check if it's a server error (5xx) - these are transient
-            if status.is_server_error() {
-                Err(TranscriptionError::NetworkError(error_text))
-            } else {
-                Err(TranscriptionError::ApiError(error_text))
-            }
-        }
+        // Server errors (5xx) are transient
+        500..=599 => Err(TranscriptionError::NetworkError(body.to_string())),
+        _ => Err(TranscriptionError::ApiError(body.to_string())),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures;
 
     #[test]
     fn test_transient_error_detection() {
@@ -297,6 +979,8 @@ mod tests {
     fn test_transcription_result_serialization() {
         let result = TranscriptionResult {
             text: "Hello, world!".to_string(),
+            segments: None,
+            removed_silence: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -313,4 +997,594 @@ mod tests {
         assert!(json.contains("NetworkError"));
         assert!(json.contains("Connection timeout"));
     }
+
+    /// Mock provider returning queued responses in order
+    struct MockProvider {
+        responses: std::sync::Mutex<Vec<Result<TranscriptionResult, TranscriptionError>>>,
+        calls: std::sync::Mutex<Vec<(usize, String, String)>>,
+        uploads_audio: bool,
+        trims_silence: bool,
+    }
+
+    impl MockProvider {
+        fn new(mut responses: Vec<Result<TranscriptionResult, TranscriptionError>>) -> Self {
+            responses.reverse();
+            Self {
+                responses: std::sync::Mutex::new(responses),
+                calls: std::sync::Mutex::new(Vec::new()),
+                uploads_audio: true,
+                trims_silence: false,
+            }
+        }
+
+        /// Like `new`, for a provider that transcribes on the device
+        fn local(responses: Vec<Result<TranscriptionResult, TranscriptionError>>) -> Self {
+            Self {
+                uploads_audio: false,
+                ..Self::new(responses)
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TranscriptionProvider for MockProvider {
+        async fn transcribe(
+            &self,
+            file_data: &[u8],
+            file_name: &str,
+            language: &str,
+        ) -> Result<TranscriptionResult, TranscriptionError> {
+            self.calls.lock().unwrap().push((
+                file_data.len(),
+                file_name.to_string(),
+                language.to_string(),
+            ));
+            self.responses
+                .lock()
+                .unwrap()
+                .pop()
+                .unwrap_or_else(|| Err(TranscriptionError::Unknown("no response".to_string())))
+        }
+
+        fn uploads_audio(&self) -> bool {
+            self.uploads_audio
+        }
+
+        fn trims_silence(&self) -> bool {
+            self.trims_silence
+        }
+    }
+
+    /// Audio data that passes format detection (EBML magic bytes)
+    const FAKE_WEBM: &[u8] = b"\x1A\x45\xDF\xA3fake audio";
+
+    fn write_temp_audio(data: &[u8]) -> String {
+        let path =
+            std::env::temp_dir().join(format!("evervoice-test-{}.webm", uuid::Uuid::new_v4()));
+        fs::write(&path, data).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_transcription_endpoint_trims_trailing_slash() {
+        assert_eq!(
+            transcription_endpoint("http://localhost:8000/v1/"),
+            "http://localhost:8000/v1/audio/transcriptions"
+        );
+        assert_eq!(
+            transcription_endpoint("https://api.groq.com/openai/v1"),
+            "https://api.groq.com/openai/v1/audio/transcriptions"
+        );
+    }
+
+    #[test]
+    fn test_parse_whisper_response_status_codes() {
+        let result = parse_whisper_response(200, r#"{"text":"Hallo Welt"}"#).unwrap();
+        assert_eq!(result.text, "Hallo Welt");
+
+        assert!(matches!(
+            parse_whisper_response(401, ""),
+            Err(TranscriptionError::InvalidApiKey)
+        ));
+        assert!(matches!(
+            parse_whisper_response(429, ""),
+            Err(TranscriptionError::RateLimitExceeded)
+        ));
+        assert!(matches!(
+            parse_whisper_response(400, r#"{"error":{"message":"Invalid file format"}}"#),
+            Err(TranscriptionError::InvalidAudioFormat(_))
+        ));
+        assert!(matches!(
+            parse_whisper_response(503, "Service Unavailable"),
+            Err(TranscriptionError::NetworkError(_))
+        ));
+        assert!(matches!(
+            parse_whisper_response(404, "Not Found"),
+            Err(TranscriptionError::ApiError(_))
+        ));
+    }
+
+    #[test]
+    fn test_provider_from_settings_requires_openai_api_key() {
+        let settings = Settings::default();
+        assert!(matches!(
+            provider_from_settings(&settings),
+            Err(TranscriptionError::ApiKeyNotConfigured)
+        ));
+
+        let settings = Settings {
+            api_key: Some("sk-test".to_string()),
+            ..Settings::default()
+        };
+        assert!(provider_from_settings(&settings).is_ok());
+    }
+
+    #[test]
+    fn test_provider_from_settings_openai_compatible_without_key() {
+        let settings = Settings {
+            transcription_provider: TranscriptionProviderSettings::OpenAiCompatible {
+                base_url: "http://localhost:8000/v1".to_string(),
+                api_key: None,
+                model: None,
+            },
+            ..Settings::default()
+        };
+        assert!(provider_from_settings(&settings).is_ok());
+    }
+
+    #[test]
+    fn test_provider_from_settings_trims_silence_as_configured() {
+        let settings = Settings {
+            api_key: Some("sk-test".to_string()),
+            ..Settings::default()
+        };
+        assert!(provider_from_settings(&settings).unwrap().trims_silence());
+
+        let settings = Settings {
+            trim_silence: false,
+            ..settings
+        };
+        assert!(!provider_from_settings(&settings).unwrap().trims_silence());
+    }
+
+    #[test]
+    fn test_provider_from_settings_local_unknown_model() {
+        let settings = Settings {
+            transcription_provider: TranscriptionProviderSettings::Local {
+                model: "no-such-model".to_string(),
+            },
+            ..Settings::default()
+        };
+        assert!(matches!(
+            provider_from_settings(&settings),
+            Err(TranscriptionError::LocalEngine(msg)) if msg.contains("no-such-model")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_passes_file_to_provider() {
+        let path = write_temp_audio(FAKE_WEBM);
+        let provider = MockProvider::new(vec![Ok(TranscriptionResult {
+            text: "Hello".to_string(),
+            segments: None,
+            removed_silence: Vec::new(),
+        })]);
+
+        let result = transcribe_audio_file(&path, "en", &provider).await.unwrap();
+        assert_eq!(result.text, "Hello");
+
+        let calls = provider.calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].0, FAKE_WEBM.len());
+        assert!(calls[0].1.ends_with(".webm"));
+        assert_eq!(calls[0].2, "en");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_names_upload_after_detected_format() {
+        for (name, extension) in [
+            ("silence.mp3", "mp3"),
+            ("silence.m4a", "m4a"),
+            ("silence.flac", "flac"),
+        ] {
+            // Imported files may carry a misleading extension
+            let path = write_temp_audio(&fixtures::audio(name));
+            let provider = MockProvider::local(vec![Ok(TranscriptionResult {
+                text: String::new(),
+                segments: None,
+                removed_silence: Vec::new(),
+            })]);
+
+            transcribe_audio_file(&path, "en", &provider).await.unwrap();
+            let calls = provider.calls.lock().unwrap();
+            let stem = Path::new(&path).file_stem().unwrap().to_str().unwrap();
+            assert_eq!(calls[0].1, format!("{}.{}", stem, extension));
+
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_compresses_uploads() {
+        let data = fixtures::audio("silence.m4a");
+        let path = write_temp_audio(&data);
+        let provider = MockProvider::new(vec![Ok(TranscriptionResult {
+            text: String::new(),
+            segments: None,
+            removed_silence: Vec::new(),
+        })]);
+
+        // Builds with the `transcode` feature upload Opus/OGG if smaller
+        let compressed = file_storage::compress_audio(&data);
+        transcribe_audio_file(&path, "en", &provider).await.unwrap();
+        let calls = provider.calls.lock().unwrap();
+        match compressed {
+            Some(compressed) => {
+                assert_eq!(calls[0].0, compressed.len());
+                assert!(calls[0].1.ends_with(".ogg"));
+            }
+            None => {
+                assert_eq!(calls[0].0, data.len());
+                assert!(calls[0].1.ends_with(".m4a"));
+            }
+        }
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_trims_silence() {
+        let samples = [
+            vec![0.0; 48_000],
+            fixtures::tone(16_000, 2.0, 300.0, 0.3),
+            vec![0.0; 48_000],
+        ]
+        .concat();
+        let data = audio_decode::wav_file(&samples, 16_000);
+        let path = write_temp_audio(&data);
+        let provider = MockProvider {
+            trims_silence: true,
+            ..MockProvider::new(vec![Ok(TranscriptionResult {
+                text: "Hello".to_string(),
+                segments: None,
+                removed_silence: Vec::new(),
+            })])
+        };
+
+        // Trimming only needs libopus to compress the upload
+        let result = transcribe_audio_file(&path, "en", &provider).await.unwrap();
+        assert_eq!(result.removed_silence.len(), 2);
+        let calls = provider.calls.lock().unwrap();
+        assert!(calls[0].0 < data.len());
+        let extension = if cfg!(feature = "transcode") {
+            ".ogg"
+        } else {
+            ".wav"
+        };
+        assert!(calls[0].1.ends_with(extension), "{}", calls[0].1);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_rejects_unsupported_format() {
+        let path = write_temp_audio(b"%PDF-1.7 not audio");
+        let provider = MockProvider::new(vec![]);
+
+        let result = transcribe_audio_file(&path, "en", &provider).await;
+        assert!(matches!(
+            result,
+            Err(TranscriptionError::InvalidAudioFormat(msg)) if msg.contains("MP3")
+        ));
+        assert!(provider.calls.lock().unwrap().is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_missing_file() {
+        let provider = MockProvider::new(vec![]);
+        let result = transcribe_audio_file("/nonexistent/recording.webm", "en", &provider).await;
+        assert!(matches!(result, Err(TranscriptionError::FileNotFound(_))));
+        assert!(provider.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_transcribe_audio_file_retries_transient_errors() {
+        let path = write_temp_audio(FAKE_WEBM);
+        let provider = MockProvider::new(vec![
+            Err(TranscriptionError::NetworkError("timeout".to_string())),
+            Err(TranscriptionError::RateLimitExceeded),
+            Ok(TranscriptionResult {
+                text: "Third time lucky".to_string(),
+                segments: None,
+                removed_silence: Vec::new(),
+            }),
+        ]);
+
+        let result = transcribe_audio_file(&path, "de", &provider).await.unwrap();
+        assert_eq!(result.text, "Third time lucky");
+        assert_eq!(provider.calls.lock().unwrap().len(), 3);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_file_does_not_retry_permanent_errors() {
+        let path = write_temp_audio(FAKE_WEBM);
+        let provider = MockProvider::new(vec![Err(TranscriptionError::InvalidApiKey)]);
+
+        let result = transcribe_audio_file(&path, "de", &provider).await;
+        assert!(matches!(result, Err(TranscriptionError::InvalidApiKey)));
+        assert_eq!(provider.calls.lock().unwrap().len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_stitch_transcripts_removes_overlap() {
+        let parts = vec![
+            "The meeting started at nine. We discussed the budget for next".to_string(),
+            "discussed the budget for next year and agreed on the plan.".to_string(),
+        ];
+        assert_eq!(
+            stitch_transcripts(&parts),
+            "The meeting started at nine. We discussed the budget for next year and agreed on the plan."
+        );
+    }
+
+    #[test]
+    fn test_stitch_transcripts_ignores_case_and_punctuation() {
+        let parts = vec![
+            "Then we went to the Store, bought".to_string(),
+            "we went to the store. Bought some apples".to_string(),
+        ];
+        assert_eq!(
+            stitch_transcripts(&parts),
+            "Then we went to the Store, bought some apples"
+        );
+    }
+
+    #[test]
+    fn test_stitch_transcripts_appends_without_overlap() {
+        let parts = vec![
+            "First part of the recording.".to_string(),
+            "Completely different words here.".to_string(),
+        ];
+        assert_eq!(
+            stitch_transcripts(&parts),
+            "First part of the recording. Completely different words here."
+        );
+
+        // A single shared word is not treated as overlap
+        let parts = vec!["We said yes".to_string(), "yes is good".to_string()];
+        assert_eq!(stitch_transcripts(&parts), "We said yes yes is good");
+    }
+
+    #[test]
+    fn test_chunk_file_name() {
+        assert_eq!(
+            chunk_file_name("recording-2024-01-21.webm", 0),
+            "recording-2024-01-21-part1.webm"
+        );
+        assert_eq!(chunk_file_name("memo.webm", 2), "memo-part3.webm");
+    }
+
+    /// Provider that echoes which chunk it received
+    struct ChunkEchoProvider {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TranscriptionProvider for ChunkEchoProvider {
+        async fn transcribe(
+            &self,
+            file_data: &[u8],
+            file_name: &str,
+            _language: &str,
+        ) -> Result<TranscriptionResult, TranscriptionError> {
+            assert!(crate::audio_chunking::is_webm(file_data));
+            self.calls.lock().unwrap().push(file_name.to_string());
+            let part = file_name
+                .trim_end_matches(".webm")
+                .rsplit("part")
+                .next()
+                .unwrap();
+            Ok(TranscriptionResult {
+                text: format!("shared overlap words {} shared overlap words", part),
+                segments: None,
+                removed_silence: Vec::new(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_in_chunks_stitches_in_order() {
+        let timecodes: Vec<u64> = (0..12).map(|i| i * 5_000).collect();
+        let data = crate::audio_chunking::build_test_webm(&timecodes, 1_000);
+        let provider = ChunkEchoProvider {
+            calls: std::sync::Mutex::new(Vec::new()),
+        };
+
+        let reported = std::sync::Mutex::new(Vec::new());
+        let progress = |done, total| reported.lock().unwrap().push((done, total));
+
+        let result = transcribe_in_chunks(&provider, &data, "memo.webm", "en", 4_500, &progress)
+            .await
+            .unwrap();
+
+        let calls = provider.calls.lock().unwrap();
+        assert!(calls.len() > 1);
+
+        // Progress is reported once per chunk, ending at total/total
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), calls.len());
+        assert_eq!(reported.last(), Some(&(calls.len(), calls.len())));
+        assert_eq!(calls[0], "memo-part1.webm");
+
+        let mut expected = vec!["shared overlap words".to_string()];
+        for i in 1..=calls.len() {
+            expected.push(format!("{} shared overlap words", i));
+        }
+        assert_eq!(result.text, expected.join(" "));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_in_chunks_rejects_unsplittable_formats() {
+        let provider = MockProvider::new(vec![]);
+        let result = transcribe_in_chunks(
+            &provider,
+            b"RIFF....WAVE",
+            "memo.wav",
+            "en",
+            1024,
+            &|_, _| {},
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(TranscriptionError::InvalidAudioFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_verbose_json_with_segments_and_words() {
+        let body = r#"{
+            "text": "Hello world. How are you?",
+            "segments": [
+                {"id": 0, "start": 0.0, "end": 1.2, "text": " Hello world."},
+                {"id": 1, "start": 1.2, "end": 2.5, "text": " How are you?"}
+            ],
+            "words": [
+                {"word": "Hello", "start": 0.0, "end": 0.5},
+                {"word": "world", "start": 0.5, "end": 1.1},
+                {"word": "How", "start": 1.3, "end": 1.5},
+                {"word": "are", "start": 1.5, "end": 1.8},
+                {"word": "you", "start": 1.8, "end": 2.4}
+            ]
+        }"#;
+
+        let result = parse_whisper_response(200, body).unwrap();
+        let segments = result.segments.unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "Hello world.");
+        assert_eq!(segments[1].start, 1.2);
+
+        let first_words = segments[0].words.as_ref().unwrap();
+        assert_eq!(first_words.len(), 2);
+        assert_eq!(first_words[1].word, "world");
+        assert_eq!(segments[1].words.as_ref().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_plain_json_has_no_segments() {
+        let result = parse_whisper_response(200, r#"{"text":"Hi"}"#).unwrap();
+        assert!(result.segments.is_none());
+    }
+
+    #[test]
+    fn test_segment_serialization_omits_missing_words() {
+        let segment = TranscriptionSegment {
+            start: 1.0,
+            end: 2.0,
+            text: "Hi".to_string(),
+            words: None,
+        };
+        let json = serde_json::to_string(&segment).unwrap();
+        assert_eq!(json, r#"{"start":1.0,"end":2.0,"text":"Hi"}"#);
+    }
+
+    #[test]
+    fn test_restore_original_timing() {
+        let removed = vec![
+            SilenceRange {
+                start: 0.0,
+                end: 4.0,
+            },
+            SilenceRange {
+                start: 10.0,
+                end: 30.0,
+            },
+        ];
+        let result = TranscriptionResult {
+            text: "Hello there. Bye.".to_string(),
+            segments: Some(vec![
+                TranscriptionSegment {
+                    start: 0.0,
+                    end: 2.5,
+                    text: "Hello there.".to_string(),
+                    words: Some(vec![TranscriptionWord {
+                        word: "there".to_string(),
+                        start: 1.0,
+                        end: 2.5,
+                    }]),
+                },
+                TranscriptionSegment {
+                    start: 6.5,
+                    end: 7.0,
+                    text: "Bye.".to_string(),
+                    words: None,
+                },
+            ]),
+            removed_silence: Vec::new(),
+        };
+
+        let restored = restore_original_timing(result, removed.clone());
+
+        let segments = restored.segments.unwrap();
+        assert_eq!((segments[0].start, segments[0].end), (4.0, 6.5));
+        let word = &segments[0].words.as_ref().unwrap()[0];
+        assert_eq!((word.start, word.end), (5.0, 6.5));
+        assert_eq!((segments[1].start, segments[1].end), (30.5, 31.0));
+        assert_eq!(restored.text, "Hello there. Bye.");
+        assert_eq!(restored.removed_silence, removed);
+    }
+
+    #[test]
+    fn test_merge_chunk_segments_offsets_and_drops_overlap() {
+        let segment = |start: f64, end: f64, text: &str| TranscriptionSegment {
+            start,
+            end,
+            text: text.to_string(),
+            words: None,
+        };
+        let results = vec![
+            TranscriptionResult {
+                text: String::new(),
+                segments: Some(vec![segment(0.0, 8.0, "one"), segment(8.0, 14.0, "two")]),
+                removed_silence: Vec::new(),
+            },
+            TranscriptionResult {
+                text: String::new(),
+                // Chunk starts at 10 s: "two" is repeated in the overlap
+                segments: Some(vec![segment(0.0, 4.0, "two"), segment(4.0, 9.0, "three")]),
+                removed_silence: Vec::new(),
+            },
+        ];
+
+        let merged = merge_chunk_segments(&results, &[0.0, 10.0]).unwrap();
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["one", "two", "three"]);
+        assert_eq!(merged[2].start, 14.0);
+        assert_eq!(merged[2].end, 19.0);
+    }
+
+    #[test]
+    fn test_merge_chunk_segments_requires_all_chunks() {
+        let results = vec![
+            TranscriptionResult {
+                text: String::new(),
+                segments: Some(Vec::new()),
+                removed_silence: Vec::new(),
+            },
+            TranscriptionResult {
+                text: String::new(),
+                segments: None,
+                removed_silence: Vec::new(),
+            },
+        ];
+        assert!(merge_chunk_segments(&results, &[0.0, 10.0]).is_none());
+    }
 }
@@ -0,0 +1,25 @@
+//! Small formatting helpers shared by several modules.
+
+/// Format a duration as `M:SS` or `H:MM:SS`
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as u64;
+    let (hours, minutes, secs) = (total / 3600, (total / 60) % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.0), "0:00");
+        assert_eq!(format_duration(65.4), "1:05");
+        assert_eq!(format_duration(3725.6), "1:02:06");
+        assert_eq!(format_duration(-3.0), "0:00");
+    }
+}
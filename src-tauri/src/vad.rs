@@ -0,0 +1,279 @@
+//! Voice-activity detection and silence trimming before transcription.
+//!
+//! Speech is told apart from silence by frame loudness relative to the
+//! recording's own noise floor, so the same thresholds work for a quiet
+//! room and a noisy street. Long silences are cut out, which saves
+//! transcription minutes and keeps Whisper from hallucinating text into
+//! them. The removed ranges are kept so timestamps from the shortened audio
+//! can be mapped back to the original file.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Length of the frames whose loudness is measured
+const FRAME_MS: usize = 30;
+
+/// Frames this much louder than the noise floor count as speech
+const SPEECH_MARGIN_DB: f32 = 10.0;
+
+/// Frames within this much of the loudest frame always count as speech,
+/// for recordings with too few pauses to measure the noise floor
+const PEAK_MARGIN_DB: f32 = 25.0;
+
+/// Frames quieter than this never count as speech
+const MIN_SPEECH_DB: f32 = -55.0;
+
+/// Louder runs shorter than this are clicks or bumps rather than speech
+const MIN_SPEECH_MS: usize = 90;
+
+/// Silence kept before and after speech so word onsets and endings survive
+const SPEECH_PADDING_MS: usize = 250;
+
+/// Silences shorter than this (after padding) are normal pauses and kept
+const MIN_SILENCE_MS: usize = 1_000;
+
+/// A stretch of silence removed from a recording, in seconds of the original
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SilenceRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Find the sample ranges containing speech, padded and merged across short pauses
+///
+/// Returns an empty list when nothing in the recording is loud enough to be speech.
+pub fn detect_speech(samples: &[f32], sample_rate: u32) -> Vec<Range<usize>> {
+    let ms_to_samples = |ms: usize| sample_rate as usize * ms / 1000;
+    let frame_len = ms_to_samples(FRAME_MS).max(1);
+    let levels: Vec<f32> = samples.chunks(frame_len).map(level_db).collect();
+    if levels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = levels.clone();
+    sorted.sort_by(f32::total_cmp);
+    let noise_floor = sorted[sorted.len() / 10];
+    let peak = sorted[sorted.len() - 1];
+    let threshold = (noise_floor + SPEECH_MARGIN_DB)
+        .min(peak - PEAK_MARGIN_DB)
+        .max(MIN_SPEECH_DB);
+
+    // Runs of loud frames, as frame index ranges
+    let min_frames = MIN_SPEECH_MS.div_ceil(FRAME_MS);
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    for (index, &level) in levels.iter().chain([f32::MIN].iter()).enumerate() {
+        match (level >= threshold, run_start) {
+            (true, None) => run_start = Some(index),
+            (false, Some(start)) => {
+                if index - start >= min_frames {
+                    runs.push(start..index);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let padding = ms_to_samples(SPEECH_PADDING_MS);
+    let min_silence = ms_to_samples(MIN_SILENCE_MS);
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for run in runs {
+        let start = (run.start * frame_len).saturating_sub(padding);
+        let end = (run.end * frame_len + padding).min(samples.len());
+        match regions.last_mut() {
+            Some(last) if start < last.end + min_silence => last.end = end,
+            _ => regions.push(start..end),
+        }
+    }
+    regions
+}
+
+/// Cut long silences out of mono audio
+///
+/// Returns the remaining audio and the removed ranges, or `None` if there is
+/// no speech or no silence long enough to remove.
+pub fn remove_silence(samples: &[f32], sample_rate: u32) -> Option<(Vec<f32>, Vec<SilenceRange>)> {
+    let regions = detect_speech(samples, sample_rate);
+    if regions.is_empty() {
+        return None;
+    }
+
+    let seconds = |sample: usize| sample as f64 / f64::from(sample_rate);
+    let mut kept = Vec::with_capacity(regions.iter().map(|r| r.len()).sum());
+    let mut removed = Vec::new();
+    let mut position = 0;
+    for region in regions {
+        if region.start > position {
+            removed.push(SilenceRange {
+                start: seconds(position),
+                end: seconds(region.start),
+            });
+        }
+        kept.extend_from_slice(&samples[region.clone()]);
+        position = region.end;
+    }
+    if position < samples.len() {
+        removed.push(SilenceRange {
+            start: seconds(position),
+            end: seconds(samples.len()),
+        });
+    }
+
+    if removed.is_empty() {
+        None
+    } else {
+        Some((kept, removed))
+    }
+}
+
+/// Map a time in the shortened audio back to the original recording
+///
+/// `removed` must be in order, as returned by `remove_silence`.
+pub fn to_original_time(removed: &[SilenceRange], time: f64) -> f64 {
+    let mut original = time;
+    for range in removed {
+        if range.start > original {
+            break;
+        }
+        original += range.end - range.start;
+    }
+    original
+}
+
+/// Loudness of a frame in dBFS
+fn level_db(frame: &[f32]) -> f32 {
+    let power = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+    // Floor digital silence at -100 dB instead of negative infinity
+    10.0 * power.max(1e-10).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    /// A 200 Hz tone at the given amplitude
+    fn tone(seconds: f64, amplitude: f32) -> Vec<f32> {
+        let len = (seconds * f64::from(RATE)) as usize;
+        (0..len)
+            .map(|i| (i as f32 * 200.0 * std::f32::consts::TAU / RATE as f32).sin() * amplitude)
+            .collect()
+    }
+
+    /// Low deterministic noise
+    fn noise(seconds: f64) -> Vec<f32> {
+        let len = (seconds * f64::from(RATE)) as usize;
+        (0..len)
+            .map(|i| ((i * 7919 % 1000) as f32 / 1000.0 - 0.5) * 0.002)
+            .collect()
+    }
+
+    fn concat(parts: &[Vec<f32>]) -> Vec<f32> {
+        parts.concat()
+    }
+
+    #[test]
+    fn test_detects_speech_between_silences() {
+        let samples = concat(&[
+            noise(3.0),
+            tone(2.0, 0.3),
+            noise(4.0),
+            tone(1.0, 0.3),
+            noise(2.0),
+        ]);
+
+        let regions = detect_speech(&samples, RATE);
+
+        let seconds: Vec<(f64, f64)> = regions
+            .iter()
+            .map(|r| (r.start as f64 / 16_000.0, r.end as f64 / 16_000.0))
+            .collect();
+        assert_eq!(seconds.len(), 2, "{:?}", seconds);
+        assert!((seconds[0].0 - 2.75).abs() < 0.05, "{:?}", seconds);
+        assert!((seconds[0].1 - 5.25).abs() < 0.05, "{:?}", seconds);
+        assert!((seconds[1].0 - 8.75).abs() < 0.05, "{:?}", seconds);
+        assert!((seconds[1].1 - 10.25).abs() < 0.05, "{:?}", seconds);
+    }
+
+    #[test]
+    fn test_keeps_short_pauses_and_ignores_clicks() {
+        let samples = concat(&[
+            tone(1.0, 0.3),
+            noise(0.8),
+            tone(1.0, 0.3),
+            noise(2.0),
+            tone(0.03, 0.9),
+            noise(2.0),
+        ]);
+
+        let regions = detect_speech(&samples, RATE);
+
+        assert_eq!(regions.len(), 1, "{:?}", regions);
+        assert_eq!(regions[0].start, 0);
+        assert!((regions[0].end as f64 / 16_000.0 - 3.05).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_quiet_speaker_in_continuous_speech() {
+        // No pauses, so the noise floor is speech; the peak margin still applies
+        let samples = concat(&[tone(3.0, 0.02), tone(3.0, 0.3)]);
+
+        assert_eq!(detect_speech(&samples, RATE), vec![0..samples.len()]);
+    }
+
+    #[test]
+    fn test_silent_recording_has_no_speech() {
+        assert!(detect_speech(&vec![0.0; 32_000], RATE).is_empty());
+        assert!(detect_speech(&noise(2.0), RATE).is_empty());
+        assert!(detect_speech(&[], RATE).is_empty());
+        assert!(remove_silence(&noise(2.0), RATE).is_none());
+    }
+
+    #[test]
+    fn test_remove_silence_records_removed_ranges() {
+        let samples = concat(&[
+            noise(3.0),
+            tone(2.0, 0.3),
+            noise(4.0),
+            tone(1.0, 0.3),
+            noise(2.0),
+        ]);
+
+        let (kept, removed) = remove_silence(&samples, RATE).unwrap();
+
+        assert_eq!(removed.len(), 3, "{:?}", removed);
+        let removed_samples: f64 = removed.iter().map(|r| r.end - r.start).sum::<f64>() * 16_000.0;
+        assert_eq!(kept.len() + removed_samples.round() as usize, samples.len());
+        assert_eq!(removed[0].start, 0.0);
+        assert_eq!(removed[2].end, 12.0);
+    }
+
+    #[test]
+    fn test_remove_silence_without_long_silence() {
+        let samples = concat(&[tone(1.0, 0.3), noise(0.5), tone(1.0, 0.3)]);
+        assert!(remove_silence(&samples, RATE).is_none());
+    }
+
+    #[test]
+    fn test_to_original_time() {
+        let removed = [
+            SilenceRange {
+                start: 0.0,
+                end: 2.0,
+            },
+            SilenceRange {
+                start: 5.0,
+                end: 8.0,
+            },
+        ];
+
+        assert_eq!(to_original_time(&removed, 0.0), 2.0);
+        assert_eq!(to_original_time(&removed, 1.5), 3.5);
+        assert_eq!(to_original_time(&removed, 2.9), 4.9);
+        assert_eq!(to_original_time(&removed, 4.0), 9.0);
+        assert_eq!(to_original_time(&[], 4.0), 4.0);
+    }
+}
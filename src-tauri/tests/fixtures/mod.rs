@@ -0,0 +1,34 @@
+//! Helpers shared by the unit tests: the files in `tests/fixtures`, scratch
+//! directories and generated audio.
+
+use std::path::PathBuf;
+
+/// Path of a file in `tests/fixtures/audio`
+pub fn audio_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/audio")
+        .join(name)
+}
+
+/// Create an empty scratch directory, unique to the calling test
+pub fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("evervoice-{}-{}", prefix, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Load a file from `tests/fixtures/audio`
+pub fn audio(name: &str) -> Vec<u8> {
+    let path = audio_path(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+/// A tone at the given frequency and amplitude
+pub fn tone(sample_rate: u32, seconds: f32, frequency: f32, amplitude: f32) -> Vec<f32> {
+    let len = (seconds * sample_rate as f32) as usize;
+    (0..len)
+        .map(|i| {
+            (i as f32 * frequency * std::f32::consts::TAU / sample_rate as f32).sin() * amplitude
+        })
+        .collect()
+}
@@ -37,6 +37,8 @@ vi.mock('@/hooks', () => ({
     isSaving: false,
   })),
   useGlobalHotkey: vi.fn(),
+  useJobUpdates: vi.fn(),
+  usePendingTranscriptions: vi.fn(),
 }));
 
 // Mock tauri-api
@@ -90,6 +92,7 @@ vi.mock('@/stores', () => ({
       summaryError: null,
       summarySourceText: null,
       setSummary: vi.fn(),
+      appendSummary: vi.fn(),
       setSummaryState: vi.fn(),
       setSummaryError: vi.fn(),
     };
@@ -9,10 +9,17 @@ import {
   TranscriptionDisplay,
   HistorySidebar,
 } from "@/components";
-import { useAudioRecorder, useRecordingDuration, useFileStorage, useGlobalHotkey, useToast } from "@/hooks";
+import { useAudioRecorder, useRecordingDuration, useFileStorage, useGlobalHotkey, useJobUpdates, usePendingTranscriptions, useToast } from "@/hooks";
 import { useSettingsStore, useRecordingStore, useHistoryStore, getSelectedRecording } from "@/stores";
 import { Toaster } from "@/components/ui/toaster";
-import { transcribeAudio, summarizeText, updateHistorySummary } from "@/lib/tauri-api";
+import {
+  transcribeAudio,
+  summarizeText,
+  updateHistorySummary,
+  type JobUpdate,
+  type PendingTranscriptionEvent,
+} from "@/lib/tauri-api";
+import type { ResponseAction } from "@/types";
 
 /** Tab type for controlled tab switching */
 type TabType = 'transcription' | 'summary';
@@ -80,6 +87,7 @@ export default function Home() {
   // Summary store
   const summary = useRecordingStore((state) => state.summary);
   const setSummary = useRecordingStore((state) => state.setSummary);
+  const appendSummary = useRecordingStore((state) => state.appendSummary);
   const summaryState = useRecordingStore((state) => state.summaryState);
   const setSummaryState = useRecordingStore((state) => state.setSummaryState);
   const summaryError = useRecordingStore((state) => state.summaryError);
@@ -172,7 +180,20 @@ export default function Home() {
     let transcribedText: string;
 
     try {
-      const response = await transcribeAudio(recordingFilePath);
+      const response = await transcribeAudio(recordingFilePath, recordingDuration);
+
+      if (response.queued) {
+        // Offline: the backend transcribes it and saves it to history later
+        setTranscriptionState('idle');
+        setRecordingState('idle');
+        reset(); // Reset the audio recorder hook state
+        isProcessingRef.current = false;
+        toast({
+          title: 'Transcription queued',
+          description: 'The recording will be transcribed when the connection is back.',
+        });
+        return;
+      }
 
       if (!response.success || !response.text) {
         setTranscriptionError({
@@ -192,7 +213,12 @@ export default function Home() {
       setTranscriptionState('success');
 
       // Save to history after successful transcription
-      await addRecordingToHistory(recordingFilePath, recordingDuration, transcribedText);
+      await addRecordingToHistory(
+        recordingFilePath,
+        recordingDuration,
+        transcribedText,
+        response.segments
+      );
 
     } catch (error) {
       setTranscriptionError({
@@ -207,12 +233,20 @@ export default function Home() {
       return;
     }
 
-    // Phase 2: Summarization
+    // Phase 2: Summarization, shown in the Summary tab as it streams in
+    setSummary(null, transcribedText);
     setSummaryState('loading');
     setSummaryError(null);
 
     try {
-      const generatedSummary = await summarizeText(transcribedText);
+      let streaming = false;
+      const generatedSummary = await summarizeText(transcribedText, (delta) => {
+        appendSummary(delta);
+        if (!streaming) {
+          streaming = true;
+          setActiveTab('summary');
+        }
+      });
 
       // Store summary in the recording store
       setSummary(generatedSummary, transcribedText);
@@ -246,6 +280,7 @@ export default function Home() {
 
     } catch (error) {
       const message = error instanceof Error ? error.message : 'Failed to summarize text';
+      setSummary(null);
       setSummaryState('error');
       setSummaryError(message);
       toast({
@@ -261,7 +296,7 @@ export default function Home() {
     }
   }, [
     setTranscriptionState, setTranscriptionError, setTranscription,
-    setSummaryState, setSummaryError, setSummary,
+    setSummaryState, setSummaryError, setSummary, appendSummary,
     setRecordingState, reset,
     addRecordingToHistory, loadHistory, toast
   ]);
@@ -273,13 +308,72 @@ export default function Home() {
         const saved = await saveRecording(audioResult);
         if (saved) {
           setFilePath(saved.filePath);
-          // Auto-start processing after saving
-          processRecording(saved.filePath, elapsedSeconds);
+
+          if (saved.jobId) {
+            // The automatic pipeline transcribes the recording and adds it to
+            // history itself; show its history item while it runs
+            await loadHistory();
+            selectRecording(saved.historyId);
+            setRecordingState('idle');
+            reset(); // Reset the audio recorder hook state
+            toast({
+              title: 'Processing started',
+              description: 'The recording is being processed in the background.',
+            });
+          } else {
+            // Auto-start processing after saving
+            processRecording(saved.filePath, elapsedSeconds);
+          }
         }
       }
     };
     saveAndProcess();
-  }, [recorderState, audioResult, filePath, isSaving, saveRecording, setFilePath, processRecording, elapsedSeconds]);
+  }, [
+    recorderState, audioResult, filePath, isSaving, saveRecording, setFilePath, processRecording, elapsedSeconds,
+    loadHistory, selectRecording, setRecordingState, reset, toast
+  ]);
+
+  // Refresh history as background pipelines fill in their recordings
+  const handleJobUpdate = useCallback((job: JobUpdate) => {
+    if (job.kind !== 'pipeline' || job.status === 'queued') return;
+
+    loadHistory();
+    if (job.status === 'failed' && job.errorType === 'queued_offline') {
+      // The pending queue transcribes it and resumes the pipeline once online
+      toast({
+        title: 'Processing paused',
+        description: job.errorMessage ?? 'The recording will be processed when the connection is back.',
+      });
+    } else if (job.status === 'failed') {
+      toast({
+        title: 'Processing failed',
+        description: job.errorMessage ?? 'The pipeline could not be completed.',
+        variant: 'destructive',
+      });
+    }
+  }, [loadHistory, toast]);
+
+  useJobUpdates(handleJobUpdate);
+
+  // Show recordings transcribed from the offline queue
+  const handlePendingTranscription = useCallback((event: PendingTranscriptionEvent) => {
+    if (event.success) {
+      loadHistory();
+      toast({
+        title: 'Queued transcription complete',
+        description: 'The recording has been transcribed and added to history.',
+        variant: 'success',
+      });
+    } else {
+      toast({
+        title: 'Queued transcription failed',
+        description: event.errorMessage ?? 'The recording could not be transcribed.',
+        variant: 'destructive',
+      });
+    }
+  }, [loadHistory, toast]);
+
+  usePendingTranscriptions(handlePendingTranscription);
 
   // Determine what transcription to display
   // Priority: selected history item > current recording transcription
@@ -290,8 +384,9 @@ export default function Home() {
 
   // Determine what summary to display
   // Priority: current summary in store > saved summary in selected recording
-  // This ensures freshly generated summaries are shown immediately
-  const hasFreshSummary = summary !== null && summaryState === 'success';
+  // This ensures freshly generated summaries are shown immediately, and
+  // partial ones while they stream in
+  const hasFreshSummary = summary !== null && (summaryState === 'success' || summaryState === 'loading');
   const hasSelectedRecordingWithSummary = selectedRecording?.summary !== undefined && selectedRecording?.summary !== null;
 
   // Use fresh summary first (just generated), then fall back to persisted summary
@@ -307,6 +402,18 @@ export default function Home() {
     ? summaryError
     : (hasSelectedRecordingWithSummary ? null : summaryError);
 
+  // Show the text a custom action stored on the history item
+  const handleActionResult = useCallback(async (action: ResponseAction, result: string) => {
+    if (action === 'replaceSummary') {
+      setSummary(result, displayTranscription);
+      setSummaryState('success');
+      setActiveTab('summary');
+    } else {
+      setTranscription(result);
+    }
+    await loadHistory();
+  }, [displayTranscription, setSummary, setSummaryState, setTranscription, loadHistory]);
+
   return (
     <div className="flex h-screen bg-[#0a0a0a]">
       {/* Main Content Area */}
@@ -399,6 +506,8 @@ export default function Home() {
               transcriptionState={displayTranscriptionState}
               transcription={displayTranscription}
               error={selectedRecording ? null : transcriptionError}
+              historyId={selectedRecordingId}
+              onActionResult={handleActionResult}
               onRetry={selectedRecording ? undefined : undefined}
               summary={displaySummary}
               summaryState={displaySummaryState}
@@ -4,7 +4,8 @@ import { useState } from 'react';
 import { Loader2, Send } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { useToast } from '@/hooks/use-toast';
-import { callExternalService } from '@/lib/tauri-api';
+import { openUrl, runCustomAction } from '@/lib/tauri-api';
+import type { ResponseAction } from '@/types';
 import { cn } from '@/lib/utils';
 
 /**
@@ -13,10 +14,14 @@ import { cn } from '@/lib/utils';
 export interface CustomActionButtonProps {
   /** The display name for the button */
   name: string;
-  /** The URL endpoint to POST the transcription to */
-  url: string;
+  /** The ID of the custom action in settings */
+  actionId: string;
   /** The transcription text to send */
   transcription: string;
+  /** History item the text belongs to, for the action's other placeholders */
+  historyId?: string | null;
+  /** Called with the new text when the action replaced the transcription or summary */
+  onResult?: (action: ResponseAction, result: string) => void;
   /** Whether the button is disabled */
   disabled?: boolean;
   /** Additional CSS classes */
@@ -26,14 +31,17 @@ export interface CustomActionButtonProps {
 /**
  * Button component that posts transcription text to a custom external service.
  *
- * - POSTs transcription as JSON to configured URL
+ * - Runs the configured action (method, auth, body template) with the transcription
  * - Shows per-button loading state during request
+ * - Opens returned links and reports replaced transcriptions or summaries
  * - Displays toast notifications for success/error states
  */
 export function CustomActionButton({
   name,
-  url,
+  actionId,
   transcription,
+  historyId = null,
+  onResult,
   disabled = false,
   className,
 }: CustomActionButtonProps) {
@@ -46,12 +54,34 @@ export function CustomActionButton({
     setIsLoading(true);
 
     try {
-      const response = await callExternalService(url, transcription);
+      const response = await runCustomAction(actionId, transcription, historyId);
 
       if (response.success) {
+        const result = response.message;
+        let description = result ?? 'Action completed successfully.';
+
+        switch (response.response_action) {
+          case 'openUrl':
+            if (result) {
+              await openUrl(result);
+              description = 'Opened the link in your browser.';
+            }
+            break;
+          case 'replaceTranscription':
+          case 'replaceSummary':
+            if (result) {
+              onResult?.(response.response_action, result);
+              description =
+                response.response_action === 'replaceSummary'
+                  ? 'The summary was replaced.'
+                  : 'The transcription was replaced.';
+            }
+            break;
+        }
+
         toast({
           title: `${name} completed`,
-          description: response.message ?? 'Action completed successfully.',
+          description,
           variant: 'success',
         });
       } else {
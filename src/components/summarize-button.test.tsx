@@ -71,6 +71,51 @@ describe('SummarizeButton Store Integration', () => {
     });
   });
 
+  it('streams partial summary text into the recording store', async () => {
+    const user = userEvent.setup();
+    let finish: (summary: string) => void = () => {};
+
+    mockSummarizeText.mockImplementation((_text, onDelta) => {
+      onDelta?.('## Test');
+      onDelta?.(' Summary');
+      return new Promise((resolve) => {
+        finish = resolve;
+      });
+    });
+
+    render(<SummarizeButton transcription="Test transcription text" />);
+    await user.click(screen.getByTestId('summarize-button'));
+
+    expect(useRecordingStore.getState().summary).toBe('## Test Summary');
+    expect(useRecordingStore.getState().summaryState).toBe('loading');
+    expect(screen.getByText('Summarizing...')).toBeInTheDocument();
+
+    finish('## Test Summary\n- Point 1');
+    await waitFor(() => {
+      expect(useRecordingStore.getState().summary).toBe('## Test Summary\n- Point 1');
+      expect(useRecordingStore.getState().summaryState).toBe('success');
+    });
+  });
+
+  it('discards a partial summary when summarization fails', async () => {
+    const user = userEvent.setup();
+
+    mockSummarizeText.mockImplementation(async (_text, onDelta) => {
+      onDelta?.('## Partial');
+      throw new Error('Connection lost');
+    });
+
+    render(<SummarizeButton transcription="Test transcription text" />);
+    await user.click(screen.getByTestId('summarize-button'));
+
+    await waitFor(() => {
+      const state = useRecordingStore.getState();
+      expect(state.summaryState).toBe('error');
+      expect(state.summary).toBeNull();
+    });
+    expect(screen.getByText('Summarize')).toBeInTheDocument();
+  });
+
   it('button shows "Copy Summary" after summary exists in store', async () => {
     const user = userEvent.setup();
     const testSummary = '## Test Summary';
@@ -27,6 +27,7 @@ export interface SummarizeButtonProps {
  * Button component that summarizes transcription text and stores in the recording store.
  *
  * - Calls OpenAI API via Tauri backend to generate markdown summary
+ * - Streams the summary into the recording store as it is generated
  * - Shows loading state during API call using store's summaryState
  * - Stores summary in recording store for display in Summary tab
  * - Persists summary to history store for later retrieval
@@ -47,6 +48,7 @@ export function SummarizeButton({
     summary,
     summaryState,
     setSummary,
+    appendSummary,
     setSummaryState,
     setSummaryError,
   } = useRecordingStore();
@@ -55,7 +57,7 @@ export function SummarizeButton({
   const loadHistory = useHistoryStore((state) => state.loadHistory);
 
   const isLoading = summaryState === 'loading';
-  const hasSummary = summary !== null;
+  const hasSummary = summary !== null && !isLoading;
 
   const handleSummarize = async () => {
     if (!transcription || isLoading) return;
@@ -64,8 +66,8 @@ export function SummarizeButton({
     setSummaryError(null);
 
     try {
-      // Call the Tauri backend to summarize
-      const generatedSummary = await summarizeText(transcription);
+      // Call the Tauri backend to summarize, showing the summary as it streams in
+      const generatedSummary = await summarizeText(transcription, appendSummary);
 
       // Store summary in the recording store with source text reference
       setSummary(generatedSummary, transcription);
@@ -100,6 +102,7 @@ export function SummarizeButton({
       onSummaryComplete?.();
     } catch (error) {
       const message = error instanceof Error ? error.message : 'Failed to summarize text';
+      setSummary(null);
       setSummaryState('error');
       setSummaryError(message);
       toast({
@@ -10,7 +10,8 @@ import * as tauriApi from '@/lib/tauri-api';
 // Mock the Tauri API
 vi.mock('@/lib/tauri-api', () => ({
   summarizeText: vi.fn(),
-  callExternalService: vi.fn(),
+  runCustomAction: vi.fn(),
+  openUrl: vi.fn(),
   isTauri: vi.fn(() => true),
 }));
 
@@ -121,7 +122,7 @@ describe('SummarizeButton', () => {
     await user.click(button);
 
     // Check that summarize API was called with the transcription
-    expect(tauriApi.summarizeText).toHaveBeenCalledWith('Test transcription text');
+    expect(tauriApi.summarizeText).toHaveBeenCalledWith('Test transcription text', expect.any(Function));
 
     // Wait for the async operation to complete and verify success toast
     // Updated: Toast message changed to reflect summary generation
@@ -173,8 +174,8 @@ describe('CustomActionButton', () => {
     vi.resetAllMocks();
   });
 
-  it('posts to configured URL and shows success toast', async () => {
-    vi.mocked(tauriApi.callExternalService).mockResolvedValueOnce({
+  it('runs the configured action and shows success toast', async () => {
+    vi.mocked(tauriApi.runCustomAction).mockResolvedValueOnce({
       success: true,
       message: 'Successfully sent to Notion',
     });
@@ -184,8 +185,9 @@ describe('CustomActionButton', () => {
     render(
       <CustomActionButton
         name="Send to Notion"
-        url="https://api.notion.com/v1/pages"
+        actionId="1"
         transcription="Test transcription text"
+        historyId="recording-1"
       />
     );
 
@@ -194,9 +196,10 @@ describe('CustomActionButton', () => {
 
     await user.click(button);
 
-    expect(tauriApi.callExternalService).toHaveBeenCalledWith(
-      'https://api.notion.com/v1/pages',
-      'Test transcription text'
+    expect(tauriApi.runCustomAction).toHaveBeenCalledWith(
+      '1',
+      'Test transcription text',
+      'recording-1'
     );
 
     await waitFor(() => {
@@ -210,7 +213,7 @@ describe('CustomActionButton', () => {
   });
 
   it('shows error toast on failure', async () => {
-    vi.mocked(tauriApi.callExternalService).mockResolvedValueOnce({
+    vi.mocked(tauriApi.runCustomAction).mockResolvedValueOnce({
       success: false,
       message: 'Network error',
     });
@@ -220,7 +223,7 @@ describe('CustomActionButton', () => {
     render(
       <CustomActionButton
         name="Send to API"
-        url="https://api.example.com"
+        actionId="2"
         transcription="Test text"
       />
     );
@@ -238,4 +241,61 @@ describe('CustomActionButton', () => {
       );
     });
   });
+
+  it('opens a returned link', async () => {
+    vi.mocked(tauriApi.runCustomAction).mockResolvedValueOnce({
+      success: true,
+      message: 'https://notion.so/page-123',
+      response_action: 'openUrl',
+    });
+
+    const user = userEvent.setup();
+
+    render(
+      <CustomActionButton name="Send to Notion" actionId="1" transcription="Test text" />
+    );
+
+    await user.click(screen.getByTestId('custom-action-button-send-to-notion'));
+
+    await waitFor(() => {
+      expect(tauriApi.openUrl).toHaveBeenCalledWith('https://notion.so/page-123');
+      expect(mockToast).toHaveBeenCalledWith(
+        expect.objectContaining({
+          title: 'Send to Notion completed',
+          description: 'Opened the link in your browser.',
+        })
+      );
+    });
+  });
+
+  it('passes replaced text to onResult', async () => {
+    vi.mocked(tauriApi.runCustomAction).mockResolvedValueOnce({
+      success: true,
+      message: 'Cleaned up summary',
+      response_action: 'replaceSummary',
+    });
+    const onResult = vi.fn();
+
+    const user = userEvent.setup();
+
+    render(
+      <CustomActionButton
+        name="Send to API"
+        actionId="2"
+        transcription="Test text"
+        historyId="recording-1"
+        onResult={onResult}
+      />
+    );
+
+    await user.click(screen.getByTestId('custom-action-button-send-to-api'));
+
+    await waitFor(() => {
+      expect(onResult).toHaveBeenCalledWith('replaceSummary', 'Cleaned up summary');
+      expect(mockToast).toHaveBeenCalledWith(
+        expect.objectContaining({ description: 'The summary was replaced.' })
+      );
+    });
+    expect(tauriApi.openUrl).not.toHaveBeenCalled();
+  });
 });
@@ -12,7 +12,7 @@ import { useSettingsStore } from '@/stores/settings-store';
 
 // Mock the tauri-api module
 vi.mock('@/lib/tauri-api', () => ({
-  callExternalService: vi.fn(() => Promise.resolve({ success: true })),
+  runCustomAction: vi.fn(() => Promise.resolve({ success: true })),
   isTauri: vi.fn(() => false),
 }));
 
@@ -98,9 +98,9 @@ describe('TranscriptionDisplay Button Integration', () => {
   });
 
   it('custom actions in Transcription tab receive transcription text', async () => {
-    const { callExternalService } = await import('@/lib/tauri-api');
-    const mockCallExternalService = vi.mocked(callExternalService);
-    mockCallExternalService.mockResolvedValue({ success: true });
+    const { runCustomAction } = await import('@/lib/tauri-api');
+    const mockRunCustomAction = vi.mocked(runCustomAction);
+    mockRunCustomAction.mockResolvedValue({ success: true });
 
     const user = userEvent.setup();
     const transcriptionText = 'This is the transcription text to send';
@@ -119,16 +119,13 @@ describe('TranscriptionDisplay Button Integration', () => {
     await user.click(notionButton);
 
     // Verify the correct text was passed
-    expect(mockCallExternalService).toHaveBeenCalledWith(
-      'https://api.notion.com/v1/pages',
-      transcriptionText
-    );
+    expect(mockRunCustomAction).toHaveBeenCalledWith('action-1', transcriptionText, null);
   });
 
   it('custom actions in Summary tab receive summary text (not transcription)', async () => {
-    const { callExternalService } = await import('@/lib/tauri-api');
-    const mockCallExternalService = vi.mocked(callExternalService);
-    mockCallExternalService.mockResolvedValue({ success: true });
+    const { runCustomAction } = await import('@/lib/tauri-api');
+    const mockRunCustomAction = vi.mocked(runCustomAction);
+    mockRunCustomAction.mockResolvedValue({ success: true });
 
     const user = userEvent.setup();
     const transcriptionText = 'This is the transcription';
@@ -154,10 +151,7 @@ describe('TranscriptionDisplay Button Integration', () => {
     await user.click(notionButton);
 
     // Verify the summary text was passed, not the transcription
-    expect(mockCallExternalService).toHaveBeenCalledWith(
-      'https://api.notion.com/v1/pages',
-      summaryText
-    );
+    expect(mockRunCustomAction).toHaveBeenCalledWith('action-1', summaryText, null);
   });
 
   it('has consistent layout between Transcription and Summary tabs', async () => {
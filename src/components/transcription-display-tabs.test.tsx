@@ -155,6 +155,30 @@ describe('TranscriptionDisplay Tabbed Interface', () => {
     expect(screen.getByText('Generating summary...')).toBeInTheDocument();
   });
 
+  it('shows a summary that is still streaming in', async () => {
+    const user = userEvent.setup();
+
+    render(
+      <TranscriptionDisplay
+        transcriptionState="success"
+        transcription="Test transcription text"
+        error={null}
+        summary={'## Key Points\n- First'}
+        summaryState="loading"
+      />
+    );
+
+    await user.click(screen.getByRole('tab', { name: 'Summary' }));
+
+    const streaming = screen.getByTestId('summary-streaming');
+    expect(streaming.querySelector('h2')).toHaveTextContent('Key Points');
+    expect(streaming).toHaveTextContent('First');
+    expect(screen.getByLabelText('Generating summary')).toBeInTheDocument();
+    expect(screen.queryByText('Generating summary...')).not.toBeInTheDocument();
+    // Copy and custom actions only appear once the summary is complete
+    expect(screen.queryByTestId('summary-content')).not.toBeInTheDocument();
+  });
+
   it('shows error state in Summary tab when summaryState is error', async () => {
     const user = userEvent.setup();
     const onSummaryRetry = vi.fn();
@@ -8,7 +8,7 @@ import { CopyTextButton } from './copy-text-button';
 import { CustomActionButton } from './custom-action-button';
 import { useToast } from '@/hooks/use-toast';
 import { useSettingsStore } from '@/stores/settings-store';
-import type { TranscriptionState, TranscriptionError, SummaryState } from '@/types';
+import type { TranscriptionState, TranscriptionError, SummaryState, ResponseAction } from '@/types';
 import { cn } from '@/lib/utils';
 
 /** Tab type for the tabbed interface */
@@ -24,6 +24,10 @@ export interface TranscriptionDisplayProps {
   transcription: string | null;
   /** Transcription error (if failed) */
   error: TranscriptionError | null;
+  /** History item being shown, passed to custom actions */
+  historyId?: string | null;
+  /** Handler for custom actions that replaced the transcription or summary */
+  onActionResult?: (action: ResponseAction, result: string) => void;
   /** Handler for retry button click */
   onRetry?: () => void;
   /** AI-generated summary markdown text */
@@ -58,6 +62,8 @@ export function TranscriptionDisplay({
   transcriptionState,
   transcription,
   error,
+  historyId = null,
+  onActionResult,
   onRetry,
   summary = null,
   summaryState = 'idle',
@@ -332,8 +338,10 @@ export function TranscriptionDisplay({
               <CustomActionButton
                 key={action.id}
                 name={action.name}
-                url={action.url}
+                actionId={action.id}
                 transcription={transcription}
+                historyId={historyId}
+                onResult={onActionResult}
               />
             ))}
           </div>
@@ -348,8 +356,22 @@ export function TranscriptionDisplay({
           hidden={activeTab !== 'summary'}
           tabIndex={0}
         >
+          {/* Summary streaming in */}
+          {summaryState === 'loading' && summary && (
+            <div
+              className="max-h-64 overflow-y-auto px-4 py-3 text-sm leading-relaxed text-zinc-300 [&_h1]:text-base [&_h1]:font-bold [&_h1]:mb-2 [&_h2]:text-sm [&_h2]:font-semibold [&_h2]:mb-2 [&_h3]:text-sm [&_h3]:font-medium [&_h3]:mb-1 [&_ul]:list-disc [&_ul]:pl-5 [&_ul]:space-y-1 [&_ol]:list-decimal [&_ol]:pl-5 [&_ol]:space-y-1 [&_li]:text-zinc-300 [&_p]:mb-2 [&_strong]:font-semibold [&_strong]:text-zinc-200"
+              data-testid="summary-streaming"
+            >
+              <ReactMarkdown>{summary}</ReactMarkdown>
+              <Loader2
+                className="h-4 w-4 animate-spin text-green-400"
+                aria-label="Generating summary"
+              />
+            </div>
+          )}
+
           {/* Loading state */}
-          {summaryState === 'loading' && (
+          {summaryState === 'loading' && !summary && (
             <div className="flex items-center justify-center px-4 py-8">
               <Loader2
                 className="h-6 w-6 animate-spin text-green-400"
@@ -434,8 +456,10 @@ export function TranscriptionDisplay({
                   <CustomActionButton
                     key={action.id}
                     name={action.name}
-                    url={action.url}
+                    actionId={action.id}
                     transcription={summary}
+                    historyId={historyId}
+                    onResult={onActionResult}
                   />
                 ))}
               </div>
@@ -6,3 +6,5 @@ export * from './use-transcription-history';
 export * from './use-toast';
 export * from './use-audio-playback';
 export * from './use-global-hotkey';
+export * from './use-job-updates';
+export * from './use-pending-transcriptions';
@@ -38,6 +38,9 @@ describe('useFileStorage', () => {
     duration: 5000,
   });
 
+  // Backend save result without an automatic pipeline
+  const savedFile = (filePath: string) => ({ filePath, historyId: null, jobId: null });
+
   beforeEach(() => {
     vi.clearAllMocks();
     mockIsTauri.mockReturnValue(true);
@@ -66,7 +69,7 @@ describe('useFileStorage', () => {
   describe('saveRecording', () => {
     it('should save recording and return saved recording with file path', async () => {
       const expectedFilePath = '/path/to/recordings/recording-2024-01-21T14-30-00-abc123.webm';
-      mockSaveRecording.mockResolvedValue(expectedFilePath);
+      mockSaveRecording.mockResolvedValue(savedFile(expectedFilePath));
 
       const { result } = renderHook(() => useFileStorage());
       const mockRecording = createMockRecording();
@@ -77,18 +80,37 @@ describe('useFileStorage', () => {
       });
 
       expect(mockEnsureDirectoryExists).toHaveBeenCalled();
-      expect(mockSaveRecording).toHaveBeenCalledWith(mockRecording.blob);
+      expect(mockSaveRecording).toHaveBeenCalledWith(mockRecording.blob, 5);
       expect(saved).not.toBeNull();
       expect(saved?.filePath).toBe(expectedFilePath);
       expect(saved?.mimeType).toBe('audio/webm');
       expect(saved?.duration).toBe(5000);
       expect(saved?.savedAt).toBeDefined();
+      expect(saved?.jobId).toBeNull();
       expect(result.current.savedRecording).toEqual(saved);
     });
 
+    it('should return the history item and job of an automatic pipeline', async () => {
+      mockSaveRecording.mockResolvedValue({
+        filePath: '/path/to/file.webm',
+        historyId: 'history-1',
+        jobId: 'job-1',
+      });
+
+      const { result } = renderHook(() => useFileStorage());
+
+      let saved: Awaited<ReturnType<typeof result.current.saveRecording>>;
+      await act(async () => {
+        saved = await result.current.saveRecording(createMockRecording());
+      });
+
+      expect(saved?.historyId).toBe('history-1');
+      expect(saved?.jobId).toBe('job-1');
+    });
+
     it('should set isSaving to true during save operation', async () => {
-      let resolveSave: (value: string) => void;
-      const savePromise = new Promise<string>((resolve) => {
+      let resolveSave: (value: ReturnType<typeof savedFile>) => void;
+      const savePromise = new Promise<ReturnType<typeof savedFile>>((resolve) => {
         resolveSave = resolve;
       });
       mockSaveRecording.mockReturnValue(savePromise);
@@ -107,7 +129,7 @@ describe('useFileStorage', () => {
 
       // Complete the save
       await act(async () => {
-        resolveSave!('/path/to/file.webm');
+        resolveSave!(savedFile('/path/to/file.webm'));
         await savePromise;
       });
 
@@ -157,7 +179,7 @@ describe('useFileStorage', () => {
   describe('deleteRecording', () => {
     it('should delete recording and clear savedRecording if it matches', async () => {
       const filePath = '/path/to/recordings/recording-2024-01-21T14-30-00-abc123.webm';
-      mockSaveRecording.mockResolvedValue(filePath);
+      mockSaveRecording.mockResolvedValue(savedFile(filePath));
       mockDeleteRecording.mockResolvedValue(undefined);
 
       const { result } = renderHook(() => useFileStorage());
@@ -241,7 +263,7 @@ describe('useFileStorage', () => {
 
   describe('clearSavedRecording', () => {
     it('should clear the saved recording state', async () => {
-      mockSaveRecording.mockResolvedValue('/path/to/file.webm');
+      mockSaveRecording.mockResolvedValue(savedFile('/path/to/file.webm'));
 
       const { result } = renderHook(() => useFileStorage());
 
@@ -98,14 +98,19 @@ export function useFileStorage(): UseFileStorageReturn {
         // Ensure the recordings directory exists
         await ensureDirectoryExists();
 
-        // Save the recording and get the file path
-        const filePath = await saveRecordingApi(recording.blob);
+        // Save the recording; this may also start the automatic pipeline
+        const { filePath, historyId, jobId } = await saveRecordingApi(
+          recording.blob,
+          recording.duration / 1000
+        );
 
         // Create the saved recording object
         const saved: SavedRecording = {
           ...recording,
           filePath,
           savedAt: new Date().toISOString(),
+          historyId,
+          jobId,
         };
 
         setSavedRecording(saved);
@@ -0,0 +1,49 @@
+'use client';
+
+import { useEffect, useRef } from 'react';
+import { listenForJobUpdates, isTauri, type JobUpdate } from '@/lib/tauri-api';
+
+/**
+ * Hook that calls `onJobUpdate` for every background job state change.
+ *
+ * Jobs (transcriptions, summaries and pipelines started by the backend) keep
+ * running when no command is waiting for them; their progress arrives as
+ * `job-updated` events. The listener is removed on unmount.
+ *
+ * @param onJobUpdate - Callback receiving the updated job
+ */
+export function useJobUpdates(onJobUpdate: (job: JobUpdate) => void) {
+  // Keep the latest callback without re-subscribing on every render
+  const onJobUpdateRef = useRef(onJobUpdate);
+
+  useEffect(() => {
+    onJobUpdateRef.current = onJobUpdate;
+  }, [onJobUpdate]);
+
+  useEffect(() => {
+    if (!isTauri()) {
+      return;
+    }
+
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+
+    listenForJobUpdates((job) => onJobUpdateRef.current(job))
+      .then((cleanup) => {
+        // The component may have unmounted while the listener was registering
+        if (cancelled) {
+          cleanup();
+        } else {
+          unlisten = cleanup;
+        }
+      })
+      .catch((error) => {
+        console.error('Failed to set up job update listener:', error);
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+}
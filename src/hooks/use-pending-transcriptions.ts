@@ -0,0 +1,53 @@
+'use client';
+
+import { useEffect, useRef } from 'react';
+import {
+  listenForPendingTranscriptions,
+  isTauri,
+  type PendingTranscriptionEvent,
+} from '@/lib/tauri-api';
+
+/**
+ * Hook that calls `onCompleted` whenever a queued transcription finishes.
+ *
+ * Recordings that couldn't be transcribed while offline are retried by the
+ * backend once the network is back; each outcome arrives as a
+ * `pending-transcription-completed` event. The listener is removed on unmount.
+ *
+ * @param onCompleted - Callback receiving the outcome of the queued transcription
+ */
+export function usePendingTranscriptions(onCompleted: (event: PendingTranscriptionEvent) => void) {
+  // Keep the latest callback without re-subscribing on every render
+  const onCompletedRef = useRef(onCompleted);
+
+  useEffect(() => {
+    onCompletedRef.current = onCompleted;
+  }, [onCompleted]);
+
+  useEffect(() => {
+    if (!isTauri()) {
+      return;
+    }
+
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+
+    listenForPendingTranscriptions((event) => onCompletedRef.current(event))
+      .then((cleanup) => {
+        // The component may have unmounted while the listener was registering
+        if (cancelled) {
+          cleanup();
+        } else {
+          unlisten = cleanup;
+        }
+      })
+      .catch((error) => {
+        console.error('Failed to set up pending transcription listener:', error);
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+}
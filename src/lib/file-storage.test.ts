@@ -96,24 +96,25 @@ describe('File Storage API', () => {
       const mockInvoke = await getMockedInvoke();
       const expectedFilePath =
         'C:\\Users\\test\\AppData\\Roaming\\EverVoice\\recordings\\recording-2024-01-21T14-30-00-abc123.webm';
-      mockInvoke.mockResolvedValue(expectedFilePath);
+      mockInvoke.mockResolvedValue({ file_path: expectedFilePath, history_id: null, job_id: null });
 
       // Create a mock blob with arrayBuffer method
       const testData = new Uint8Array([1, 2, 3, 4, 5]);
       const blob = createMockBlob(testData);
 
-      const result = await saveRecording(blob);
+      const result = await saveRecording(blob, 12.5);
 
       expect(mockInvoke).toHaveBeenCalledWith('save_recording', {
         data: [1, 2, 3, 4, 5],
+        durationSeconds: 12.5,
       });
-      expect(result).toBe(expectedFilePath);
+      expect(result).toEqual({ filePath: expectedFilePath, historyId: null, jobId: null });
     });
 
     it('should handle empty blob', async () => {
       const mockInvoke = await getMockedInvoke();
       const expectedFilePath = '/path/to/recording.webm';
-      mockInvoke.mockResolvedValue(expectedFilePath);
+      mockInvoke.mockResolvedValue({ file_path: expectedFilePath, history_id: null, job_id: null });
 
       // Create an empty mock blob
       const blob = createMockBlob(new Uint8Array([]));
@@ -122,8 +123,26 @@ describe('File Storage API', () => {
 
       expect(mockInvoke).toHaveBeenCalledWith('save_recording', {
         data: [],
+        durationSeconds: null,
+      });
+      expect(result.filePath).toBe(expectedFilePath);
+    });
+
+    it('should return the IDs of an automatic pipeline', async () => {
+      const mockInvoke = await getMockedInvoke();
+      mockInvoke.mockResolvedValue({
+        file_path: '/path/to/recording.ogg',
+        history_id: 'history-1',
+        job_id: 'job-1',
+      });
+
+      const result = await saveRecording(createMockBlob(new Uint8Array([1])));
+
+      expect(result).toEqual({
+        filePath: '/path/to/recording.ogg',
+        historyId: 'history-1',
+        jobId: 'job-1',
       });
-      expect(result).toBe(expectedFilePath);
     });
   });
 
@@ -0,0 +1,93 @@
+import { describe, it, expect, beforeEach, vi, afterEach } from 'vitest';
+import { summarizeText } from './tauri-api';
+
+// Mock the @tauri-apps/api modules
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(),
+}));
+
+type Listener = (event: { payload: Record<string, unknown> }) => void;
+
+describe('Summarization API', () => {
+  let originalWindow: typeof globalThis.window;
+  let listeners: Map<string, Listener>;
+  let unlisten: ReturnType<typeof vi.fn>;
+
+  /** Deliver a backend event to the registered listener */
+  const emit = (name: string, payload: Record<string, unknown>) => {
+    listeners.get(name)?.({ payload });
+  };
+
+  beforeEach(async () => {
+    vi.clearAllMocks();
+    originalWindow = globalThis.window;
+    // @ts-expect-error - Mocking window.__TAURI__
+    globalThis.window = { __TAURI__: {} };
+
+    listeners = new Map();
+    unlisten = vi.fn();
+    const { listen } = await import('@tauri-apps/api/event');
+    vi.mocked(listen).mockImplementation(async (name, handler) => {
+      listeners.set(name, handler as unknown as Listener);
+      return unlisten;
+    });
+  });
+
+  afterEach(() => {
+    globalThis.window = originalWindow;
+    vi.resetAllMocks();
+  });
+
+  it('streams chunks of its own job and resolves with the complete summary', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockImplementation(async (_command, args) => {
+      const jobId = (args as { jobId: string }).jobId;
+      emit('summary-chunk', { job_id: 'other-job', delta: 'Not mine' });
+      emit('summary-chunk', { job_id: jobId, delta: '## Sum' });
+      emit('summary-chunk', { job_id: jobId, delta: 'mary' });
+      emit('summary-complete', {
+        job_id: jobId,
+        history_id: null,
+        success: true,
+        summary: '## Summary',
+        error_type: null,
+        error_message: null,
+      });
+    });
+    const deltas: string[] = [];
+
+    const summary = await summarizeText('Some text', (delta) => deltas.push(delta));
+
+    expect(summary).toBe('## Summary');
+    expect(deltas).toEqual(['## Sum', 'mary']);
+    expect(invoke).toHaveBeenCalledWith('summarize_transcription_stream', {
+      jobId: expect.any(String),
+      text: 'Some text',
+    });
+    expect(unlisten).toHaveBeenCalledTimes(2);
+  });
+
+  it('rejects with the error of a summary that fails right away', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    // Completion is emitted before the command returns
+    vi.mocked(invoke).mockImplementation(async (_command, args) => {
+      emit('summary-complete', {
+        job_id: (args as { jobId: string }).jobId,
+        history_id: null,
+        success: false,
+        summary: null,
+        error_type: 'api_key_not_configured',
+        error_message: 'API key not configured. Please add your OpenAI API key in Settings.',
+      });
+    });
+
+    await expect(summarizeText('Some text')).rejects.toThrow(
+      'API key not configured. Please add your OpenAI API key in Settings.'
+    );
+    expect(unlisten).toHaveBeenCalledTimes(2);
+  });
+});
@@ -1,4 +1,12 @@
-import type { Settings, TranscriptionResponse, TranscriptionError, Recording, RawRecording } from '@/types';
+import type {
+  Settings,
+  TranscriptionResponse,
+  TranscriptionError,
+  TranscriptionSegment,
+  Recording,
+  RawRecording,
+  ResponseAction,
+} from '@/types';
 
 /**
  * Check if running in Tauri environment
@@ -139,6 +147,95 @@ export async function listenForGlobalHotkey(
   return unlisten;
 }
 
+// ============================================================================
+// Background Jobs API
+// ============================================================================
+
+/**
+ * Snapshot of a background job, as sent with `job-updated` events
+ */
+export interface JobUpdate {
+  id: string;
+  kind: 'transcription' | 'summarization' | 'pipeline';
+  status: 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+  /** Fraction of the work done, from 0 to 1 */
+  progress: number;
+  /** History item the job reads from or writes to */
+  historyId: string | null;
+  result: string | null;
+  errorType: string | null;
+  errorMessage: string | null;
+  createdAt: string;
+}
+
+/**
+ * Listen for background job updates from the backend
+ *
+ * @param callback - Function to call on every job state change
+ * @returns Cleanup function to unsubscribe from the event
+ */
+export async function listenForJobUpdates(callback: (job: JobUpdate) => void): Promise<() => void> {
+  if (!isTauri()) {
+    // Return a no-op cleanup function in non-Tauri environments
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<JobUpdate>('job-updated', (event) => {
+    callback(event.payload);
+  });
+}
+
+/**
+ * Raw pending transcription event from Rust backend (uses snake_case)
+ */
+interface RawPendingTranscriptionEvent {
+  file_path: string;
+  success: boolean;
+  history_id: string | null;
+  text: string | null;
+  error_message: string | null;
+}
+
+/**
+ * Outcome of a queued transcription that ran once the network was back
+ */
+export interface PendingTranscriptionEvent {
+  filePath: string;
+  success: boolean;
+  /** History item the transcription was saved to (if successful) */
+  historyId: string | null;
+  text: string | null;
+  errorMessage: string | null;
+}
+
+/**
+ * Listen for queued transcriptions finishing in the background
+ *
+ * @param callback - Function to call when a queued transcription succeeds or fails
+ * @returns Cleanup function to unsubscribe from the event
+ */
+export async function listenForPendingTranscriptions(
+  callback: (event: PendingTranscriptionEvent) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    // Return a no-op cleanup function in non-Tauri environments
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<RawPendingTranscriptionEvent>('pending-transcription-completed', (event) => {
+    const raw = event.payload;
+    callback({
+      filePath: raw.file_path,
+      success: raw.success,
+      historyId: raw.history_id,
+      text: raw.text,
+      errorMessage: raw.error_message,
+    });
+  });
+}
+
 // ============================================================================
 // File Storage API
 // ============================================================================
@@ -163,20 +260,58 @@ export async function ensureDirectoryExists(): Promise<string> {
 }
 
 /**
- * Save a recording blob to disk and return the full file path
+ * Result of saving a recording
+ */
+export interface SaveRecordingResult {
+  /** The full file path where the recording was saved */
+  filePath: string;
+  /** History item created for the automatic pipeline, if one was started */
+  historyId: string | null;
+  /** Background job running the automatic pipeline, if one was started */
+  jobId: string | null;
+}
+
+/**
+ * Raw save result from the Rust backend (snake_case)
+ */
+interface RawSaveRecordingResult {
+  file_path: string;
+  history_id: string | null;
+  job_id: string | null;
+}
+
+/**
+ * Save a recording blob to disk
  *
  * The file is saved with a unique name containing an ISO timestamp and UUID:
  * `recording-{YYYY-MM-DDTHH-mm-ss}-{uuid}.webm`
  *
+ * When settings select an automatic pipeline, the backend also creates the
+ * history item and starts the pipeline, and returns their IDs. The recording
+ * must then not be transcribed or added to history again.
+ *
  * @param blob - The audio Blob from MediaRecorder
- * @returns The full file path where the recording was saved
+ * @param durationSeconds - Recording duration, stored with the history item (optional)
+ * @returns The saved file path, plus the history item and job IDs of a started pipeline
  */
-export async function saveRecording(blob: Blob): Promise<string> {
+export async function saveRecording(
+  blob: Blob,
+  durationSeconds: number | null = null
+): Promise<SaveRecordingResult> {
   // Convert Blob to ArrayBuffer, then to Uint8Array for Tauri
   const arrayBuffer = await blob.arrayBuffer();
   const data = Array.from(new Uint8Array(arrayBuffer));
 
-  return invokeCommand<string>('save_recording', { data });
+  const raw = await invokeCommand<RawSaveRecordingResult>('save_recording', {
+    data,
+    durationSeconds,
+  });
+
+  return {
+    filePath: raw.file_path,
+    historyId: raw.history_id,
+    jobId: raw.job_id,
+  };
 }
 
 /**
@@ -265,9 +400,11 @@ export function parseFileStorageError(error: unknown): FileStorageError {
 interface RawTranscriptionResponse {
   success: boolean;
   text: string | null;
+  segments: TranscriptionSegment[] | null;
   error_type: string | null;
   error_message: string | null;
   retryable: boolean | null;
+  queued: boolean;
 }
 
 /**
@@ -279,29 +416,43 @@ interface RawTranscriptionResponse {
  * 3. Call OpenAI Whisper API with exponential backoff retry
  * 4. Return the transcription text or a structured error
  *
+ * When the network is unreachable the recording is queued instead (`queued`),
+ * and a `pending-transcription-completed` event follows once it is transcribed.
+ *
  * @param filePath - Full path to the audio file to transcribe
+ * @param durationSeconds - Duration of the recording, saved with a queued transcription
  * @returns A TranscriptionResponse containing either the transcribed text or error details
  */
-export async function transcribeAudio(filePath: string): Promise<TranscriptionResponse> {
+export async function transcribeAudio(
+  filePath: string,
+  durationSeconds: number | null = null
+): Promise<TranscriptionResponse> {
   if (!isTauri()) {
     return {
       success: false,
       text: null,
+      segments: null,
       errorType: 'unknown',
       errorMessage: 'Transcription is only available in the desktop app.',
       retryable: false,
+      queued: false,
     };
   }
 
-  const raw = await invokeCommand<RawTranscriptionResponse>('transcribe_audio', { filePath });
+  const raw = await invokeCommand<RawTranscriptionResponse>('transcribe_audio', {
+    filePath,
+    durationSeconds,
+  });
 
   // Convert snake_case from Rust to camelCase for TypeScript
   return {
     success: raw.success,
     text: raw.text,
+    segments: raw.segments ?? null,
     errorType: raw.error_type as TranscriptionResponse['errorType'],
     errorMessage: raw.error_message,
     retryable: raw.retryable,
+    queued: raw.queued ?? false,
   };
 }
 
@@ -382,17 +533,20 @@ export async function getHistory(): Promise<Recording[]> {
  * @param filePath - Full path to the audio file
  * @param durationSeconds - Recording duration in seconds
  * @param transcription - Transcribed text
+ * @param segments - Timestamped segments from the transcription, used for subtitle exports
  * @returns The ID of the newly created recording
  */
 export async function saveRecordingHistory(
   filePath: string,
   durationSeconds: number,
-  transcription: string
+  transcription: string,
+  segments: TranscriptionSegment[] | null = null
 ): Promise<string> {
   return invokeCommand<string>('save_recording_history', {
     filePath,
     durationSeconds,
     transcription,
+    segments,
   });
 }
 
@@ -433,26 +587,83 @@ interface RawSummarizationResponse {
   error_message: string | null;
 }
 
+/**
+ * Raw payload of the `summary-chunk` event
+ */
+interface RawSummaryChunkEvent {
+  job_id: string;
+  delta: string;
+}
+
+/**
+ * Raw payload of the `summary-complete` event
+ */
+interface RawSummaryCompleteEvent extends RawSummarizationResponse {
+  job_id: string;
+  history_id: string | null;
+}
+
+/**
+ * Create an ID for a summarization job
+ */
+function createJobId(): string {
+  if (typeof crypto !== 'undefined' && crypto.randomUUID) {
+    return crypto.randomUUID();
+  }
+  return `summary-${Date.now()}-${Math.random().toString(36).slice(2)}`;
+}
+
 /**
  * Summarize transcription text using OpenAI GPT API
  *
+ * The summary is streamed: `onDelta` receives each piece of text as it is
+ * generated, so it can be shown before the whole summary is done.
+ *
  * @param text - The transcription text to summarize
+ * @param onDelta - Called with every newly generated piece of the summary (optional)
  * @returns Markdown-formatted summary
  * @throws Error if summarization fails
  */
-export async function summarizeText(text: string): Promise<string> {
+export async function summarizeText(
+  text: string,
+  onDelta?: (delta: string) => void
+): Promise<string> {
   if (!isTauri()) {
     throw new Error('Summarization is only available in the desktop app.');
   }
 
-  const response = await invokeCommand<RawSummarizationResponse>('summarize_transcription', { text });
+  // Subscribe before invoking so events of a summary that fails right away aren't missed
+  const jobId = createJobId();
+  const { listen } = await import('@tauri-apps/api/event');
+  let complete: (response: RawSummaryCompleteEvent) => void = () => {};
+  const completed = new Promise<RawSummaryCompleteEvent>((resolve) => {
+    complete = resolve;
+  });
+  const unlistenChunk = await listen<RawSummaryChunkEvent>('summary-chunk', (event) => {
+    if (event.payload.job_id === jobId) {
+      onDelta?.(event.payload.delta);
+    }
+  });
+  const unlistenComplete = await listen<RawSummaryCompleteEvent>('summary-complete', (event) => {
+    if (event.payload.job_id === jobId) {
+      complete(event.payload);
+    }
+  });
 
-  if (response.success && response.summary) {
-    return response.summary;
-  }
+  try {
+    await invokeCommand<void>('summarize_transcription_stream', { jobId, text });
+    const response = await completed;
+
+    if (response.success && response.summary) {
+      return response.summary;
+    }
 
-  // Throw error with user-friendly message
-  throw new Error(response.error_message || 'Summarization failed');
+    // Throw error with user-friendly message
+    throw new Error(response.error_message || 'Summarization failed');
+  } finally {
+    unlistenChunk();
+    unlistenComplete();
+  }
 }
 
 // ============================================================================
@@ -465,6 +676,8 @@ export async function summarizeText(text: string): Promise<string> {
 export interface ExternalServiceResponse {
   success: boolean;
   message?: string;
+  /** How `message` should be handled, for successful custom actions */
+  response_action?: ResponseAction | null;
 }
 
 /**
@@ -484,3 +697,39 @@ export async function callExternalService(url: string, text: string): Promise<Ex
 
   return invokeCommand<ExternalServiceResponse>('call_external_service', { url, text });
 }
+
+/**
+ * Run a custom action from settings
+ *
+ * The action is looked up by ID, so its method, headers, authentication and
+ * body template are all applied.
+ *
+ * @param actionId - The ID of the custom action to run
+ * @param text - The text to send, used for the `{transcription}` placeholder
+ * @param historyId - History item that fills the other placeholders, if any
+ * @returns Response indicating success or failure with optional message
+ */
+export async function runCustomAction(
+  actionId: string,
+  text: string,
+  historyId: string | null = null
+): Promise<ExternalServiceResponse> {
+  if (!isTauri()) {
+    return {
+      success: false,
+      message: 'External service calls are only available in the desktop app.',
+    };
+  }
+
+  return invokeCommand<ExternalServiceResponse>('run_custom_action', { actionId, text, historyId });
+}
+
+/**
+ * Open a link returned by a custom action in the default browser
+ *
+ * @param url - The http(s) URL to open
+ * @throws Error if the URL isn't a web URL or can't be opened
+ */
+export async function openUrl(url: string): Promise<void> {
+  return invokeCommand<void>('open_url', { url });
+}
@@ -0,0 +1,79 @@
+import { describe, it, expect, beforeEach, vi, afterEach } from 'vitest';
+import { transcribeAudio, listenForPendingTranscriptions } from './tauri-api';
+
+// Mock the @tauri-apps/api modules
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(),
+}));
+
+describe('Transcription API', () => {
+  let originalWindow: typeof globalThis.window;
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    originalWindow = globalThis.window;
+    // @ts-expect-error - Mocking window.__TAURI__
+    globalThis.window = { __TAURI__: {} };
+  });
+
+  afterEach(() => {
+    globalThis.window = originalWindow;
+    vi.resetAllMocks();
+  });
+
+  it('passes the duration and reports a queued transcription', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockResolvedValue({
+      success: false,
+      text: null,
+      segments: null,
+      error_type: 'network_error',
+      error_message: 'Network error',
+      retryable: true,
+      queued: true,
+    });
+
+    const response = await transcribeAudio('/recordings/a.webm', 42);
+
+    expect(invoke).toHaveBeenCalledWith('transcribe_audio', {
+      filePath: '/recordings/a.webm',
+      durationSeconds: 42,
+    });
+    expect(response.queued).toBe(true);
+    expect(response.errorType).toBe('network_error');
+  });
+
+  it('maps completed queued transcriptions to camelCase', async () => {
+    const { listen } = await import('@tauri-apps/api/event');
+    let handler: ((event: { payload: Record<string, unknown> }) => void) | undefined;
+    vi.mocked(listen).mockImplementation(async (_name, callback) => {
+      handler = callback as unknown as typeof handler;
+      return vi.fn();
+    });
+    const callback = vi.fn();
+
+    await listenForPendingTranscriptions(callback);
+    handler?.({
+      payload: {
+        file_path: '/recordings/a.webm',
+        success: true,
+        history_id: 'item-1',
+        text: 'Hello',
+        error_message: null,
+      },
+    });
+
+    expect(listen).toHaveBeenCalledWith('pending-transcription-completed', expect.any(Function));
+    expect(callback).toHaveBeenCalledWith({
+      filePath: '/recordings/a.webm',
+      success: true,
+      historyId: 'item-1',
+      text: 'Hello',
+      errorMessage: null,
+    });
+  });
+});
@@ -95,12 +95,36 @@ describe('History Store', () => {
       expect(mockSaveRecordingHistory).toHaveBeenCalledWith(
         '/path/to/new.webm',
         45,
-        'New transcription'
+        'New transcription',
+        null
       );
       // After adding, loadHistory is called to refresh the list
       expect(mockGetHistory).toHaveBeenCalled();
       expect(useHistoryStore.getState().recordings).toHaveLength(3);
     });
+
+    it('should persist transcription segments for subtitle exports', async () => {
+      mockSaveRecordingHistory.mockResolvedValue('new-uuid');
+      mockGetHistory.mockResolvedValue(mockRecordings);
+      const segments = [
+        { start: 0, end: 2.5, text: 'Hello' },
+        { start: 2.5, end: 4, text: 'world' },
+      ];
+
+      await useHistoryStore.getState().addRecording(
+        '/path/to/new.webm',
+        4,
+        'Hello world',
+        segments
+      );
+
+      expect(mockSaveRecordingHistory).toHaveBeenCalledWith(
+        '/path/to/new.webm',
+        4,
+        'Hello world',
+        segments
+      );
+    });
   });
 
   describe('selectRecording', () => {
@@ -1,5 +1,5 @@
 import { create } from 'zustand';
-import type { Recording } from '@/types';
+import type { Recording, TranscriptionSegment } from '@/types';
 import {
   getHistory,
   saveRecordingHistory,
@@ -33,7 +33,12 @@ interface HistoryStoreActions {
   /** Select a recording by ID for display */
   selectRecording: (id: string | null) => void;
   /** Add a new recording to history and persist */
-  addRecording: (filePath: string, durationSeconds: number, transcription: string) => Promise<void>;
+  addRecording: (
+    filePath: string,
+    durationSeconds: number,
+    transcription: string,
+    segments?: TranscriptionSegment[] | null
+  ) => Promise<void>;
   /** Delete a recording from history and persist */
   deleteRecording: (id: string) => Promise<void>;
   /** Delete all recordings from history */
@@ -109,7 +114,12 @@ export const useHistoryStore = create<HistoryStore>((set, get) => ({
     }
   },
 
-  addRecording: async (filePath: string, durationSeconds: number, transcription: string) => {
+  addRecording: async (
+    filePath: string,
+    durationSeconds: number,
+    transcription: string,
+    segments: TranscriptionSegment[] | null = null
+  ) => {
     // Skip if not in Tauri environment
     if (!isTauri()) {
       return;
@@ -119,7 +129,7 @@ export const useHistoryStore = create<HistoryStore>((set, get) => ({
 
     try {
       // Save and get the new recording ID
-      const newId = await saveRecordingHistory(filePath, durationSeconds, transcription);
+      const newId = await saveRecordingHistory(filePath, durationSeconds, transcription, segments);
       // Refresh the list to get the new recording with its server-generated ID
       await get().loadHistory();
       // Auto-select the new recording so summary can be persisted
@@ -59,6 +59,8 @@ interface RecordingStoreActions {
   setTranscriptionError: (error: TranscriptionError | null) => void;
   /** Set the summary text and its source transcription */
   setSummary: (summary: string | null, sourceText?: string | null) => void;
+  /** Append newly generated text to a summary that is still being streamed */
+  appendSummary: (delta: string) => void;
   /** Set the summary state */
   setSummaryState: (state: SummaryState) => void;
   /** Set the summary error message */
@@ -140,6 +142,10 @@ export const useRecordingStore = create<RecordingStore>((set) => ({
     set({ summary, summarySourceText: sourceText ?? null });
   },
 
+  appendSummary: (delta: string) => {
+    set((state) => ({ summary: (state.summary ?? '') + delta }));
+  },
+
   setSummaryState: (summaryState: SummaryState) => {
     set({ summaryState });
   },
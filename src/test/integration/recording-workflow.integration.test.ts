@@ -130,7 +130,11 @@ describe('Recording Workflow Integration Tests', () => {
       });
 
       // Simulate file save
-      mockSaveRecording.mockResolvedValue('/path/to/recording.webm');
+      mockSaveRecording.mockResolvedValue({
+        filePath: '/path/to/recording.webm',
+        historyId: null,
+        jobId: null,
+      });
       store.setFilePath('/path/to/recording.webm');
 
       // Verify pre-transcription state
@@ -273,7 +277,7 @@ describe('Recording Workflow Integration Tests', () => {
 
       // Simulate file save
       const expectedPath = '/recordings/recording-2024-01-21T10-30-00-uuid.webm';
-      mockSaveRecording.mockResolvedValue(expectedPath);
+      mockSaveRecording.mockResolvedValue({ filePath: expectedPath, historyId: null, jobId: null });
 
       // In real usage, the hook would call this after save
       recordingStore.setFilePath(expectedPath);
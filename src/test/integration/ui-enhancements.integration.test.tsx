@@ -28,7 +28,7 @@ vi.mock('@/lib/tauri-api', () => ({
   saveRecordingHistory: vi.fn(),
   deleteRecordingHistory: vi.fn(),
   summarizeText: vi.fn(),
-  callExternalService: vi.fn(),
+  runCustomAction: vi.fn(),
   transcribeAudio: vi.fn(),
   saveRecording: vi.fn(),
   ensureDirectoryExists: vi.fn(),
@@ -38,14 +38,14 @@ import {
   getHistory,
   saveRecordingHistory,
   summarizeText,
-  callExternalService,
+  runCustomAction,
   isTauri,
 } from '@/lib/tauri-api';
 
 const mockGetHistory = vi.mocked(getHistory);
 const mockSaveRecordingHistory = vi.mocked(saveRecordingHistory);
 const mockSummarizeText = vi.mocked(summarizeText);
-const mockCallExternalService = vi.mocked(callExternalService);
+const mockRunCustomAction = vi.mocked(runCustomAction);
 const mockIsTauri = vi.mocked(isTauri);
 
 // Test fixture data
@@ -127,7 +127,8 @@ describe('UI Enhancements Integration Tests', () => {
       expect(mockSaveRecordingHistory).toHaveBeenCalledWith(
         '/recordings/new-recording.webm',
         30,
-        'New transcription text'
+        'New transcription text',
+        null
       );
 
       // Verify the history store is updated
@@ -294,7 +295,7 @@ describe('UI Enhancements Integration Tests', () => {
       expect(state.settings.customActions[1].name).toBe('Post to Slack');
 
       // Setup mock for external service call
-      mockCallExternalService.mockResolvedValue({
+      mockRunCustomAction.mockResolvedValue({
         success: true,
         message: 'Message sent successfully',
       });
@@ -302,13 +303,10 @@ describe('UI Enhancements Integration Tests', () => {
       // Execute the first custom action
       const transcription = 'Test transcription to send';
       const firstAction = state.settings.customActions[0];
-      const result = await callExternalService(firstAction.url, transcription);
+      const result = await runCustomAction(firstAction.id, transcription);
 
       // Verify the call was made correctly
-      expect(mockCallExternalService).toHaveBeenCalledWith(
-        'https://api.notion.com/v1/pages',
-        transcription
-      );
+      expect(mockRunCustomAction).toHaveBeenCalledWith('action-1', transcription);
       expect(result.success).toBe(true);
     });
 
@@ -327,12 +325,12 @@ describe('UI Enhancements Integration Tests', () => {
     });
 
     it('should handle external service errors correctly', async () => {
-      mockCallExternalService.mockResolvedValue({
+      mockRunCustomAction.mockResolvedValue({
         success: false,
         message: 'Connection refused',
       });
 
-      const result = await callExternalService('https://example.com/api', 'test text');
+      const result = await runCustomAction('action-1', 'test text');
 
       expect(result.success).toBe(false);
       expect(result.message).toBe('Connection refused');
@@ -44,6 +44,10 @@ export interface SavedRecording extends AudioRecordingResult {
   filePath: string;
   /** ISO timestamp when the recording was saved */
   savedAt: string;
+  /** History item created for the automatic pipeline, if one was started */
+  historyId: string | null;
+  /** Background job running the automatic pipeline, if one was started */
+  jobId: string | null;
 }
 
 // ============================================================================
@@ -99,6 +103,30 @@ export type TranscriptionErrorType =
   | 'api_error'
   | 'unknown';
 
+/**
+ * Word-level timing within a transcription segment
+ */
+export interface TranscriptionWord {
+  word: string;
+  /** Start time in seconds from the beginning of the recording */
+  start: number;
+  /** End time in seconds from the beginning of the recording */
+  end: number;
+}
+
+/**
+ * A timestamped segment of a transcription
+ */
+export interface TranscriptionSegment {
+  /** Start time in seconds from the beginning of the recording */
+  start: number;
+  /** End time in seconds from the beginning of the recording */
+  end: number;
+  text: string;
+  /** Word-level timings (only when word timestamps are enabled) */
+  words?: TranscriptionWord[];
+}
+
 /**
  * Transcription response from the Rust backend
  */
@@ -107,12 +135,16 @@ export interface TranscriptionResponse {
   success: boolean;
   /** The transcribed text (if successful) */
   text: string | null;
+  /** Timestamped segments (if successful and returned by the provider) */
+  segments: TranscriptionSegment[] | null;
   /** Error type (if failed) */
   errorType: TranscriptionErrorType | null;
   /** User-friendly error message (if failed) */
   errorMessage: string | null;
   /** Whether the error is retryable */
   retryable: boolean | null;
+  /** Whether the recording was queued to be transcribed once the network is back */
+  queued: boolean;
 }
 
 /**
@@ -1,3 +1,13 @@
+/**
+ * What a custom action does with the result of its response
+ *
+ * - `show`: show the result in a notification
+ * - `replaceTranscription` / `replaceSummary`: the result was stored on the
+ *   history item and replaces the displayed text
+ * - `openUrl`: the result is a link to open in the browser
+ */
+export type ResponseAction = 'show' | 'replaceTranscription' | 'replaceSummary' | 'openUrl';
+
 /**
  * Custom action configuration for external service integration
  */